@@ -0,0 +1,117 @@
+//! The control-flow graphs in the Graphviz format, e.g. for
+//! `dot -Tsvg prog.dot -o prog.svg`. Every function is a cluster, and the
+//! blocks that can't be reached are dashed.
+
+use std::fmt::Write;
+
+use super::{Cfg, Item, Terminator};
+
+pub fn to_dot(cfgs: &[Cfg]) -> String {
+    let mut dot = String::from("digraph cfg {\n");
+    dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+    for cfg in cfgs {
+        write_cfg(&mut dot, cfg);
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn write_cfg(dot: &mut String, cfg: &Cfg) {
+    let _ = writeln!(dot, "    subgraph \"cluster_{}\" {{", cfg.name);
+    let _ = writeln!(dot, "        label={};", string(&cfg.name));
+
+    let reachable = cfg.reachable();
+    for (id, block) in cfg.blocks.iter().enumerate() {
+        let mut lines = vec![format!("bb{}: {}", id, block.kind)];
+        for item in &block.items {
+            match item {
+                Item::Declaration(decl) => lines.push(format!("{};", decl)),
+                Item::Expression(expr) => lines.push(format!("{};", expr)),
+            }
+        }
+        match &block.terminator {
+            Terminator::Branch(condition, ..) => lines.push(format!("if ({})", condition)),
+            Terminator::Return(expr) => lines.push(format!("return {};", expr)),
+            Terminator::Jump(_) | Terminator::Exit => {}
+        }
+        // The lines end with `\l` to align them to the left.
+        let label: String = lines.iter().map(|line| escape(line) + "\\l").collect();
+        let style = if reachable[id] { "" } else { ", style=dashed" };
+        let _ = writeln!(
+            dot,
+            "        {} [label=\"{}\"{}];",
+            node(cfg, id),
+            label,
+            style
+        );
+    }
+
+    for (id, block) in cfg.blocks.iter().enumerate() {
+        let successors = block.terminator.successors();
+        let labels: &[&str] = match block.terminator {
+            Terminator::Branch(..) => &["true", "false"],
+            _ => &[],
+        };
+        for (index, successor) in successors.into_iter().enumerate() {
+            let _ = write!(dot, "        {} -> {}", node(cfg, id), node(cfg, successor));
+            if let Some(label) = labels.get(index) {
+                let _ = write!(dot, " [label={}]", string(label));
+            }
+            dot.push_str(";\n");
+        }
+    }
+    dot.push_str("    }\n");
+}
+
+/// The node names have the function name, the clusters share the namespace.
+fn node(cfg: &Cfg, id: usize) -> String {
+    string(&format!("{}.bb{}", cfg.name, id))
+}
+
+/// A DOT string with the escapes.
+fn string(value: &str) -> String {
+    format!("\"{}\"", escape(value))
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cfg::CfgBuilder, parser::Parser, tokenizer::Tokenizer};
+
+    #[test]
+    fn test_to_dot() {
+        let src = "int main() { int a = 2; while (a > 0) a = a - 1; return a; }";
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        let cfgs = CfgBuilder::new().build(&program).unwrap();
+        assert_eq!(
+            to_dot(&cfgs),
+            r#"digraph cfg {
+    node [shape=box, fontname="monospace"];
+    subgraph "cluster_main" {
+        label="main";
+        "main.bb0" [label="bb0: entry\lint a = 2;\l"];
+        "main.bb1" [label="bb1: exit\l"];
+        "main.bb2" [label="bb2: while_start\lif (a > 0)\l"];
+        "main.bb3" [label="bb3: while_body\la = a - 1;\l"];
+        "main.bb4" [label="bb4: while_end\lreturn a;\l"];
+        "main.bb5" [label="bb5: unreachable\l", style=dashed];
+        "main.bb0" -> "main.bb2";
+        "main.bb2" -> "main.bb3" [label="true"];
+        "main.bb2" -> "main.bb4" [label="false"];
+        "main.bb3" -> "main.bb2";
+        "main.bb4" -> "main.bb1";
+        "main.bb5" -> "main.bb1";
+    }
+}
+"#
+        );
+    }
+}
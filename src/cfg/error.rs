@@ -0,0 +1,55 @@
+use thiserror::Error;
+
+use crate::tokenizer::Span;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CfgError {
+    #[error("'break' statement is not in a loop or switch statement")]
+    NoLoopFoundForBreak(Span),
+    #[error("'continue' statement is not in a loop or switch statement")]
+    NoLoopFoundForContinue(Span),
+
+    #[error("Can't build the control-flow graph of a statement that failed to parse")]
+    UnexpectedErrorNode,
+}
+
+impl CfgError {
+    /// The span of the statement, if the error is about one.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CfgError::NoLoopFoundForBreak(span) | CfgError::NoLoopFoundForContinue(span) => {
+                Some(*span)
+            }
+            CfgError::UnexpectedErrorNode => None,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CfgWarning {
+    #[error("Control reaches the end of non-void function {0:?} without a return")]
+    MissingReturn(String, Span),
+    /// The span is the one of the first statement that is never executed.
+    #[error("Function {0:?} has code after a return, break or continue that is never executed")]
+    UnreachableCode(String, Span),
+    /// The span is the one of the first read.
+    #[error("Variable {1:?} may be used uninitialized in function {0:?}")]
+    Uninitialized(String, String, Span),
+    /// The span is the one of the declaration.
+    #[error("Unused variable {1:?} in function {0:?}")]
+    UnusedVariable(String, String, Span),
+    #[error("Variable {1:?} is set but never read in function {0:?}")]
+    UnusedButSetVariable(String, String, Span),
+}
+
+impl CfgWarning {
+    pub fn span(&self) -> Span {
+        match self {
+            CfgWarning::MissingReturn(_, span)
+            | CfgWarning::Uninitialized(_, _, span)
+            | CfgWarning::UnusedVariable(_, _, span)
+            | CfgWarning::UnusedButSetVariable(_, _, span)
+            | CfgWarning::UnreachableCode(_, span) => *span,
+        }
+    }
+}
@@ -0,0 +1,605 @@
+//! Control-flow graphs of the functions, built from the AST.
+//!
+//! The statements are split into basic blocks the same way the codegen
+//! lowers them to labels and branches, e.g. a `for` loop has a block for the
+//! condition, the body, the increment and the code after the loop. The
+//! expressions stay in a single block, even `&&`, `||` and `?:`.
+
+mod dot;
+mod error;
+mod variables;
+
+pub use self::{
+    dot::to_dot,
+    error::{CfgError, CfgWarning},
+};
+use crate::{
+    fold::evaluate,
+    parser::ast::{Block, BlockItem, DeclOrExpr, Expr, Function, Program, Statement, VarDecl},
+    tokenizer::Span,
+};
+use std::mem;
+
+pub type CfgResult<T> = Result<T, CfgError>;
+
+pub type BlockId = usize;
+
+/// The block that the function starts with.
+pub const ENTRY: BlockId = 0;
+/// The block after the function body, the returns and the end of the body
+/// jump to it.
+pub const EXIT: BlockId = 1;
+/// Kind of the blocks that start after a return, a break or a continue.
+const UNREACHABLE: &str = "unreachable";
+
+#[derive(Debug, PartialEq)]
+pub struct Cfg {
+    /// Name of the function.
+    pub name: String,
+    /// Names of the parameters.
+    pub params: Vec<String>,
+    /// The span of the function name.
+    pub span: Span,
+    /// The blocks of the function, indexed by their ids.
+    pub blocks: Vec<BasicBlock>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BasicBlock {
+    /// What the block is for, e.g. `while_start` like the codegen labels.
+    pub kind: &'static str,
+    pub items: Vec<Item>,
+    pub terminator: Terminator,
+    /// The span of the first statement with code in the block.
+    pub span: Option<Span>,
+}
+
+/// A statement without control flow.
+#[derive(Debug, PartialEq)]
+pub enum Item {
+    Declaration(VarDecl),
+    Expression(Expr),
+}
+
+/// How a block ends.
+#[derive(Debug, PartialEq)]
+pub enum Terminator {
+    Jump(BlockId),
+    /// Go to the first block if the condition is true, to the second one otherwise.
+    Branch(Expr, BlockId, BlockId),
+    /// Return the value and go to the exit block.
+    Return(Expr),
+    /// The end of the exit block.
+    Exit,
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch(_, then_block, else_block) => vec![*then_block, *else_block],
+            Terminator::Return(_) => vec![EXIT],
+            Terminator::Exit => vec![],
+        }
+    }
+
+    /// The successors that can be taken. A branch on a constant condition
+    /// only goes one way, e.g. `while (1)`.
+    pub fn taken_successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Branch(condition, then_block, else_block) => match evaluate(condition) {
+                Some(value) if value.is_true() => vec![*then_block],
+                Some(_) => vec![*else_block],
+                None => vec![*then_block, *else_block],
+            },
+            terminator => terminator.successors(),
+        }
+    }
+}
+
+impl BasicBlock {
+    /// Whether the block runs any statements, the blocks that only jump don't.
+    fn has_code(&self) -> bool {
+        !self.items.is_empty()
+            || matches!(
+                self.terminator,
+                Terminator::Branch(..) | Terminator::Return(_)
+            )
+    }
+}
+
+impl Cfg {
+    /// The predecessors of every block.
+    pub fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let mut predecessors = vec![vec![]; self.blocks.len()];
+        for (id, block) in self.blocks.iter().enumerate() {
+            for successor in block.terminator.successors() {
+                predecessors[successor].push(id);
+            }
+        }
+        predecessors
+    }
+
+    /// Whether each block can be reached from the entry block.
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut stack = vec![ENTRY];
+        while let Some(id) = stack.pop() {
+            if !mem::replace(&mut reachable[id], true) {
+                stack.extend(self.blocks[id].terminator.taken_successors());
+            }
+        }
+        reachable
+    }
+
+    /// Whether the end of the function body can be reached, so the function
+    /// returns without a return statement.
+    pub fn falls_off_end(&self) -> bool {
+        let reachable = self.reachable();
+        self.blocks
+            .iter()
+            .zip(reachable)
+            .any(|(block, reachable)| reachable && block.terminator == Terminator::Jump(EXIT))
+    }
+
+    /// The span of the first statement after a return, a break or a
+    /// continue that is never executed, if there is one.
+    pub fn unreachable_code(&self) -> Option<Span> {
+        let reachable = self.reachable();
+        let mut visited = vec![false; self.blocks.len()];
+        // The blocks are popped in the order of the source.
+        let mut stack: Vec<BlockId> = (0..self.blocks.len())
+            .rev()
+            .filter(|&id| self.blocks[id].kind == UNREACHABLE)
+            .collect();
+        // The code that the unreachable blocks lead to is unreachable too,
+        // until it joins the rest of the function. The condition and the
+        // increment of a loop belong to the loop, e.g. `do { return 1; } while (0);`
+        // is fine.
+        while let Some(id) = stack.pop() {
+            let kind = self.blocks[id].kind;
+            if reachable[id]
+                || kind == "do_continue"
+                || kind == "for_continue"
+                || mem::replace(&mut visited[id], true)
+            {
+                continue;
+            }
+            if self.blocks[id].has_code() {
+                return self.blocks[id].span;
+            }
+            stack.extend(self.blocks[id].terminator.successors());
+        }
+        None
+    }
+
+    pub fn warnings(&self) -> Vec<CfgWarning> {
+        let mut warnings = vec![];
+        // `main` returns 0 if it reaches the end.
+        if self.name != "main" && self.falls_off_end() {
+            warnings.push(CfgWarning::MissingReturn(self.name.clone(), self.span));
+        }
+        if let Some(span) = self.unreachable_code() {
+            warnings.push(CfgWarning::UnreachableCode(self.name.clone(), span));
+        }
+        warnings.extend(variables::check(self));
+        warnings
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Loop {
+    /// Block that the continue statements jump to.
+    continue_block: BlockId,
+    /// Block that the break statements jump to.
+    end_block: BlockId,
+}
+
+/// Builds the control-flow graphs of the function definitions.
+#[derive(Default)]
+pub struct CfgBuilder {
+    blocks: Vec<BasicBlock>,
+    /// The block that the statements are added to.
+    current: BlockId,
+    loops: Vec<Loop>,
+    /// The span of the block item that is being built.
+    item_span: Span,
+}
+
+impl CfgBuilder {
+    pub fn new() -> CfgBuilder {
+        CfgBuilder::default()
+    }
+
+    pub fn build(mut self, program: &Program) -> CfgResult<Vec<Cfg>> {
+        program
+            .functions
+            .iter()
+            .filter(|func| func.is_definition())
+            .map(|func| self.build_function(func))
+            .collect()
+    }
+
+    pub fn build_function(&mut self, func: &Function) -> CfgResult<Cfg> {
+        self.blocks = vec![];
+        self.loops = vec![];
+        self.current = self.new_block("entry");
+        self.new_block("exit");
+
+        if let Some(ref body) = func.body {
+            self.build_block(body)?;
+        }
+        // The end of the body falls through to the exit.
+        self.blocks[self.current].terminator = Terminator::Jump(EXIT);
+
+        Ok(Cfg {
+            name: func.name.clone(),
+            params: func
+                .params
+                .iter()
+                .filter_map(|param| param.name.clone())
+                .collect(),
+            span: func.span,
+            blocks: mem::take(&mut self.blocks),
+        })
+    }
+
+    fn new_block(&mut self, kind: &'static str) -> BlockId {
+        self.blocks.push(BasicBlock {
+            kind,
+            items: vec![],
+            terminator: Terminator::Exit,
+            span: None,
+        });
+        self.blocks.len() - 1
+    }
+
+    /// End the current block and continue with the next one.
+    fn finish(&mut self, terminator: Terminator, next: BlockId) {
+        let block = &mut self.blocks[self.current];
+        block.terminator = terminator;
+        if block.has_code() {
+            block.span.get_or_insert(self.item_span);
+        }
+        self.current = next;
+    }
+
+    fn push_item(&mut self, item: Item) {
+        let block = &mut self.blocks[self.current];
+        block.items.push(item);
+        block.span.get_or_insert(self.item_span);
+    }
+
+    fn build_block(&mut self, block: &Block) -> CfgResult<()> {
+        let outer_span = self.item_span;
+        for item in &block.items {
+            match item {
+                BlockItem::Statement(stmt, span) => {
+                    self.item_span = *span;
+                    self.build_statement(stmt)?;
+                }
+                BlockItem::Declaration(decl) => {
+                    self.item_span = decl.span;
+                    self.push_item(Item::Declaration(decl.clone()));
+                }
+            }
+        }
+        self.item_span = outer_span;
+        Ok(())
+    }
+
+    fn build_statement(&mut self, stmt: &Statement) -> CfgResult<()> {
+        match stmt {
+            Statement::Block(block) => self.build_block(block)?,
+            Statement::Return(expr) => {
+                // The code after a return starts a block that nothing jumps to.
+                let next = self.new_block(UNREACHABLE);
+                self.finish(Terminator::Return(*expr.clone()), next);
+            }
+            Statement::Expression(expr) => self.build_expr(expr),
+            Statement::Conditional(cond) => {
+                let then_block = self.new_block("if_then");
+                let else_block = cond.else_stmt.as_ref().map(|_| self.new_block("if_else"));
+                let end_block = self.new_block("if_end");
+
+                let branch = Terminator::Branch(
+                    cond.condition.clone(),
+                    then_block,
+                    else_block.unwrap_or(end_block),
+                );
+                self.finish(branch, then_block);
+                self.build_statement(&cond.if_stmt)?;
+                if let (Some(else_stmt), Some(else_block)) = (&cond.else_stmt, else_block) {
+                    self.finish(Terminator::Jump(end_block), else_block);
+                    self.build_statement(else_stmt)?;
+                }
+                self.finish(Terminator::Jump(end_block), end_block);
+            }
+            Statement::While(condition, body) => {
+                let start_block = self.new_block("while_start");
+                let body_block = self.new_block("while_body");
+                let end_block = self.new_block("while_end");
+
+                self.finish(Terminator::Jump(start_block), start_block);
+                let branch = Terminator::Branch(*condition.clone(), body_block, end_block);
+                self.finish(branch, body_block);
+                self.build_loop_body(body, start_block, end_block)?;
+                self.finish(Terminator::Jump(start_block), end_block);
+            }
+            Statement::DoWhile(body, condition) => {
+                let start_block = self.new_block("do_start");
+                let continue_block = self.new_block("do_continue");
+                let end_block = self.new_block("do_end");
+
+                self.finish(Terminator::Jump(start_block), start_block);
+                self.build_loop_body(body, continue_block, end_block)?;
+                self.finish(Terminator::Jump(continue_block), continue_block);
+                let branch = Terminator::Branch(*condition.clone(), start_block, end_block);
+                self.finish(branch, end_block);
+            }
+            Statement::For(for_loop) => {
+                let start_block = self.new_block("for_start");
+                let body_block = self.new_block("for_body");
+                let continue_block = self.new_block("for_continue");
+                let end_block = self.new_block("for_end");
+
+                match &*for_loop.init {
+                    DeclOrExpr::Declaration(decl) => {
+                        self.push_item(Item::Declaration(decl.clone()))
+                    }
+                    DeclOrExpr::Expression(expr) => self.build_expr(expr),
+                }
+                self.finish(Terminator::Jump(start_block), start_block);
+                // A missing condition is always true.
+                let condition = match &*for_loop.condition {
+                    Expr::Null => Terminator::Jump(body_block),
+                    condition => Terminator::Branch(condition.clone(), body_block, end_block),
+                };
+                self.finish(condition, body_block);
+                self.build_loop_body(&for_loop.body, continue_block, end_block)?;
+                self.finish(Terminator::Jump(continue_block), continue_block);
+                self.build_expr(&for_loop.increment);
+                self.finish(Terminator::Jump(start_block), end_block);
+            }
+            Statement::Break(span) => {
+                let end_block = self
+                    .loops
+                    .last()
+                    .ok_or(CfgError::NoLoopFoundForBreak(*span))?
+                    .end_block;
+                let next = self.new_block(UNREACHABLE);
+                self.finish(Terminator::Jump(end_block), next);
+            }
+            Statement::Continue(span) => {
+                let continue_block = self
+                    .loops
+                    .last()
+                    .ok_or(CfgError::NoLoopFoundForContinue(*span))?
+                    .continue_block;
+                let next = self.new_block(UNREACHABLE);
+                self.finish(Terminator::Jump(continue_block), next);
+            }
+            Statement::Null => {}
+            Statement::Error => return Err(CfgError::UnexpectedErrorNode),
+        }
+        Ok(())
+    }
+
+    fn build_loop_body(
+        &mut self,
+        body: &Statement,
+        continue_block: BlockId,
+        end_block: BlockId,
+    ) -> CfgResult<()> {
+        self.loops.push(Loop {
+            continue_block,
+            end_block,
+        });
+        let result = self.build_statement(body);
+        self.loops.pop();
+        result
+    }
+
+    fn build_expr(&mut self, expr: &Expr) {
+        // The null expressions, e.g. a missing `for` increment, don't do anything.
+        if *expr != Expr::Null {
+            self.push_item(Item::Expression(expr.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::Parser, tokenizer::Tokenizer};
+
+    /// The kind and the successors of every block of the function.
+    fn build(src: &str) -> CfgResult<Vec<(&'static str, Vec<BlockId>)>> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        let cfgs = CfgBuilder::new().build(&program)?;
+        Ok(cfgs[0]
+            .blocks
+            .iter()
+            .map(|block| (block.kind, block.terminator.successors()))
+            .collect())
+    }
+
+    macro_rules! cfg_test {
+        ($name:ident, $src:expr => $expected:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(build($src).unwrap(), $expected);
+            }
+        };
+    }
+
+    cfg_test!(
+        test_straight_line,
+        "int main() { int a = 1; a = a + 1; return a; }" =>
+        vec![("entry", vec![EXIT]), ("exit", vec![]), ("unreachable", vec![EXIT])]
+    );
+
+    cfg_test!(
+        test_if_else,
+        "int main() { int a = 1; if (a) a = 2; else a = 3; return a; }" =>
+        vec![
+            ("entry", vec![2, 3]),
+            ("exit", vec![]),
+            ("if_then", vec![4]),
+            ("if_else", vec![4]),
+            ("if_end", vec![EXIT]),
+            ("unreachable", vec![EXIT]),
+        ]
+    );
+
+    cfg_test!(
+        test_while,
+        "int main() { int a = 0; while (a < 3) { if (a) break; a = a + 1; } return a; }" =>
+        vec![
+            ("entry", vec![2]),
+            ("exit", vec![]),
+            ("while_start", vec![3, 4]),
+            ("while_body", vec![5, 6]),
+            ("while_end", vec![EXIT]),
+            ("if_then", vec![4]),
+            ("if_end", vec![2]),
+            // The code after the break in the `if` block.
+            ("unreachable", vec![6]),
+            ("unreachable", vec![EXIT]),
+        ]
+    );
+
+    cfg_test!(
+        test_do_while,
+        "int main() { int a = 0; do { a = a + 1; continue; } while (a < 3); return a; }" =>
+        vec![
+            ("entry", vec![2]),
+            ("exit", vec![]),
+            ("do_start", vec![3]),
+            ("do_continue", vec![2, 4]),
+            ("do_end", vec![EXIT]),
+            ("unreachable", vec![3]),
+            ("unreachable", vec![EXIT]),
+        ]
+    );
+
+    cfg_test!(
+        test_for,
+        "int main() { int a = 0; for (int i = 0; i < 3; i = i + 1) a = a + i; return a; }" =>
+        vec![
+            ("entry", vec![2]),
+            ("exit", vec![]),
+            ("for_start", vec![3, 5]),
+            ("for_body", vec![4]),
+            ("for_continue", vec![2]),
+            ("for_end", vec![EXIT]),
+            ("unreachable", vec![EXIT]),
+        ]
+    );
+
+    cfg_test!(
+        test_for_without_condition,
+        "int main() { for (;;) {} }" =>
+        vec![
+            ("entry", vec![2]),
+            ("exit", vec![]),
+            ("for_start", vec![3]),
+            ("for_body", vec![4]),
+            ("for_continue", vec![2]),
+            ("for_end", vec![EXIT]),
+        ]
+    );
+
+    #[test]
+    fn test_reachable() {
+        let tokens = Tokenizer::new("int main() { for (;;) {} return 1; }")
+            .tokenize()
+            .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let cfg = &CfgBuilder::new().build(&program).unwrap()[0];
+        let kinds: Vec<_> = cfg.blocks.iter().map(|block| block.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                "entry",
+                "exit",
+                "for_start",
+                "for_body",
+                "for_continue",
+                "for_end",
+                "unreachable"
+            ]
+        );
+        // Nothing leaves the loop, so the return is never reached.
+        assert_eq!(
+            cfg.reachable(),
+            [true, false, true, true, true, false, false]
+        );
+        assert_eq!(cfg.predecessors()[EXIT], [5, 6]);
+    }
+
+    #[test]
+    fn test_break_outside_loop() {
+        assert_eq!(
+            build("int main() { break; }"),
+            Err(CfgError::NoLoopFoundForBreak(Span { lo: 13, hi: 18 }))
+        );
+        assert_eq!(
+            build("int main() { if (1) continue; }"),
+            Err(CfgError::NoLoopFoundForContinue(Span { lo: 20, hi: 28 }))
+        );
+    }
+
+    /// The warnings of every function of the program.
+    fn warnings(src: &str) -> Vec<CfgWarning> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        let cfgs = CfgBuilder::new().build(&program).unwrap();
+        cfgs.iter().flat_map(Cfg::warnings).collect()
+    }
+
+    #[test]
+    fn test_missing_return() {
+        assert_eq!(
+            warnings("int f(int a) { if (a) return 1; } int main() { return f(1); }"),
+            [CfgWarning::MissingReturn("f".into(), Span { lo: 4, hi: 5 })]
+        );
+        // The loops with constant conditions only leave with a return.
+        assert_eq!(
+            warnings("int f() { while (1) { return 1; } } int main() { for (;;) { return f(); } }"),
+            []
+        );
+        assert_eq!(
+            warnings("int f(int a) { if (a) return 1; else { return 2; } } int main() {}"),
+            []
+        );
+        assert_eq!(
+            warnings("int f(int a) { do { if (a) break; return 1; } while (1); } int main() {}"),
+            [CfgWarning::MissingReturn("f".into(), Span { lo: 4, hi: 5 })]
+        );
+    }
+
+    #[test]
+    fn test_unreachable_code() {
+        assert_eq!(
+            warnings("int f(); int main() { return 1; f(); f(); }"),
+            [CfgWarning::UnreachableCode(
+                "main".into(),
+                Span { lo: 32, hi: 36 }
+            )]
+        );
+        assert_eq!(
+            warnings("int main() { while (1) { break; if (1) return 2; } return 1; }"),
+            [CfgWarning::UnreachableCode(
+                "main".into(),
+                Span { lo: 32, hi: 48 }
+            )]
+        );
+        // Nothing runs after the return, the null statements don't count.
+        assert_eq!(
+            warnings("int main() { for (;;) { continue; ; } return 1; ; }"),
+            []
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! The warnings about the local variables. The variables are identified by
+//! their names, the codegen doesn't allow shadowing them yet.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{BlockId, Cfg, CfgWarning, Item, Terminator, ENTRY};
+use crate::{parser::ast::Expr, tokenizer::Span};
+
+type Vars = BTreeSet<String>;
+
+/// The variables that are read before they are initialized, with the span of
+/// the first read.
+type Reads = BTreeMap<String, Span>;
+
+pub fn check(cfg: &Cfg) -> Vec<CfgWarning> {
+    let uninitialized = uninitialized(cfg);
+
+    // The names with the spans of their first declarations.
+    let mut declared: Vec<(&str, Span)> = vec![];
+    let mut read = Vars::new();
+    let mut assigned = Vars::new();
+    for block in &cfg.blocks {
+        for item in &block.items {
+            match item {
+                Item::Declaration(decl) => {
+                    if !declared.iter().any(|(name, _)| *name == decl.name) {
+                        declared.push((&decl.name, decl.span));
+                    }
+                    if let Some(ref initializer) = decl.initializer {
+                        uses(initializer, &mut read, &mut assigned);
+                    }
+                }
+                Item::Expression(expr) => uses(expr, &mut read, &mut assigned),
+            }
+        }
+        if let Terminator::Branch(expr, ..) | Terminator::Return(expr) = &block.terminator {
+            uses(expr, &mut read, &mut assigned);
+        }
+    }
+
+    let warning = |warning: fn(String, String, Span) -> CfgWarning, name: &str, span: Span| {
+        warning(cfg.name.clone(), name.to_string(), span)
+    };
+    declared
+        .into_iter()
+        .filter_map(|(name, span)| {
+            if let Some(&read_span) = uninitialized.get(name) {
+                Some(warning(CfgWarning::Uninitialized, name, read_span))
+            } else if read.contains(name) {
+                None
+            } else if assigned.contains(name) {
+                Some(warning(CfgWarning::UnusedButSetVariable, name, span))
+            } else {
+                // The initializer doesn't count as a use, like GCC does.
+                Some(warning(CfgWarning::UnusedVariable, name, span))
+            }
+        })
+        .collect()
+}
+
+/// Collect the variables that the expression reads and assigns to.
+fn uses(expr: &Expr, read: &mut Vars, assigned: &mut Vars) {
+    match expr {
+        Expr::Var(name, _) => {
+            read.insert(name.clone());
+        }
+        Expr::Assignment(name, value, _) => {
+            assigned.insert(name.clone());
+            uses(value, read, assigned);
+        }
+        Expr::UnaryOp(_, operand, _) => uses(operand, read, assigned),
+        Expr::BinaryOp(_, lhs, rhs, _) => {
+            uses(lhs, read, assigned);
+            uses(rhs, read, assigned);
+        }
+        Expr::TernaryConditional(ternary) => {
+            uses(&ternary.condition, read, assigned);
+            uses(&ternary.if_expr, read, assigned);
+            uses(&ternary.else_expr, read, assigned);
+        }
+        Expr::FunctionCall(_, args, _) => {
+            for arg in args {
+                uses(arg, read, assigned);
+            }
+        }
+        Expr::Constant(_) | Expr::Null => {}
+    }
+}
+
+/// The variables that may be read before they are assigned to. A variable
+/// is initialized at the start of a block only if it's initialized at the
+/// end of every block that jumps to it.
+fn uninitialized(cfg: &Cfg) -> Reads {
+    let reachable = cfg.reachable();
+    let mut predecessors: Vec<Vec<BlockId>> = vec![vec![]; cfg.blocks.len()];
+    for (id, block) in cfg.blocks.iter().enumerate() {
+        if reachable[id] {
+            for successor in block.terminator.taken_successors() {
+                predecessors[successor].push(id);
+            }
+        }
+    }
+
+    // The initialized variables at the end of each block, `None` until the
+    // block is visited.
+    let mut initialized: Vec<Option<Vars>> = vec![None; cfg.blocks.len()];
+    loop {
+        let mut changed = false;
+        let mut uninitialized = Reads::new();
+        for (id, block) in cfg.blocks.iter().enumerate() {
+            if !reachable[id] {
+                continue;
+            }
+            let mut state = if id == ENTRY {
+                cfg.params.iter().cloned().collect()
+            } else {
+                let mut states = predecessors[id]
+                    .iter()
+                    .filter_map(|&predecessor| initialized[predecessor].as_ref());
+                let Some(first) = states.next() else {
+                    continue;
+                };
+                states.fold(first.clone(), |state, other| &state & other)
+            };
+
+            for item in &block.items {
+                match item {
+                    Item::Declaration(decl) => match decl.initializer {
+                        Some(ref initializer) => {
+                            initialize(initializer, &mut state, &mut uninitialized);
+                            state.insert(decl.name.clone());
+                        }
+                        None => {
+                            state.remove(&decl.name);
+                        }
+                    },
+                    Item::Expression(expr) => initialize(expr, &mut state, &mut uninitialized),
+                }
+            }
+            if let Terminator::Branch(expr, ..) | Terminator::Return(expr) = &block.terminator {
+                initialize(expr, &mut state, &mut uninitialized);
+            }
+
+            if initialized[id].as_ref() != Some(&state) {
+                initialized[id] = Some(state);
+                changed = true;
+            }
+        }
+        // The reads are checked against the final states.
+        if !changed {
+            return uninitialized;
+        }
+    }
+}
+
+/// Evaluate the expression on the initialized variables, and collect the
+/// ones that are read before they are initialized.
+fn initialize(expr: &Expr, state: &mut Vars, uninitialized: &mut Reads) {
+    match expr {
+        Expr::Var(name, span) => {
+            if !state.contains(name) {
+                uninitialized.entry(name.clone()).or_insert(*span);
+            }
+        }
+        Expr::Assignment(name, value, _) => {
+            initialize(value, state, uninitialized);
+            state.insert(name.clone());
+        }
+        Expr::UnaryOp(_, operand, _) => initialize(operand, state, uninitialized),
+        Expr::BinaryOp(op, lhs, rhs, _) => {
+            initialize(lhs, state, uninitialized);
+            if op.is_short_circuiting_op() {
+                // The right hand side may not run, so its assignments don't count.
+                initialize(rhs, &mut state.clone(), uninitialized);
+            } else {
+                initialize(rhs, state, uninitialized);
+            }
+        }
+        Expr::TernaryConditional(ternary) => {
+            initialize(&ternary.condition, state, uninitialized);
+            let mut if_state = state.clone();
+            initialize(&ternary.if_expr, &mut if_state, uninitialized);
+            initialize(&ternary.else_expr, state, uninitialized);
+            *state = &*state & &if_state;
+        }
+        Expr::FunctionCall(_, args, _) => {
+            for arg in args {
+                initialize(arg, state, uninitialized);
+            }
+        }
+        Expr::Constant(_) | Expr::Null => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cfg::CfgBuilder, parser::Parser, tokenizer::Tokenizer};
+
+    fn check_warnings(src: &str) -> Vec<CfgWarning> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        check(&CfgBuilder::new().build(&program).unwrap()[0])
+    }
+
+    /// The messages of the warnings about the variables of the first function.
+    fn check_src(src: &str) -> Vec<String> {
+        check_warnings(src)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn uninitialized(name: &str) -> String {
+        CfgWarning::Uninitialized("main".into(), name.into(), Span::default()).to_string()
+    }
+
+    #[test]
+    fn test_uninitialized() {
+        // The span is the one of the first read.
+        assert_eq!(
+            check_warnings("int main() { int a; int b = a; return b; }"),
+            [CfgWarning::Uninitialized(
+                "main".into(),
+                "a".into(),
+                Span { lo: 28, hi: 29 }
+            )]
+        );
+        assert_eq!(
+            check_src("int main() { int a; return a; }"),
+            [uninitialized("a")]
+        );
+        assert_eq!(
+            check_src("int main() { int a; int b = 1; if (b) a = 1; return a; }"),
+            [uninitialized("a")]
+        );
+        assert_eq!(
+            check_src("int main() { int a; int b = 1; if (b) a = 1; else a = 2; return a; }"),
+            [] as [String; 0]
+        );
+        // The variable is initialized on the second iteration, but not the first one.
+        assert_eq!(
+            check_src(
+                "int main() { int a; int i = 0; while (i < 2) { i = i + a; a = 1; } return i; }"
+            ),
+            [uninitialized("a")]
+        );
+        // The declaration in the body makes it uninitialized again.
+        assert_eq!(
+            check_src("int main() { for (int i = 0; i < 2; i = i + 1) { int a; if (i) i = a; a = 1; } return 0; }"),
+            [uninitialized("a")]
+        );
+        // The right hand side of `&&` may not run.
+        assert_eq!(
+            check_src("int main() { int a; int b = 0; if (b && (a = 1)) b = 2; return a + b; }"),
+            [uninitialized("a")]
+        );
+        assert_eq!(
+            check_src("int main() { int a; while (1) { a = 1; break; } return a; }"),
+            [] as [String; 0]
+        );
+        assert_eq!(check_src("int f(int a) { return a; }"), [] as [String; 0]);
+    }
+
+    #[test]
+    fn test_unused() {
+        assert_eq!(
+            check_warnings("int main() { int a = 1; int b; b = 2; int c = 3; return c; }"),
+            [
+                CfgWarning::UnusedVariable("main".into(), "a".into(), Span { lo: 17, hi: 18 }),
+                CfgWarning::UnusedButSetVariable(
+                    "main".into(),
+                    "b".into(),
+                    Span { lo: 28, hi: 29 }
+                ),
+            ]
+        );
+        // The reads in the unreachable code still count.
+        assert_eq!(
+            check_src("int main() { int a = 1; return 0; return a; }"),
+            [] as [String; 0]
+        );
+    }
+}
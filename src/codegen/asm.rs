@@ -1,20 +1,586 @@
-#[derive(Debug, PartialEq)]
+//! The generated assembly as structured lines, so the code can be inspected
+//! and rewritten after it's generated, e.g. by the [peephole](super::peephole)
+//! optimizer. The codegen pushes the lines as text and they are parsed here,
+//! a line that doesn't parse is a bug in the codegen and returns an
+//! [`AsmError`].
+
+use std::fmt;
+
+use super::AsmError;
+
+#[derive(Debug, PartialEq, Default)]
 pub struct Assembly {
-    asm: Vec<String>,
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Line {
+    /// E.g. `.p2align 2`.
+    Directive(String),
+    Label(String),
+    Instruction(Instruction),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub operands: Vec<Operand>,
+}
+
+/// The mnemonics that the codegen emits.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Opcode {
+    Add,
+    Sub,
+    Mul,
+    Sdiv,
+    Msub,
+    Neg,
+    Mvn,
+    And,
+    Orr,
+    Eor,
+    Lsl,
+    Asr,
+    Mov,
+    Movz,
+    Movk,
+    Fmov,
+    Fadd,
+    Fsub,
+    Fmul,
+    Fdiv,
+    Fneg,
+    Scvtf,
+    Fcvtzs,
+    Fcvt,
+    Cmp,
+    Fcmp,
+    Cset,
+    Ldr,
+    Str,
+    Ldp,
+    Stp,
+    B,
+    /// A conditional branch, e.g. `beq`.
+    BCond(Condition),
+    Cbz,
+    Cbnz,
+    Bl,
+    Ret,
+}
+
+/// The condition codes that `cset` and the conditional branches use.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Condition {
+    Eq,
+    Ne,
+    Hs,
+    Lo,
+    Mi,
+    Pl,
+    Hi,
+    Ls,
+    Ge,
+    Lt,
+    Gt,
+    Le,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Operand {
+    /// E.g. `w0`, `d8`, `wzr` or `sp`.
+    Register(String),
+    Immediate(i64),
+    /// The floating point immediate of `fcmp`, i.e. `#0.0`.
+    FloatImmediate(f64),
+    /// `[base, #offset]`, the offset is added to the base before the access
+    /// and written back to it with `!`.
+    Address {
+        base: String,
+        offset: Option<i64>,
+        writeback: bool,
+    },
+    /// The shift of the previous operand, e.g. `lsl #16`.
+    Lsl(i64),
+    /// E.g. `eq` in `cset w0, eq`.
+    Condition(Condition),
+    Label(String),
 }
 
 impl Assembly {
     pub fn new() -> Assembly {
-        Assembly { asm: Vec::new() }
+        Assembly { lines: Vec::new() }
+    }
+
+    pub fn push<L: AsRef<str>>(&mut self, line: L) -> Result<(), AsmError> {
+        self.lines.push(Line::parse(line.as_ref())?);
+        Ok(())
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    pub fn lines_mut(&mut self) -> &mut Vec<Line> {
+        &mut self.lines
+    }
+}
+
+impl Line {
+    pub fn parse(line: &str) -> Result<Line, AsmError> {
+        if let Some(label) = line.strip_suffix(':') {
+            return Ok(Line::Label(label.to_string()));
+        }
+        if line.starts_with('.') {
+            return Ok(Line::Directive(line.to_string()));
+        }
+        let (opcode, rest) = line.split_once(' ').unwrap_or((line, ""));
+        Ok(Line::Instruction(Instruction {
+            opcode: Opcode::parse(opcode)?,
+            operands: split_operands(rest)
+                .into_iter()
+                .map(Operand::parse)
+                .collect::<Result<_, _>>()?,
+        }))
+    }
+
+    pub fn instruction(&self) -> Option<&Instruction> {
+        match self {
+            Line::Instruction(inst) => Some(inst),
+            _ => None,
+        }
+    }
+}
+
+impl Instruction {
+    pub fn new(opcode: Opcode, operands: Vec<Operand>) -> Instruction {
+        Instruction { opcode, operands }
+    }
+}
+
+impl Opcode {
+    const ALL: [Opcode; 36] = [
+        Opcode::Add,
+        Opcode::Sub,
+        Opcode::Mul,
+        Opcode::Sdiv,
+        Opcode::Msub,
+        Opcode::Neg,
+        Opcode::Mvn,
+        Opcode::And,
+        Opcode::Orr,
+        Opcode::Eor,
+        Opcode::Lsl,
+        Opcode::Asr,
+        Opcode::Mov,
+        Opcode::Movz,
+        Opcode::Movk,
+        Opcode::Fmov,
+        Opcode::Fadd,
+        Opcode::Fsub,
+        Opcode::Fmul,
+        Opcode::Fdiv,
+        Opcode::Fneg,
+        Opcode::Scvtf,
+        Opcode::Fcvtzs,
+        Opcode::Fcvt,
+        Opcode::Cmp,
+        Opcode::Fcmp,
+        Opcode::Cset,
+        Opcode::Ldr,
+        Opcode::Str,
+        Opcode::Ldp,
+        Opcode::Stp,
+        Opcode::B,
+        Opcode::Cbz,
+        Opcode::Cbnz,
+        Opcode::Bl,
+        Opcode::Ret,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Sub => "sub",
+            Opcode::Mul => "mul",
+            Opcode::Sdiv => "sdiv",
+            Opcode::Msub => "msub",
+            Opcode::Neg => "neg",
+            Opcode::Mvn => "mvn",
+            Opcode::And => "and",
+            Opcode::Orr => "orr",
+            Opcode::Eor => "eor",
+            Opcode::Lsl => "lsl",
+            Opcode::Asr => "asr",
+            Opcode::Mov => "mov",
+            Opcode::Movz => "movz",
+            Opcode::Movk => "movk",
+            Opcode::Fmov => "fmov",
+            Opcode::Fadd => "fadd",
+            Opcode::Fsub => "fsub",
+            Opcode::Fmul => "fmul",
+            Opcode::Fdiv => "fdiv",
+            Opcode::Fneg => "fneg",
+            Opcode::Scvtf => "scvtf",
+            Opcode::Fcvtzs => "fcvtzs",
+            Opcode::Fcvt => "fcvt",
+            Opcode::Cmp => "cmp",
+            Opcode::Fcmp => "fcmp",
+            Opcode::Cset => "cset",
+            Opcode::Ldr => "ldr",
+            Opcode::Str => "str",
+            Opcode::Ldp => "ldp",
+            Opcode::Stp => "stp",
+            Opcode::B => "b",
+            Opcode::BCond(cond) => match cond {
+                Condition::Eq => "beq",
+                Condition::Ne => "bne",
+                Condition::Hs => "bhs",
+                Condition::Lo => "blo",
+                Condition::Mi => "bmi",
+                Condition::Pl => "bpl",
+                Condition::Hi => "bhi",
+                Condition::Ls => "bls",
+                Condition::Ge => "bge",
+                Condition::Lt => "blt",
+                Condition::Gt => "bgt",
+                Condition::Le => "ble",
+            },
+            Opcode::Cbz => "cbz",
+            Opcode::Cbnz => "cbnz",
+            Opcode::Bl => "bl",
+            Opcode::Ret => "ret",
+        }
+    }
+
+    fn parse(name: &str) -> Result<Opcode, AsmError> {
+        Opcode::ALL
+            .into_iter()
+            .find(|opcode| opcode.name() == name)
+            .or_else(|| {
+                let cond = name.strip_prefix('b')?;
+                Condition::from_name(cond).map(Opcode::BCond)
+            })
+            .ok_or_else(|| AsmError::UnknownOpcode(name.to_string()))
+    }
+
+    /// Whether the instruction reads the condition flags.
+    pub fn reads_flags(self) -> bool {
+        matches!(self, Opcode::Cset | Opcode::BCond(_))
     }
 
-    pub fn push<S: Into<String>>(&mut self, string: S) {
-        self.asm.push(string.into())
+    /// Whether the instruction sets all the condition flags. A call counts,
+    /// the callee doesn't preserve them.
+    pub fn sets_flags(self) -> bool {
+        matches!(self, Opcode::Cmp | Opcode::Fcmp | Opcode::Bl)
     }
 }
 
-impl ToString for Assembly {
-    fn to_string(&self) -> String {
-        self.asm.join("\n")
+impl Condition {
+    const ALL: [Condition; 12] = [
+        Condition::Eq,
+        Condition::Ne,
+        Condition::Hs,
+        Condition::Lo,
+        Condition::Mi,
+        Condition::Pl,
+        Condition::Hi,
+        Condition::Ls,
+        Condition::Ge,
+        Condition::Lt,
+        Condition::Gt,
+        Condition::Le,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Condition::Eq => "eq",
+            Condition::Ne => "ne",
+            Condition::Hs => "hs",
+            Condition::Lo => "lo",
+            Condition::Mi => "mi",
+            Condition::Pl => "pl",
+            Condition::Hi => "hi",
+            Condition::Ls => "ls",
+            Condition::Ge => "ge",
+            Condition::Lt => "lt",
+            Condition::Gt => "gt",
+            Condition::Le => "le",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Condition> {
+        Condition::ALL.into_iter().find(|cond| cond.name() == name)
+    }
+
+    /// The condition that is true when this one is false, also for the
+    /// unordered floating point comparisons.
+    pub fn invert(self) -> Condition {
+        match self {
+            Condition::Eq => Condition::Ne,
+            Condition::Ne => Condition::Eq,
+            Condition::Hs => Condition::Lo,
+            Condition::Lo => Condition::Hs,
+            Condition::Mi => Condition::Pl,
+            Condition::Pl => Condition::Mi,
+            Condition::Hi => Condition::Ls,
+            Condition::Ls => Condition::Hi,
+            Condition::Ge => Condition::Lt,
+            Condition::Lt => Condition::Ge,
+            Condition::Gt => Condition::Le,
+            Condition::Le => Condition::Gt,
+        }
+    }
+}
+
+impl Operand {
+    fn parse(operand: &str) -> Result<Operand, AsmError> {
+        if let Some(address) = operand.strip_prefix('[') {
+            let invalid = || AsmError::InvalidAddress(operand.to_string());
+            let (address, writeback) = match address.strip_suffix("]!") {
+                Some(address) => (address, true),
+                None => (address.strip_suffix(']').ok_or_else(invalid)?, false),
+            };
+            let (base, offset) = match address.split_once(", #") {
+                Some((base, offset)) => (base, Some(offset.parse().map_err(|_| invalid())?)),
+                None => (address, None),
+            };
+            if !is_register(base) {
+                return Err(invalid());
+            }
+            return Ok(Operand::Address {
+                base: base.to_string(),
+                offset,
+                writeback,
+            });
+        }
+        if let Some(imm) = operand.strip_prefix('#') {
+            if let Ok(imm) = imm.parse() {
+                return Ok(Operand::Immediate(imm));
+            }
+            return imm
+                .parse()
+                .map(Operand::FloatImmediate)
+                .map_err(|_| AsmError::InvalidImmediate(operand.to_string()));
+        }
+        if let Some(shift) = operand.strip_prefix("lsl ") {
+            return shift
+                .strip_prefix('#')
+                .and_then(|shift| shift.parse().ok())
+                .map(Operand::Lsl)
+                .ok_or_else(|| AsmError::InvalidShift(operand.to_string()));
+        }
+        if is_register(operand) {
+            Ok(Operand::Register(operand.to_string()))
+        } else if let Some(cond) = Condition::from_name(operand) {
+            Ok(Operand::Condition(cond))
+        } else if is_label(operand) {
+            Ok(Operand::Label(operand.to_string()))
+        } else {
+            Err(AsmError::InvalidOperand(operand.to_string()))
+        }
+    }
+
+    pub fn register(&self) -> Option<&str> {
+        match self {
+            Operand::Register(register) => Some(register),
+            _ => None,
+        }
+    }
+}
+
+fn is_register(operand: &str) -> bool {
+    match operand {
+        "sp" | "wzr" | "xzr" => true,
+        _ => {
+            let mut chars = operand.chars();
+            matches!(chars.next(), Some('w' | 'x' | 's' | 'd'))
+                && !chars.as_str().is_empty()
+                && chars.all(|c| c.is_ascii_digit())
+        }
+    }
+}
+
+/// The labels are the ones of the codegen, e.g. `Lmain_bb1` or `_putchar`.
+fn is_label(operand: &str) -> bool {
+    operand.starts_with(['L', '_'])
+        && operand
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split the operands at the commas that aren't in an address.
+fn split_operands(operands: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in operands.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(operands[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = operands[start..].trim();
+    if !last.is_empty() {
+        result.push(last);
+    }
+    result
+}
+
+impl From<Instruction> for Line {
+    fn from(inst: Instruction) -> Line {
+        Line::Instruction(inst)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Register(register) => write!(f, "{}", register),
+            Operand::Immediate(imm) => write!(f, "#{}", imm),
+            Operand::FloatImmediate(imm) => write!(f, "#{:?}", imm),
+            Operand::Address {
+                base,
+                offset,
+                writeback,
+            } => {
+                match offset {
+                    Some(offset) => write!(f, "[{}, #{}]", base, offset)?,
+                    None => write!(f, "[{}]", base)?,
+                }
+                if *writeback {
+                    write!(f, "!")?;
+                }
+                Ok(())
+            }
+            Operand::Lsl(shift) => write!(f, "lsl #{}", shift),
+            Operand::Condition(cond) => write!(f, "{}", cond),
+            Operand::Label(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.opcode.name())?;
+        for (index, operand) in self.operands.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, operand)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Line::Directive(directive) => write!(f, "{}", directive),
+            Line::Label(label) => write!(f, "{}:", label),
+            Line::Instruction(inst) => write!(f, "{}", inst),
+        }
+    }
+}
+
+impl fmt::Display for Assembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, line) in self.lines.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        let lines = [
+            ".globl _main",
+            "_main:",
+            "stp x29, x30, [sp, #-16]!",
+            "ldp x29, x30, [sp], #16",
+            "str w0, [sp, #12]",
+            "movk w16, #16, lsl #16",
+            "fcmp s0, #0.0",
+            "cset w0, eq",
+            "beq Lmain_if_else_0",
+            "bl _putchar",
+            "ret",
+        ];
+        for line in lines {
+            assert_eq!(Line::parse(line).unwrap().to_string(), line);
+        }
+        assert_eq!(
+            Line::parse("ldr w1, [sp, #8]"),
+            Ok(Line::Instruction(Instruction::new(
+                Opcode::Ldr,
+                vec![
+                    Operand::Register("w1".to_string()),
+                    Operand::Address {
+                        base: "sp".to_string(),
+                        offset: Some(8),
+                        writeback: false
+                    }
+                ]
+            )))
+        );
+        let cset = Line::parse("cset w0, lt").unwrap();
+        assert_eq!(
+            cset.instruction().unwrap().operands[1],
+            Operand::Condition(Condition::Lt)
+        );
+        assert_eq!(
+            Line::parse("bpl L").unwrap().instruction().unwrap().opcode,
+            Opcode::BCond(Condition::Pl)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let errors = [
+            ("frob w0", AsmError::UnknownOpcode("frob".into())),
+            ("fcmp s0, #zero", AsmError::InvalidImmediate("#zero".into())),
+            (
+                "movk w16, #1, lsl #x",
+                AsmError::InvalidShift("lsl #x".into()),
+            ),
+            (
+                "ldr w0, [sp, #x]",
+                AsmError::InvalidAddress("[sp, #x]".into()),
+            ),
+            (
+                "ldr w0, [sp, #8",
+                AsmError::InvalidAddress("[sp, #8".into()),
+            ),
+            ("mov w0, 1", AsmError::InvalidOperand("1".into())),
+        ];
+        for (line, err) in errors {
+            assert_eq!(Line::parse(line), Err(err), "{}", line);
+        }
+
+        // The codegen gets the error instead of a panic.
+        let mut asm = Assembly::new();
+        assert_eq!(
+            asm.push("frob w0"),
+            Err(AsmError::UnknownOpcode("frob".into()))
+        );
+        assert!(asm.lines().is_empty());
     }
 }
@@ -1,6 +1,10 @@
 use thiserror::Error;
 
-use crate::parser::ast::BinaryOp;
+use crate::{
+    cfg::CfgError,
+    parser::ast::{BinaryOp, Constant, Type, UnaryOp},
+    tokenizer::Span,
+};
 
 #[derive(Error, Debug)]
 pub enum CodegenError {
@@ -8,15 +12,78 @@ pub enum CodegenError {
     NoFunctionFound,
 
     #[error("Variable {0:?} is already declared")]
-    VarAlreadyDeclared(String),
+    VarAlreadyDeclared(String, Span),
     #[error("Variable {0:?} not found")]
-    VarNotFound(String),
+    VarNotFound(String, Span),
+    #[error("No stack slot is reserved for the intermediate value at depth {0}")]
+    OpSlotNotFound(usize),
 
+    #[error("Constant {0} isn't supported")]
+    UnsupportedConstant(Constant),
     #[error("Unexpected binary operator {0:?}")]
     UnexpectedBinaryOp(BinaryOp),
+    /// The span is `None` in the IR codegen, the lowering checks the operands
+    /// first.
+    #[error("Invalid operand of type {1:?} for unary operator {0:?}")]
+    InvalidUnaryOperand(UnaryOp, Type, Option<Span>),
+    #[error("Invalid operands of type {1:?} for binary operator {0:?}")]
+    InvalidBinaryOperands(BinaryOp, Type, Option<Span>),
 
     #[error("'break' statement is not in a loop or switch statement")]
-    NoLoopFoundForBreak,
+    NoLoopFoundForBreak(Span),
     #[error("'continue' statement is not in a loop or switch statement")]
-    NoLoopFoundForContinue,
+    NoLoopFoundForContinue(Span),
+
+    #[error("Can't generate code for a statement that failed to parse")]
+    UnexpectedErrorNode,
+    #[error(transparent)]
+    Cfg(#[from] CfgError),
+
+    #[error("Parameter name omitted in the definition of function {0:?}")]
+    UnnamedParameter(String, Span),
+    #[error("Function {0:?} is called with more than {1} arguments")]
+    TooManyArguments(String, usize, Option<Span>),
+
+    #[error("Generated invalid assembly: {0}")]
+    InvalidAssembly(#[from] AsmError),
+}
+
+impl CodegenError {
+    /// The span of the code that can't be compiled, the internal errors
+    /// don't have one.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CodegenError::VarAlreadyDeclared(_, span)
+            | CodegenError::VarNotFound(_, span)
+            | CodegenError::NoLoopFoundForBreak(span)
+            | CodegenError::NoLoopFoundForContinue(span)
+            | CodegenError::UnnamedParameter(_, span) => Some(*span),
+            CodegenError::TooManyArguments(_, _, span)
+            | CodegenError::InvalidUnaryOperand(_, _, span)
+            | CodegenError::InvalidBinaryOperands(_, _, span) => *span,
+            CodegenError::Cfg(err) => err.span(),
+            CodegenError::NoFunctionFound
+            | CodegenError::OpSlotNotFound(_)
+            | CodegenError::UnsupportedConstant(_)
+            | CodegenError::UnexpectedBinaryOp(_)
+            | CodegenError::UnexpectedErrorNode
+            | CodegenError::InvalidAssembly(_) => None,
+        }
+    }
+}
+
+/// A line of assembly that the codegen emitted but can't be parsed, it's a
+/// bug in the codegen.
+#[derive(Error, Debug, PartialEq)]
+pub enum AsmError {
+    #[error("Unknown opcode {0:?}")]
+    UnknownOpcode(String),
+    #[error("Invalid immediate {0:?}")]
+    InvalidImmediate(String),
+    #[error("Invalid shift {0:?}")]
+    InvalidShift(String),
+    #[error("Invalid address {0:?}")]
+    InvalidAddress(String),
+    #[error("Invalid operand {0:?}")]
+    InvalidOperand(String),
 }
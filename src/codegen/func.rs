@@ -2,14 +2,31 @@ use std::collections::HashMap;
 
 use crate::{
     codegen::{CodegenError, CodegenResult},
-    parser::ast::{Block, BlockItem, DeclOrExpr, Expr, Statement, VarDecl, VarSize},
+    parser::ast::{
+        Block, BlockItem, DeclOrExpr, Expr, Function, Statement, Type, VarDecl, VarSize,
+    },
+    tokenizer::Span,
 };
 
 #[derive(Debug, PartialEq)]
 pub struct CodegenFunction {
+    pub name: String,
     pub stack: FuncStack,
     pub op_stack_depth: usize,
     pub loops: Vec<Loop>,
+    /// Label of the function epilogue that the return statements jump to.
+    pub return_label: String,
+    pub return_ty: Type,
+    /// Number of the labelled constructs generated so far, labels are numbered
+    /// per function so the output doesn't depend on the other functions.
+    pub label_count: usize,
+}
+
+/// Types of a function that the callers need to know.
+#[derive(Debug, PartialEq)]
+pub struct FuncSignature {
+    pub return_ty: Type,
+    pub params: Vec<Type>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -19,6 +36,8 @@ pub enum CodegenVar {
 
 #[derive(Debug, PartialEq)]
 pub struct StackVar {
+    /// Type of the variable.
+    pub ty: Type,
     /// Size of the variable.
     pub size: VarSize,
     /// Offset from the stack pointer.
@@ -30,37 +49,58 @@ pub struct FuncStack {
     pub var_map: HashMap<String, CodegenVar>,
     pub size: usize,
     pub op_count: usize,
+    /// Whether the function calls other functions. The frame pointer and the
+    /// link register need to be saved in that case.
+    pub makes_calls: bool,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Loop {
-    pub start_label: String,
+    /// Label that the continue statements jump to.
+    pub continue_label: String,
+    /// Label that the break statements jump to.
     pub end_label: String,
 }
 
 impl CodegenFunction {
-    pub fn new(block: &Block) -> CodegenResult<CodegenFunction> {
+    pub fn new(func: &Function) -> CodegenResult<CodegenFunction> {
         Ok(CodegenFunction {
-            stack: block.to_func_stack()?,
+            name: func.name.clone(),
+            stack: func.to_func_stack()?,
             op_stack_depth: 0,
             loops: vec![],
+            return_label: format!("L{}_return", func.name),
+            return_ty: func.return_ty,
+            label_count: 0,
         })
     }
 }
 
-impl Block {
+impl Function {
     fn to_func_stack(&self) -> CodegenResult<FuncStack> {
         let mut stack = FuncStack {
             var_map: HashMap::new(),
             size: 0,
             op_count: 0,
+            makes_calls: false,
         };
 
-        self.func_stack(&mut stack)?;
+        // Parameters are spilled to the stack like the other local variables.
+        for param in &self.params {
+            let name = param
+                .name
+                .as_ref()
+                .ok_or(CodegenError::UnnamedParameter(self.name.clone(), self.span))?;
+            stack.push_var(name, param.ty, self.span)?;
+        }
+
+        if let Some(body) = &self.body {
+            body.func_stack(&mut stack)?;
+        }
 
         // Stack size has to be 16 byte aligned.
         // https://stackoverflow.com/a/34504752/3582646
-        if stack.size % 16 != 0 {
+        if !stack.size.is_multiple_of(16) {
             stack.size += 16 - (stack.size % 16);
         }
 
@@ -75,14 +115,16 @@ impl Block {
 
         Ok(stack)
     }
+}
 
+impl Block {
     fn func_stack(&self, stack: &mut FuncStack) -> CodegenResult<()> {
         for item in &self.items {
             match item {
                 BlockItem::Declaration(var_decl) => {
                     var_decl.func_stack(stack)?;
                 }
-                BlockItem::Statement(stmt) => {
+                BlockItem::Statement(stmt, _) => {
                     stmt.func_stack(stack)?;
                 }
             }
@@ -100,7 +142,8 @@ impl Statement {
                 expr.func_stack(stack)?;
             }
             Statement::Conditional(cond) => {
-                // Look at the conditional statements.
+                // Look at the condition and the conditional statements.
+                cond.condition.func_stack(stack)?;
                 cond.if_stmt.func_stack(stack)?;
                 if let Some(else_stmt) = &cond.else_stmt {
                     else_stmt.func_stack(stack)?;
@@ -123,7 +166,7 @@ impl Statement {
                 for_loop.increment.func_stack(stack)?;
                 for_loop.body.func_stack(stack)?;
             }
-            Statement::Break | Statement::Continue | Statement::Null => {}
+            Statement::Break(_) | Statement::Continue(_) | Statement::Null | Statement::Error => {}
         }
 
         Ok(0)
@@ -133,22 +176,13 @@ impl Statement {
 impl Expr {
     fn func_stack(&self, stack: &mut FuncStack) -> CodegenResult<()> {
         match self {
-            Expr::Assignment(_, expr) => expr.func_stack(stack)?,
-            Expr::UnaryOp(_, expr) => expr.func_stack(stack)?,
-            Expr::BinaryOp(op, lhs, rhs) => {
+            Expr::Assignment(_, expr, _) => expr.func_stack(stack)?,
+            Expr::UnaryOp(_, expr, _) => expr.func_stack(stack)?,
+            Expr::BinaryOp(op, lhs, rhs, _) => {
                 lhs.func_stack(stack)?;
                 rhs.func_stack(stack)?;
                 if !op.is_short_circuiting_op() {
-                    // FIXME: Currently we support only word variable size for operations.
-                    stack.size += VarSize::Word.to_bytes();
-                    stack.var_map.insert(
-                        format!("op_{}", stack.op_count),
-                        CodegenVar::StackVar(StackVar {
-                            size: VarSize::Word,
-                            offset: stack.size,
-                        }),
-                    );
-                    stack.op_count += 1;
+                    stack.push_op_var();
                 }
             }
             Expr::TernaryConditional(ternary) => {
@@ -156,7 +190,17 @@ impl Expr {
                 ternary.if_expr.func_stack(stack)?;
                 ternary.else_expr.func_stack(stack)?;
             }
-            Expr::Var(_) => {}
+            Expr::FunctionCall(_, args, _) => {
+                stack.makes_calls = true;
+                for arg in args {
+                    arg.func_stack(stack)?;
+                }
+                // Evaluated arguments are kept in the stack until the call.
+                for _ in args {
+                    stack.push_op_var();
+                }
+            }
+            Expr::Var(..) => {}
             Expr::Constant(_) => {}
             Expr::Null => {}
         }
@@ -167,23 +211,51 @@ impl Expr {
 
 impl VarDecl {
     fn func_stack(&self, stack: &mut FuncStack) -> CodegenResult<()> {
-        if stack.var_map.contains_key(&self.name) {
-            return Err(CodegenError::VarAlreadyDeclared(self.name.clone()));
+        if let Some(initializer) = &self.initializer {
+            initializer.func_stack(stack)?;
         }
+        stack.push_var(&self.name, self.ty, self.span)
+    }
+}
 
-        let byte_size = self.get_byte_size();
-        stack.size += byte_size;
-        // We need to invert the offsets at the end.
-        stack.var_map.insert(
-            self.name.clone(),
-            CodegenVar::StackVar(StackVar {
-                size: self.size,
-                offset: stack.size,
-            }),
-        );
+impl FuncStack {
+    fn push_var(&mut self, name: &str, ty: Type, span: Span) -> CodegenResult<()> {
+        if self.var_map.contains_key(name) {
+            return Err(CodegenError::VarAlreadyDeclared(name.to_string(), span));
+        }
 
+        let var = self.alloc(ty);
+        self.var_map
+            .insert(name.to_string(), CodegenVar::StackVar(var));
         Ok(())
     }
+
+    /// Reserve a stack slot for an intermediate value of an operation.
+    fn push_op_var(&mut self) {
+        // Operation slots are typed as the widest type, so they can hold any value.
+        let var = self.alloc(Type::Double);
+        self.var_map
+            .insert(format!("op_{}", self.op_count), CodegenVar::StackVar(var));
+        self.op_count += 1;
+    }
+
+    fn alloc(&mut self, ty: Type) -> StackVar {
+        let size = ty.size();
+        // Loads and stores need naturally aligned offsets. The stack size is
+        // 16 byte aligned at the end, so the inverted offsets stay aligned.
+        let byte_size = size.to_bytes();
+        if !self.size.is_multiple_of(byte_size) {
+            self.size += byte_size - (self.size % byte_size);
+        }
+        self.size += byte_size;
+
+        // We need to invert the offsets at the end.
+        StackVar {
+            ty,
+            size,
+            offset: self.size,
+        }
+    }
 }
 impl DeclOrExpr {
     fn func_stack(&self, stack: &mut FuncStack) -> CodegenResult<()> {
@@ -200,4 +272,10 @@ impl CodegenVar {
             CodegenVar::StackVar(var) => Ok(var.offset),
         }
     }
+
+    pub fn get_type(&self) -> Type {
+        match self {
+            CodegenVar::StackVar(var) => var.ty,
+        }
+    }
 }
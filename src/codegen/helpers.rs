@@ -1,71 +1,67 @@
-use crate::parser::ast::{BlockItem, Statement};
-
-use std::{
-    sync::atomic::{AtomicUsize, Ordering},
-    vec,
+use crate::{
+    codegen::{AsmError, Assembly},
+    parser::ast::Type,
 };
 
-static LABEL_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-pub fn unique_label() -> String {
-    format!("L{}", LABEL_COUNTER.fetch_add(1, Ordering::SeqCst))
-}
-
-// TODO: Create a new struct for Block and move this to its method.
-// FIXME: pass a block instead.
-pub fn block_has_return(block_items: &[BlockItem]) -> bool {
-    let mut return_indexes = vec![];
-    let mut branches_without_return = vec![];
-
-    for (idx, item) in block_items.iter().enumerate() {
-        match item {
-            BlockItem::Declaration(_) => {}
-            BlockItem::Statement(stmt) => {
-                if let Some(has_return) = stmt.has_return() {
-                    if has_return {
-                        return_indexes.push(idx);
-                    } else {
-                        branches_without_return.push(idx);
-                    }
-                }
-            }
+impl Type {
+    /// Name of the register with the given index that can hold this type.
+    pub fn register(self, idx: usize) -> String {
+        match self {
+            Type::Int => format!("w{}", idx),
+            Type::Float => format!("s{}", idx),
+            Type::Double => format!("d{}", idx),
         }
     }
+}
 
-    // Check if there are branches without return that don't lead to a return.
-    for idx in branches_without_return {
-        if return_indexes.iter().all(|&i| i < idx) {
-            println!("Warning: Not all branches lead to a return. This is undefined behavior!");
-        }
+/// Set the register of the type with the given index to zero.
+pub fn generate_zero(asm: &mut Assembly, ty: Type, idx: usize) -> Result<(), AsmError> {
+    match ty {
+        Type::Int => asm.push(format!("mov w{}, #0", idx))?,
+        Type::Float => asm.push(format!("fmov s{}, wzr", idx))?,
+        Type::Double => asm.push(format!("fmov d{}, xzr", idx))?,
     }
+    Ok(())
+}
 
-    !return_indexes.is_empty()
+/// `mov` can only encode an immediate with a single 16 bit chunk set (or
+/// unset), so the others are built in two halves.
+pub fn generate_int_constant(asm: &mut Assembly, register: &str, int: i32) -> Result<(), AsmError> {
+    let bits = int as u32;
+    if bits >> 16 == 0 || !bits >> 16 == 0 {
+        return asm.push(format!("mov {}, #{}", register, int));
+    }
+    asm.push(format!("movz {}, #{}", register, bits & 0xffff))?;
+    asm.push(format!("movk {}, #{}, lsl #16", register, bits >> 16))
 }
 
-impl Statement {
-    // The return type is not ideal but it does the job.
-    // Returning `None` means that the statement does not have a return.
-    // `Some(false)` means that the statement has has a branch without return.
-    // `Some(true)` means that it has a return.
-    pub fn has_return(&self) -> Option<bool> {
-        match self {
-            Statement::Return(_) => Some(true),
-            Statement::Conditional(ref cond) => {
-                let if_has_return = cond.if_stmt.has_return().unwrap_or(false);
-                let else_has_return = cond
-                    .else_stmt
-                    .as_ref()
-                    .map_or(Some(true), |b| b.has_return())
-                    .unwrap_or(false);
+/// Floating point immediates are limited, so we build the bits of the
+/// constant in a general purpose register and move them to the FP register.
+/// It's x16, the register allocator doesn't use it.
+pub fn generate_floating_constant(
+    asm: &mut Assembly,
+    bits: u64,
+    ty: Type,
+    idx: usize,
+) -> Result<(), AsmError> {
+    let (tmp, chunks) = match ty {
+        Type::Float => ("w16", 2),
+        _ => ("x16", 4),
+    };
+
+    if bits == 0 {
+        return generate_zero(asm, ty, idx);
+    }
 
-                Some(if_has_return && else_has_return)
-            }
-            Statement::Block(block) => Some(block_has_return(&block.items)),
-            Statement::Expression(_) => None,
-            Statement::While(_, stmt) => stmt.has_return(),
-            Statement::DoWhile(stmt, _) => stmt.has_return(),
-            Statement::For(for_loop) => for_loop.body.has_return(),
-            Statement::Break | Statement::Continue | Statement::Null => None,
+    let mut first = true;
+    for chunk in 0..chunks {
+        let imm = (bits >> (chunk * 16)) & 0xffff;
+        if imm == 0 {
+            continue;
         }
+        let opcode = if first { "movz" } else { "movk" };
+        first = false;
+        asm.push(format!("{} {}, #{}, lsl #{}", opcode, tmp, imm, chunk * 16))?;
     }
+    asm.push(format!("fmov {}, {}", ty.register(idx), tmp))
 }
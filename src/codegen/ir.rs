@@ -0,0 +1,708 @@
+//! Code generation from the optimized IR. The slots and the spilled
+//! temporaries live in the stack frame, the instructions load them to the
+//! scratch registers and store their results back. The other temporaries
+//! stay in the registers of the allocation.
+
+use super::{
+    arg_register_indices, asm::Condition, comparison_condition, helpers::*, peephole,
+    regalloc::Allocation, Assembly, CodegenError, CodegenResult,
+};
+use crate::{
+    interpreter::Value,
+    ir::{ssa, Block, BlockId, Function, Instruction, Module, Operand, Temp, Terminator},
+    parser::ast::{BinaryOp, Type, UnaryOp},
+};
+
+/// Size of the stack slot of a spilled temporary or a saved register, any
+/// type fits in it.
+const TEMP_SIZE: usize = 8;
+
+#[derive(Debug, PartialEq)]
+pub struct IrCodegen {
+    asm: Assembly,
+    /// Whether the assembly goes through the peephole optimizer.
+    peephole: bool,
+}
+
+impl Default for IrCodegen {
+    fn default() -> IrCodegen {
+        IrCodegen::new()
+    }
+}
+
+impl IrCodegen {
+    pub fn new() -> IrCodegen {
+        IrCodegen {
+            asm: Assembly::new(),
+            peephole: true,
+        }
+    }
+
+    pub fn with_peephole(mut self, peephole: bool) -> IrCodegen {
+        self.peephole = peephole;
+        self
+    }
+
+    pub fn generate(mut self, module: &Module) -> CodegenResult<String> {
+        // Header.
+        self.asm
+            .push(".section __TEXT,__text,regular,pure_instructions")?;
+        self.asm
+            .push(".build_version macos, 13, 0 sdk_version 13, 3")?;
+
+        for func in &module.functions {
+            let mut func = func.clone();
+            ssa::destruct(&mut func);
+            FunctionCodegen::new(&mut self.asm, &func)?.generate()?;
+        }
+        if self.peephole {
+            peephole::optimize(&mut self.asm);
+        }
+        Ok(self.asm.to_string())
+    }
+}
+
+/// The offsets of the saved registers, the spilled temporaries and the slots
+/// from the stack pointer.
+#[derive(Debug, PartialEq)]
+struct Frame {
+    /// The callee-saved registers that the function uses, e.g. `x19`.
+    saved: Vec<(String, usize)>,
+    temps: Vec<usize>,
+    slots: Vec<usize>,
+    size: usize,
+}
+
+impl Frame {
+    fn new(func: &Function, order: &[BlockId], allocation: &Allocation) -> Frame {
+        let mut size: usize = 0;
+        let mut saved = vec![];
+        let registers = (allocation.used_int.iter().map(|reg| format!("x{}", reg))).chain(
+            allocation
+                .used_floating
+                .iter()
+                .map(|reg| format!("d{}", reg)),
+        );
+        for register in registers {
+            saved.push((register, size));
+            size += TEMP_SIZE;
+        }
+        let mut temps = vec![0; func.temps.len()];
+        for temp in &allocation.spilled {
+            temps[temp.0] = size;
+            size += TEMP_SIZE;
+        }
+
+        // Only the slots that are still loaded or stored need space, the
+        // promoted ones don't.
+        let mut used = vec![false; func.slots.len()];
+        for inst in order.iter().flat_map(|id| &func.blocks[*id].instructions) {
+            if let Instruction::Load(_, slot) | Instruction::Store(slot, _) = inst {
+                used[slot.0] = true;
+            }
+        }
+        let mut slots = vec![0; func.slots.len()];
+        for (id, slot) in func.slots.iter().enumerate() {
+            if !used[id] {
+                continue;
+            }
+            // Loads and stores need naturally aligned offsets.
+            let bytes = slot.ty.size().to_bytes();
+            size = size.next_multiple_of(bytes);
+            slots[id] = size;
+            size += bytes;
+        }
+
+        // The stack size has to be 16 byte aligned, and a larger frame than
+        // what `sub` can encode is rounded up to the next 4096 bytes.
+        size = size.next_multiple_of(16);
+        if size >= 4096 {
+            size = size.next_multiple_of(4096);
+        }
+        Frame {
+            saved,
+            temps,
+            slots,
+            size,
+        }
+    }
+}
+
+struct FunctionCodegen<'a> {
+    asm: &'a mut Assembly,
+    func: &'a Function,
+    /// The reachable blocks in the order that they are emitted in.
+    order: Vec<BlockId>,
+    allocation: Allocation,
+    frame: Frame,
+    /// The indices of the argument registers of the parameters.
+    param_registers: Vec<usize>,
+    /// How many times each temporary is used.
+    uses: Vec<usize>,
+}
+
+impl<'a> FunctionCodegen<'a> {
+    fn new(asm: &'a mut Assembly, func: &'a Function) -> CodegenResult<FunctionCodegen<'a>> {
+        let reachable = func.reachable();
+        let order: Vec<BlockId> = (0..func.blocks.len()).filter(|id| reachable[*id]).collect();
+        let allocation = Allocation::new(func, &order);
+        let frame = Frame::new(func, &order, &allocation);
+        let param_types: Vec<Type> = func
+            .params
+            .iter()
+            .map(|param| func.slots[param.0].ty)
+            .collect();
+        let mut uses = vec![0; func.temps.len()];
+        for block in &func.blocks {
+            let operands = block.instructions.iter().flat_map(|inst| inst.operands());
+            for operand in operands.chain(block.terminator.operand()) {
+                if let Operand::Temp(temp) = operand {
+                    uses[temp.0] += 1;
+                }
+            }
+        }
+        Ok(FunctionCodegen {
+            asm,
+            func,
+            order,
+            allocation,
+            frame,
+            param_registers: arg_register_indices(&func.name, &param_types, None)?,
+            uses,
+        })
+    }
+
+    fn label(&self, block: BlockId) -> String {
+        format!("L{}_bb{}", self.func.name, block)
+    }
+
+    fn return_label(&self) -> String {
+        format!("L{}_return", self.func.name)
+    }
+
+    fn generate(&mut self) -> CodegenResult<()> {
+        let func = self.func;
+        let order = self.order.clone();
+        let makes_calls = order.iter().any(|id| {
+            func.blocks[*id]
+                .instructions
+                .iter()
+                .any(|inst| matches!(inst, Instruction::Call(..)))
+        });
+
+        self.asm.push(format!(".globl _{}", func.name))?;
+        self.asm.push(".p2align 2")?;
+        self.asm.push(format!("_{}:", func.name))?;
+        if makes_calls {
+            // Save the frame pointer and the link register, `bl` overwrites the latter.
+            self.asm.push("stp x29, x30, [sp, #-16]!")?;
+            self.asm.push("mov x29, sp")?;
+        }
+        if self.frame.size > 0 {
+            self.asm.push(format!("sub sp, sp, #{}", self.frame.size))?;
+        }
+        for (register, offset) in &self.frame.saved {
+            self.asm
+                .push(format!("str {}, [sp, #{}]", register, offset))?;
+        }
+
+        for (index, id) in order.iter().enumerate() {
+            let next = order.get(index + 1).copied();
+            let block = &func.blocks[*id];
+            self.asm.push(format!("{}:", self.label(*id)))?;
+            let fused = self.fused_comparison(block);
+            for (index, inst) in block.instructions.iter().enumerate() {
+                match inst {
+                    Instruction::Binary(_, _, lhs, rhs) if fused == Some(index) => {
+                        let ty = self.func.operand_type(lhs);
+                        let lhs = self.operand(lhs, 1)?;
+                        let rhs = self.comparison_operand(rhs, 2)?;
+                        let opcode = if ty.is_floating() { "fcmp" } else { "cmp" };
+                        self.asm.push(format!("{} {}, {}", opcode, lhs, rhs))?;
+                    }
+                    _ => self.generate_instruction(inst)?,
+                }
+            }
+            match (fused, &block.terminator) {
+                (Some(index), Terminator::Branch(_, then_block, else_block)) => {
+                    let Instruction::Binary(_, op, lhs, _) = &block.instructions[index] else {
+                        unreachable!("Only the comparisons are fused");
+                    };
+                    let cond = comparison_condition(*op, self.func.operand_type(lhs)).unwrap();
+                    self.generate_conditional_jump(cond, *then_block, *else_block, next)?;
+                }
+                _ => self.generate_terminator(&block.terminator, next)?,
+            }
+        }
+
+        self.asm.push(format!("{}:", self.return_label()))?;
+        for (register, offset) in &self.frame.saved {
+            self.asm
+                .push(format!("ldr {}, [sp, #{}]", register, offset))?;
+        }
+        if self.frame.size > 0 {
+            self.asm.push(format!("add sp, sp, #{}", self.frame.size))?;
+        }
+        if makes_calls {
+            self.asm.push("ldp x29, x30, [sp], #16")?;
+        }
+        self.asm.push("ret")?;
+        Ok(())
+    }
+
+    fn temp_register(&self, temp: Temp) -> Option<String> {
+        self.allocation.register(temp, self.func.temps[temp.0])
+    }
+
+    /// The register that holds the operand: its own register, or the one of
+    /// its type with the given index that it's loaded to.
+    fn operand(&mut self, operand: &Operand, idx: usize) -> CodegenResult<String> {
+        if let Operand::Temp(temp) = operand {
+            if let Some(register) = self.temp_register(*temp) {
+                return Ok(register);
+            }
+        }
+        self.load(operand, idx)
+    }
+
+    /// Move the operand to the register of its type with the given index.
+    fn load(&mut self, operand: &Operand, idx: usize) -> CodegenResult<String> {
+        let ty = self.func.operand_type(operand);
+        let register = ty.register(idx);
+        match operand {
+            Operand::Temp(temp) => match self.temp_register(*temp) {
+                Some(src) => self.move_register(ty, &register, &src)?,
+                None => self.asm.push(format!(
+                    "ldr {}, [sp, #{}]",
+                    register, self.frame.temps[temp.0]
+                ))?,
+            },
+            Operand::Const(Value::Int(v)) => generate_int_constant(self.asm, &register, *v)?,
+            Operand::Const(Value::Float(v)) => {
+                generate_floating_constant(self.asm, v.to_bits() as u64, ty, idx)?
+            }
+            Operand::Const(Value::Double(v)) => {
+                generate_floating_constant(self.asm, v.to_bits(), ty, idx)?
+            }
+        }
+        Ok(register)
+    }
+
+    /// The second operand of `cmp` or `fcmp`: the immediates that they can
+    /// encode aren't moved to a register first.
+    fn comparison_operand(&mut self, operand: &Operand, idx: usize) -> CodegenResult<String> {
+        match operand {
+            Operand::Const(Value::Int(v)) if (0..4096).contains(v) => Ok(format!("#{}", v)),
+            Operand::Const(Value::Float(v)) if *v == 0.0 => Ok("#0.0".to_string()),
+            Operand::Const(Value::Double(v)) if *v == 0.0 => Ok("#0.0".to_string()),
+            _ => self.operand(operand, idx),
+        }
+    }
+
+    fn move_register(&mut self, ty: Type, dst: &str, src: &str) -> CodegenResult<()> {
+        if dst == src {
+            return Ok(());
+        }
+        let opcode = if ty.is_floating() { "fmov" } else { "mov" };
+        self.asm.push(format!("{} {}, {}", opcode, dst, src))?;
+        Ok(())
+    }
+
+    /// The register that the result of an instruction is computed to: the
+    /// one of the temporary, or the first register of its type if it's
+    /// spilled.
+    fn dest(&self, temp: Temp) -> String {
+        self.temp_register(temp)
+            .unwrap_or_else(|| self.func.temps[temp.0].register(0))
+    }
+
+    /// Store the result of an instruction if the temporary is spilled.
+    fn store(&mut self, temp: Temp) -> CodegenResult<()> {
+        if self.allocation.registers[temp.0].is_none() {
+            let register = self.func.temps[temp.0].register(0);
+            self.asm.push(format!(
+                "str {}, [sp, #{}]",
+                register, self.frame.temps[temp.0]
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn compare_with_zero(&mut self, register: &str, ty: Type) -> CodegenResult<()> {
+        match ty {
+            Type::Int => self.asm.push(format!("cmp {}, #0", register))?,
+            Type::Float | Type::Double => self.asm.push(format!("fcmp {}, #0.0", register))?,
+        }
+        Ok(())
+    }
+
+    fn generate_instruction(&mut self, inst: &Instruction) -> CodegenResult<()> {
+        match inst {
+            Instruction::Copy(dest, src) => {
+                let ty = self.func.temps[dest.0];
+                match self.allocation.registers[dest.0] {
+                    // The value goes straight to the register.
+                    Some(register) => {
+                        self.load(src, register)?;
+                    }
+                    None => {
+                        let src = self.operand(src, 0)?;
+                        self.move_register(ty, &ty.register(0), &src)?;
+                    }
+                }
+                self.store(*dest)?;
+            }
+            Instruction::Unary(dest, op, src) => {
+                let ty = self.func.operand_type(src);
+                let src = self.operand(src, 1)?;
+                let dst = self.dest(*dest);
+                match op {
+                    UnaryOp::Negation if ty.is_floating() => {
+                        self.asm.push(format!("fneg {}, {}", dst, src))?
+                    }
+                    UnaryOp::Negation => self.asm.push(format!("neg {}, {}", dst, src))?,
+                    UnaryOp::BitwiseComplement if ty.is_floating() => {
+                        return Err(CodegenError::InvalidUnaryOperand(*op, ty, None))
+                    }
+                    UnaryOp::BitwiseComplement => self.asm.push(format!("mvn {}, {}", dst, src))?,
+                    UnaryOp::LogicalNegation => {
+                        self.compare_with_zero(&src, ty)?;
+                        self.asm.push(format!("mov {}, wzr", dst))?;
+                        self.asm.push(format!("cset {}, eq", dst))?;
+                    }
+                }
+                self.store(*dest)?;
+            }
+            Instruction::Binary(dest, op, lhs, rhs) => {
+                let ty = self.func.operand_type(lhs);
+                let lhs = self.operand(lhs, 1)?;
+                let rhs = if op.is_comparison_op() {
+                    self.comparison_operand(rhs, 2)?
+                } else {
+                    self.operand(rhs, 2)?
+                };
+                let dst = self.dest(*dest);
+                if ty.is_floating() {
+                    self.generate_floating_binary_op(*op, ty, &dst, &lhs, &rhs)?;
+                } else {
+                    self.generate_int_binary_op(*op, &dst, &lhs, &rhs)?;
+                }
+                self.store(*dest)?;
+            }
+            Instruction::Convert(dest, src) => {
+                let from = self.func.operand_type(src);
+                let to = self.func.temps[dest.0];
+                let src = self.operand(src, 1)?;
+                let dst = self.dest(*dest);
+                let opcode = match (from, to) {
+                    (Type::Int, _) => "scvtf",
+                    (_, Type::Int) => "fcvtzs",
+                    _ => "fcvt",
+                };
+                self.asm.push(format!("{} {}, {}", opcode, dst, src))?;
+                self.store(*dest)?;
+            }
+            Instruction::Load(dest, slot) => {
+                let dst = self.dest(*dest);
+                self.asm
+                    .push(format!("ldr {}, [sp, #{}]", dst, self.frame.slots[slot.0]))?;
+                self.store(*dest)?;
+            }
+            Instruction::Store(slot, src) => {
+                let src = self.operand(src, 0)?;
+                self.asm
+                    .push(format!("str {}, [sp, #{}]", src, self.frame.slots[slot.0]))?;
+            }
+            Instruction::Call(dest, name, args) => {
+                let types: Vec<Type> = args.iter().map(|arg| self.func.operand_type(arg)).collect();
+                // The arguments are in the callee-saved registers, in x9-x15
+                // and d16-d31 if the call is their last use, on the stack or
+                // constants. None of them are argument registers, so loading
+                // one doesn't clobber the others.
+                let indices = arg_register_indices(name, &types, None)?;
+                for (arg, idx) in args.iter().zip(indices) {
+                    self.load(arg, idx)?;
+                }
+                self.asm.push(format!("bl _{}", name))?;
+                let ty = self.func.temps[dest.0];
+                let dst = self.dest(*dest);
+                self.move_register(ty, &dst, &ty.register(0))?;
+                self.store(*dest)?;
+            }
+            Instruction::Param(dest, index) => {
+                // The parameters come first, before the argument registers
+                // are used for anything else.
+                let ty = self.func.temps[dest.0];
+                let src = ty.register(self.param_registers[*index]);
+                match self.temp_register(*dest) {
+                    Some(dst) => self.move_register(ty, &dst, &src)?,
+                    None => self
+                        .asm
+                        .push(format!("str {}, [sp, #{}]", src, self.frame.temps[dest.0]))?,
+                }
+            }
+            Instruction::Phi(..) => unreachable!("The phis are removed before the codegen"),
+        }
+        Ok(())
+    }
+
+    /// lhs and rhs are in registers, the result goes to dst.
+    fn generate_int_binary_op(
+        &mut self,
+        op: BinaryOp,
+        dst: &str,
+        lhs: &str,
+        rhs: &str,
+    ) -> CodegenResult<()> {
+        let opcode = match op {
+            BinaryOp::Addition => "add",
+            BinaryOp::Subtraction => "sub",
+            BinaryOp::Multiplication => "mul",
+            BinaryOp::Division => "sdiv",
+            BinaryOp::BitwiseAnd => "and",
+            BinaryOp::BitwiseOr => "orr",
+            BinaryOp::BitwiseXor => "eor",
+            BinaryOp::BitwiseShiftLeft => "lsl",
+            // Right shift of a negative value is implementation defined, GCC
+            // and Clang do an arithmetic shift.
+            BinaryOp::BitwiseShiftRight => "asr",
+            BinaryOp::Modulo => {
+                self.asm.push(format!("sdiv w3, {}, {}", lhs, rhs))?;
+                self.asm
+                    .push(format!("msub {}, w3, {}, {}", dst, rhs, lhs))?;
+                return Ok(());
+            }
+            BinaryOp::Equal
+            | BinaryOp::NotEqual
+            | BinaryOp::LessThan
+            | BinaryOp::LessThanOrEq
+            | BinaryOp::GreaterThan
+            | BinaryOp::GreaterThanOrEq => {
+                let cond = comparison_condition(op, Type::Int).unwrap();
+                self.asm.push(format!("cmp {}, {}", lhs, rhs))?;
+                self.asm.push(format!("mov {}, wzr", dst))?;
+                self.asm.push(format!("cset {}, {}", dst, cond))?;
+                return Ok(());
+            }
+            BinaryOp::And | BinaryOp::Or => return Err(CodegenError::UnexpectedBinaryOp(op)),
+        };
+        self.asm
+            .push(format!("{} {}, {}, {}", opcode, dst, lhs, rhs))?;
+        Ok(())
+    }
+
+    fn generate_floating_binary_op(
+        &mut self,
+        op: BinaryOp,
+        ty: Type,
+        dst: &str,
+        lhs: &str,
+        rhs: &str,
+    ) -> CodegenResult<()> {
+        let opcode = match op {
+            BinaryOp::Addition => "fadd",
+            BinaryOp::Subtraction => "fsub",
+            BinaryOp::Multiplication => "fmul",
+            BinaryOp::Division => "fdiv",
+            BinaryOp::Equal
+            | BinaryOp::NotEqual
+            | BinaryOp::LessThan
+            | BinaryOp::LessThanOrEq
+            | BinaryOp::GreaterThan
+            | BinaryOp::GreaterThanOrEq => {
+                let cond = comparison_condition(op, ty).unwrap();
+                self.asm.push(format!("fcmp {}, {}", lhs, rhs))?;
+                self.asm.push(format!("mov {}, wzr", dst))?;
+                self.asm.push(format!("cset {}, {}", dst, cond))?;
+                return Ok(());
+            }
+            other => return Err(CodegenError::InvalidBinaryOperands(other, ty, None)),
+        };
+        self.asm
+            .push(format!("{} {}, {}, {}", opcode, dst, lhs, rhs))?;
+        Ok(())
+    }
+
+    /// The index of the comparison at the end of the block that only its
+    /// branch uses. It sets the flags that the branch reads, so its value
+    /// isn't computed. The copies of the phis that come after it don't change
+    /// the flags.
+    fn fused_comparison(&self, block: &Block) -> Option<usize> {
+        let Terminator::Branch(Operand::Temp(condition), ..) = block.terminator else {
+            return None;
+        };
+        let index = block
+            .instructions
+            .iter()
+            .rposition(|inst| !matches!(inst, Instruction::Copy(..)))?;
+        match block.instructions[index] {
+            Instruction::Binary(dest, op, ..)
+                if dest == condition && op.is_comparison_op() && self.uses[dest.0] == 1 =>
+            {
+                Some(index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Jump to the then block if the condition code is true and to the else
+    /// block otherwise, the jumps to the next block fall through.
+    fn generate_conditional_jump(
+        &mut self,
+        cond: Condition,
+        then_block: BlockId,
+        else_block: BlockId,
+        next: Option<BlockId>,
+    ) -> CodegenResult<()> {
+        if next == Some(then_block) {
+            self.asm
+                .push(format!("b{} {}", cond.invert(), self.label(else_block)))?;
+        } else {
+            self.asm
+                .push(format!("b{} {}", cond, self.label(then_block)))?;
+            if next != Some(else_block) {
+                self.asm.push(format!("b {}", self.label(else_block)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The jumps to the next block fall through.
+    fn generate_terminator(
+        &mut self,
+        terminator: &Terminator,
+        next: Option<BlockId>,
+    ) -> CodegenResult<()> {
+        match terminator {
+            Terminator::Jump(target) => {
+                if next != Some(*target) {
+                    self.asm.push(format!("b {}", self.label(*target)))?;
+                }
+            }
+            Terminator::Branch(condition, then_block, else_block) => {
+                let ty = self.func.operand_type(condition);
+                let register = self.operand(condition, 0)?;
+                self.compare_with_zero(&register, ty)?;
+                self.generate_conditional_jump(Condition::Ne, *then_block, *else_block, next)?;
+            }
+            Terminator::Return(value) => {
+                self.load(value, 0)?;
+                // The epilogue comes right after the last block.
+                if next.is_some() {
+                    self.asm.push(format!("b {}", self.return_label()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ir::{self, OptLevel, PassManager},
+        parser::Parser,
+        tokenizer::Tokenizer,
+    };
+
+    fn generate(src: &str, level: OptLevel) -> String {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let mut module = ir::lower(&Parser::new(token_stream).parse().unwrap()).unwrap();
+        PassManager::new(level).run(&mut module);
+        IrCodegen::new().generate(&module).unwrap()
+    }
+
+    #[test]
+    fn test_block_labels() {
+        let asm = generate(
+            "int main() { int a = 3; while (a) a = a - 1; return a; }",
+            OptLevel::O0,
+        );
+        assert!(asm.contains("Lmain_bb0:"));
+        assert!(asm.contains("cbz w9, Lmain_bb"));
+        assert!(asm.contains("Lmain_return:"));
+    }
+
+    #[test]
+    fn test_compare_and_branch() {
+        // The comparisons set the flags for the branches, and `&&` jumps
+        // straight to the exit without computing its value.
+        let asm = generate(
+            "int main() {
+                int i = 0; int n = 100; int s = 0;
+                while (i < n && s != 50) { s = s + 1; i = i + 1; }
+                return s;
+            }",
+            OptLevel::O2,
+        );
+        assert!(!asm.contains("cset"));
+        assert!(!asm.contains("cbz"));
+        assert!(asm.contains("blt Lmain_bb"));
+        assert!(asm.contains("bne Lmain_bb"));
+
+        // A comparison that is also used as a value is computed.
+        let asm = generate(
+            "int main() { int a = 3; int b = a < 5; if (b) return b; return 2; }",
+            OptLevel::O0,
+        );
+        assert!(asm.contains("cset"));
+    }
+
+    #[test]
+    fn test_compare_and_branch_with_phi_copies() {
+        // The copy of the phi comes after the comparison, it can reuse the
+        // register of the compared value because the flags are already set.
+        let asm = generate(
+            "int f(int a) { int x = 5; if (a >= 10) x = 1; return x; }",
+            OptLevel::O2,
+        );
+        assert_eq!(
+            asm.lines().skip(2).collect::<Vec<_>>().join("\n"),
+            ".globl _f
+.p2align 2
+_f:
+Lf_bb0:
+mov w9, w0
+cmp w9, #10
+mov w9, #5
+blt Lf_bb2
+Lf_bb1:
+mov w9, #1
+Lf_bb2:
+mov w10, w9
+mov w0, w10
+Lf_return:
+ret"
+        );
+    }
+
+    #[test]
+    fn test_registers() {
+        let src = "int f(int n) { int i = 0; while (i < n) i = i + 1; return i; }";
+        // The variables are loaded from their slots without mem2reg.
+        let asm = generate(src, OptLevel::O0);
+        assert!(asm.contains("ldr w9, [sp, #"));
+        assert!(asm.contains("str w0, [sp, #"));
+
+        // Without calls, the values stay in the caller-saved registers and
+        // the function has no frame.
+        let asm = generate(src, OptLevel::O2);
+        assert!(!asm.contains("sp"));
+
+        // Only the callee-saved registers of the values that are live across
+        // a call are saved and restored.
+        let src = "int g(int x); int f(int n) { int s = g(n); return s + n; }";
+        let asm = generate(src, OptLevel::O2);
+        let memory_accesses: Vec<&str> = asm.lines().filter(|line| line.contains("[sp")).collect();
+        assert_eq!(
+            memory_accesses,
+            [
+                "stp x29, x30, [sp, #-16]!",
+                "str x19, [sp, #0]",
+                "ldr x19, [sp, #0]",
+                "ldp x29, x30, [sp], #16",
+            ]
+        );
+    }
+}
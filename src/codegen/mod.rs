@@ -2,17 +2,38 @@ pub mod asm;
 mod error;
 mod func;
 mod helpers;
+mod ir;
+pub mod peephole;
+mod regalloc;
 
-pub use self::{asm::Assembly, func::*};
-use self::{error::CodegenError, helpers::*};
-use crate::parser::*;
+use std::collections::HashMap;
+
+pub use self::{
+    asm::Assembly,
+    error::{AsmError, CodegenError},
+    ir::IrCodegen,
+};
+use self::{asm::Condition, func::*, helpers::*};
+use crate::{cfg::CfgBuilder, parser::*, tokenizer::Span};
 
 type CodegenResult<T> = Result<T, CodegenError>;
 
+/// Number of the argument registers for each register class, e.g. w0-w7 and v0-v7.
+pub const MAX_REG_ARGS: usize = 8;
+
 #[derive(Debug, PartialEq)]
 pub struct ARMCodegen {
     asm: Assembly,
     funcs: Vec<CodegenFunction>,
+    signatures: HashMap<String, FuncSignature>,
+    /// Whether the assembly goes through the peephole optimizer.
+    peephole: bool,
+}
+
+impl Default for ARMCodegen {
+    fn default() -> ARMCodegen {
+        ARMCodegen::new()
+    }
 }
 
 impl ARMCodegen {
@@ -20,11 +41,21 @@ impl ARMCodegen {
         ARMCodegen {
             asm: Assembly::new(),
             funcs: Vec::new(),
+            signatures: HashMap::new(),
+            peephole: true,
         }
     }
 
-    pub fn generate(mut self, program: Program) -> CodegenResult<String> {
+    pub fn with_peephole(mut self, peephole: bool) -> ARMCodegen {
+        self.peephole = peephole;
+        self
+    }
+
+    pub fn generate(mut self, program: &Program) -> CodegenResult<String> {
         self.generate_program(program)?;
+        if self.peephole {
+            peephole::optimize(&mut self.asm);
+        }
         Ok(self.asm.to_string())
     }
 
@@ -36,58 +67,128 @@ impl ARMCodegen {
         self.funcs.last_mut().ok_or(CodegenError::NoFunctionFound)
     }
 
-    fn generate_program(&mut self, program: Program) -> CodegenResult<()> {
+    fn get_var(&self, name: &str, span: Span) -> CodegenResult<&CodegenVar> {
+        self.get_current_func()?
+            .stack
+            .var_map
+            .get(name)
+            .ok_or(CodegenError::VarNotFound(name.to_string(), span))
+    }
+
+    /// Create the labels of a construct in the current function. They share
+    /// the same number, e.g. `Lmain_while_start_3` and `Lmain_while_end_3`.
+    fn new_labels<const N: usize>(&mut self, kinds: [&str; N]) -> CodegenResult<[String; N]> {
+        let func = self.get_current_func_mut()?;
+        let id = func.label_count;
+        func.label_count += 1;
+        Ok(kinds.map(|kind| format!("L{}_{}_{}", func.name, kind, id)))
+    }
+
+    /// Reserve the next stack slot for an intermediate value and return its offset.
+    fn push_op_slot(&mut self) -> CodegenResult<usize> {
+        let func = self.get_current_func_mut()?;
+        let depth = func.op_stack_depth;
+        let op_var = func
+            .stack
+            .var_map
+            .get(&format!("op_{}", depth))
+            .ok_or(CodegenError::OpSlotNotFound(depth))?;
+        func.op_stack_depth += 1;
+        op_var.get_stack_offset()
+    }
+
+    fn pop_op_slots(&mut self, count: usize) -> CodegenResult<()> {
+        self.get_current_func_mut()?.op_stack_depth -= count;
+        Ok(())
+    }
+
+    fn generate_program(&mut self, program: &Program) -> CodegenResult<()> {
         // Header.
         self.asm
-            .push(".section __TEXT,__text,regular,pure_instructions");
+            .push(".section __TEXT,__text,regular,pure_instructions")?;
         self.asm
-            .push(".build_version macos, 13, 0 sdk_version 13, 3");
+            .push(".build_version macos, 13, 0 sdk_version 13, 3")?;
+
+        // Calls need the parameter types of the functions that are defined later.
+        for func in &program.functions {
+            self.signatures.insert(
+                func.name.clone(),
+                FuncSignature {
+                    return_ty: func.return_ty,
+                    params: func.params.iter().map(|param| param.ty).collect(),
+                },
+            );
+        }
 
-        // main function.
-        self.generate_function(program.function)?;
+        for func in &program.functions {
+            // Declarations don't generate any code.
+            if func.is_definition() {
+                self.generate_function(func)?;
+            }
+        }
         Ok(())
     }
 
-    fn generate_function(&mut self, func: Function) -> CodegenResult<()> {
-        self.asm.push(format!(".globl _{}", func.name));
-        self.asm.push(".p2align 2");
-        self.asm.push(format!("_{}:", func.name));
+    fn generate_function(&mut self, func: &Function) -> CodegenResult<()> {
+        self.asm.push(format!(".globl _{}", func.name))?;
+        self.asm.push(".p2align 2")?;
+        self.asm.push(format!("_{}:", func.name))?;
 
-        self.funcs.push(CodegenFunction::new(&func.body)?);
+        self.funcs.push(CodegenFunction::new(func)?);
+        let stack = &self.get_current_func()?.stack;
+        let stack_size = stack.size;
+        let makes_calls = stack.makes_calls;
 
-        // Push the stack in the function prologue.
-        self.asm.push(format!(
-            "sub sp, sp, #{}",
-            self.funcs.last().unwrap().stack.size
-        ));
-        self.generate_block(&func.body)?;
-        // Pop the stack in the function epilogue.
-        self.asm.push(format!(
-            "add sp, sp, #{}",
-            self.funcs.last().unwrap().stack.size
-        ));
+        if makes_calls {
+            // Save the frame pointer and the link register, `bl` overwrites the latter.
+            self.asm.push("stp x29, x30, [sp, #-16]!")?;
+            self.asm.push("mov x29, sp")?;
+        }
+        // Push the stack in the function prologue, if there's anything in it.
+        if stack_size > 0 {
+            self.asm.push(format!("sub sp, sp, #{}", stack_size))?;
+        }
 
-        if self.funcs.len() == 1 {
-            // If there is only one function, that means that it's the main function.
-            // TODO: This is not the best way of checking if the main has no return.
-            // We should improve this.
-            let function_has_return = block_has_return(&func.body.items);
-            if !function_has_return {
-                // If the main function doesn't have a return statement, we need to
-                // return 0 as per the C standard. But that's not the case for the other
-                // functions.
-                self.asm.push("mov w0, #0");
-            }
+        // Spill the arguments to their stack slots.
+        let param_types: Vec<Type> = func.params.iter().map(|param| param.ty).collect();
+        let registers = arg_registers(&func.name, &param_types, func.span)?;
+        for (param, register) in func.params.iter().zip(registers) {
+            let name = param
+                .name
+                .as_ref()
+                .ok_or(CodegenError::UnnamedParameter(func.name.clone(), func.span))?;
+            let offset = self.get_var(name, func.span)?.get_stack_offset()?;
+            self.asm
+                .push(format!("str {}, [sp, #{}]", register, offset))?;
+        }
+
+        let body = func.body.as_ref().ok_or(CodegenError::NoFunctionFound)?;
+        self.generate_block(body)?;
+
+        // If the end of the main function is reached, it returns 0 as per the
+        // C standard. But that's not the case for the other functions.
+        if func.name == "main" && CfgBuilder::new().build_function(func)?.falls_off_end() {
+            self.asm.push("mov w0, #0")?;
+        }
+
+        let return_label = self.get_current_func()?.return_label.clone();
+        self.asm.push(format!("{}:", return_label))?;
+        // Pop the stack in the function epilogue.
+        if stack_size > 0 {
+            self.asm.push(format!("add sp, sp, #{}", stack_size))?;
+        }
+        if makes_calls {
+            self.asm.push("ldp x29, x30, [sp], #16")?;
         }
 
         self.funcs.pop();
-        self.asm.push("ret");
+        self.asm.push("ret")?;
         Ok(())
     }
 
     fn generate_block_item(&mut self, block_item: &BlockItem) -> CodegenResult<()> {
         match block_item {
-            BlockItem::Statement(stmt) => self.generate_statement(stmt)?,
+            BlockItem::Statement(stmt, _) => self.generate_statement(stmt)?,
             BlockItem::Declaration(var_decl) => {
                 self.generate_declaration(var_decl)?;
             }
@@ -97,12 +198,18 @@ impl ARMCodegen {
 
     fn generate_statement(&mut self, stmt: &Statement) -> CodegenResult<()> {
         match stmt {
-            Statement::Return(expr) => match expr.as_ref() {
-                Expr::Constant(Constant::Int(int)) => {
-                    self.asm.push(format!("mov w0, #{}", int));
+            Statement::Return(expr) => {
+                let return_ty = self.get_current_func()?.return_ty;
+                match expr.as_ref() {
+                    Expr::Constant(Constant::Int(int)) if return_ty == Type::Int => {
+                        self.generate_int_constant(*int)?;
+                    }
+                    expression => self.generate_expr_as(expression, return_ty)?,
                 }
-                expression => self.generate_expr(expression)?,
-            },
+                // Jump to the epilogue to restore the stack before returning.
+                let return_label = &self.get_current_func()?.return_label;
+                self.asm.push(format!("b {}", return_label))?;
+            }
             Statement::Expression(expr) => {
                 self.generate_expr(expr)?;
             }
@@ -113,98 +220,263 @@ impl ARMCodegen {
             Statement::While(expr, stmt) => self.generate_while(expr, stmt)?,
             Statement::DoWhile(stmt, expr) => self.generate_do_while(stmt, expr)?,
             Statement::For(for_loop) => self.generate_for(for_loop)?,
-            Statement::Break => {
+            Statement::Break(span) => {
                 let cur_loop = self
                     .get_current_func()?
                     .loops
                     .last()
-                    .ok_or(CodegenError::NoLoopFoundForBreak)?;
-                self.asm.push(format!("b {}", cur_loop.end_label));
+                    .ok_or(CodegenError::NoLoopFoundForBreak(*span))?;
+                self.asm.push(format!("b {}", cur_loop.end_label))?;
             }
-            Statement::Continue => {
+            Statement::Continue(span) => {
                 let cur_loop = self
                     .get_current_func()?
                     .loops
                     .last()
-                    .ok_or(CodegenError::NoLoopFoundForContinue)?;
-                self.asm.push(format!("b {}", cur_loop.start_label));
+                    .ok_or(CodegenError::NoLoopFoundForContinue(*span))?;
+                self.asm.push(format!("b {}", cur_loop.continue_label))?;
             }
             Statement::Null => {}
+            Statement::Error => return Err(CodegenError::UnexpectedErrorNode),
         }
         Ok(())
     }
 
     fn generate_declaration(&mut self, var_decl: &VarDecl) -> CodegenResult<()> {
         if let Some(expr) = &var_decl.initializer {
-            self.generate_expr(expr)?;
+            self.generate_expr_as(expr, var_decl.ty)?;
         } else {
-            self.asm.push("mov w0, #0");
+            self.generate_zero(var_decl.ty)?;
         }
 
-        let codegen_var = self
-            .get_current_func()?
-            .stack
-            .var_map
-            .get(&var_decl.name)
-            .ok_or(CodegenError::VarNotFound(var_decl.name.clone()))?;
+        let codegen_var = self.get_var(&var_decl.name, var_decl.span)?;
 
         match codegen_var {
             CodegenVar::StackVar(stack_var) => {
+                self.asm.push(format!(
+                    "str {}, [sp, #{}]",
+                    stack_var.ty.register(0),
+                    stack_var.offset
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the type of the expression's value without generating any code.
+    fn expr_type(&self, expr: &Expr) -> CodegenResult<Type> {
+        match expr {
+            Expr::Constant(Constant::Float(_)) => Ok(Type::Float),
+            Expr::Constant(Constant::Double(_)) => Ok(Type::Double),
+            Expr::Constant(_) => Ok(Type::Int),
+            Expr::Var(name, span) | Expr::Assignment(name, _, span) => {
+                Ok(self.get_var(name, *span)?.get_type())
+            }
+            Expr::UnaryOp(UnaryOp::Negation, expr, _) => self.expr_type(expr),
+            Expr::UnaryOp(UnaryOp::BitwiseComplement | UnaryOp::LogicalNegation, ..) => {
+                Ok(Type::Int)
+            }
+            Expr::BinaryOp(op, lhs, rhs, _) => {
+                if op.is_arithmetic_op() {
+                    Ok(self.expr_type(lhs)?.common(self.expr_type(rhs)?))
+                } else {
+                    // Comparisons, logical and bitwise operators all result in an int.
+                    Ok(Type::Int)
+                }
+            }
+            Expr::TernaryConditional(ternary) => Ok(self
+                .expr_type(&ternary.if_expr)?
+                .common(self.expr_type(&ternary.else_expr)?)),
+            Expr::FunctionCall(name, _, _) => Ok(self
+                .signatures
+                .get(name)
+                // Implicitly declared functions return int.
+                .map_or(Type::Int, |signature| signature.return_ty)),
+            Expr::Null => Ok(Type::Int),
+        }
+    }
+
+    /// Generate the expression and convert its value to the given type.
+    fn generate_expr_as(&mut self, expr: &Expr, ty: Type) -> CodegenResult<()> {
+        let expr_ty = self.expr_type(expr)?;
+        self.generate_expr(expr)?;
+        self.generate_conversion(expr_ty, ty)?;
+        Ok(())
+    }
+
+    fn generate_conversion(&mut self, from: Type, to: Type) -> CodegenResult<()> {
+        match (from, to) {
+            (Type::Int, Type::Float) => self.asm.push("scvtf s0, w0")?,
+            (Type::Int, Type::Double) => self.asm.push("scvtf d0, w0")?,
+            (Type::Float, Type::Int) => self.asm.push("fcvtzs w0, s0")?,
+            (Type::Double, Type::Int) => self.asm.push("fcvtzs w0, d0")?,
+            (Type::Float, Type::Double) => self.asm.push("fcvt d0, s0")?,
+            (Type::Double, Type::Float) => self.asm.push("fcvt s0, d0")?,
+            (Type::Int, Type::Int) | (Type::Float, Type::Float) | (Type::Double, Type::Double) => {}
+        }
+        Ok(())
+    }
+
+    fn generate_zero(&mut self, ty: Type) -> CodegenResult<()> {
+        Ok(generate_zero(&mut self.asm, ty, 0)?)
+    }
+
+    fn generate_int_constant(&mut self, int: i32) -> CodegenResult<()> {
+        Ok(generate_int_constant(&mut self.asm, "w0", int)?)
+    }
+
+    fn generate_floating_constant(&mut self, bits: u64, ty: Type) -> CodegenResult<()> {
+        Ok(generate_floating_constant(&mut self.asm, bits, ty, 0)?)
+    }
+
+    /// Compare the value of the given type in the first register with zero.
+    fn generate_compare_with_zero(&mut self, ty: Type) -> CodegenResult<()> {
+        match ty {
+            Type::Int => self.asm.push("cmp w0, #0")?,
+            Type::Float | Type::Double => {
+                self.asm.push(format!("fcmp {}, #0.0", ty.register(0)))?
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate both of the operands as the given type, lhs goes to the
+    /// second register and rhs to the first one.
+    fn generate_operands(&mut self, lhs: &Expr, rhs: &Expr, ty: Type) -> CodegenResult<()> {
+        self.generate_expr_as(lhs, ty)?;
+        let stack_offset = self.push_op_slot()?;
+
+        // We first push the value to the stack.
+        self.asm
+            .push(format!("str {}, [sp, #{}]", ty.register(0), stack_offset))?;
+        self.generate_expr_as(rhs, ty)?;
+        // And then we pop it back to the second register.
+        self.asm
+            .push(format!("ldr {}, [sp, #{}]", ty.register(1), stack_offset))?;
+        self.pop_op_slots(1)?;
+        Ok(())
+    }
+
+    /// Jump to the label if the condition is `when`, and fall through
+    /// otherwise. The comparisons branch on their flags and `&&`, `||` and
+    /// `!` become chains of jumps, so the booleans aren't materialized.
+    fn generate_branch(&mut self, condition: &Expr, when: bool, label: &str) -> CodegenResult<()> {
+        match condition {
+            // The missing condition of a `for` loop is always true.
+            Expr::Null => {
+                if when {
+                    self.asm.push(format!("b {}", label))?;
+                }
+            }
+            Expr::Constant(Constant::Int(int)) => {
+                if (*int != 0) == when {
+                    self.asm.push(format!("b {}", label))?;
+                }
+            }
+            Expr::UnaryOp(UnaryOp::LogicalNegation, expr, _) => {
+                self.generate_branch(expr, !when, label)?;
+            }
+            Expr::BinaryOp(op, lhs, rhs, _) if op.is_short_circuiting_op() => {
+                // `a && b` is false as soon as `a` is false, and `a || b` is
+                // true as soon as `a` is true.
+                if (*op == BinaryOp::Or) == when {
+                    self.generate_branch(lhs, when, label)?;
+                    self.generate_branch(rhs, when, label)?;
+                } else {
+                    let kind = match op {
+                        BinaryOp::And => "and_skip",
+                        _ => "or_skip",
+                    };
+                    let [skip_label] = self.new_labels([kind])?;
+                    self.generate_branch(lhs, !when, &skip_label)?;
+                    self.generate_branch(rhs, when, label)?;
+                    self.asm.push(format!("{}:", skip_label))?;
+                }
+            }
+            Expr::BinaryOp(op, lhs, rhs, _) if op.is_comparison_op() => {
+                let ty = self.expr_type(lhs)?.common(self.expr_type(rhs)?);
+                self.generate_operands(lhs, rhs, ty)?;
+                let opcode = if ty.is_floating() { "fcmp" } else { "cmp" };
                 self.asm
-                    .push(format!("str w0, [sp, #{}]", stack_var.offset));
+                    .push(format!("{} {}, {}", opcode, ty.register(1), ty.register(0)))?;
+                let cond = comparison_condition(*op, ty).unwrap();
+                let cond = if when { cond } else { cond.invert() };
+                self.asm.push(format!("b{} {}", cond, label))?;
+            }
+            _ => {
+                self.generate_condition(condition)?;
+                let opcode = if when { "bne" } else { "beq" };
+                self.asm.push(format!("{} {}", opcode, label))?;
             }
         }
+        Ok(())
+    }
 
+    /// Generate the expression and set the condition flags by comparing it with zero.
+    fn generate_condition(&mut self, expr: &Expr) -> CodegenResult<()> {
+        let ty = self.expr_type(expr)?;
+        self.generate_expr(expr)?;
+        self.generate_compare_with_zero(ty)?;
         Ok(())
     }
+
     fn generate_expr(&mut self, expr: &Expr) -> CodegenResult<()> {
         match expr {
             Expr::Constant(Constant::Int(int)) => {
-                self.asm.push(format!("mov w0, #{}", int));
+                self.generate_int_constant(*int)?;
                 Ok(())
             }
-            Expr::Constant(_) => {
-                // TODO: Support the other types later.
-                todo!("Only integer constants are supported")
+            Expr::Constant(Constant::Float(float)) => {
+                self.generate_floating_constant(float.to_bits() as u64, Type::Float)?;
+                Ok(())
             }
-            Expr::UnaryOp(unary_op, expr) => {
-                self.generate_unary_op(unary_op, expr)?;
+            Expr::Constant(Constant::Double(double)) => {
+                self.generate_floating_constant(double.to_bits(), Type::Double)?;
                 Ok(())
             }
-            Expr::BinaryOp(binary_op, lhs, rhs) => {
-                self.generate_binary_op(binary_op, lhs, rhs)?;
+            Expr::Constant(Constant::Bool(bool)) => {
+                self.generate_int_constant(*bool as i32)?;
                 Ok(())
             }
-            Expr::Var(var_name) => {
-                let codegen_var = self
-                    .get_current_func()?
-                    .stack
-                    .var_map
-                    .get(var_name)
-                    .ok_or(CodegenError::VarNotFound(var_name.clone()))?;
+            Expr::Constant(constant @ Constant::String(_)) => {
+                Err(CodegenError::UnsupportedConstant(constant.clone()))
+            }
+            Expr::UnaryOp(unary_op, expr, span) => {
+                self.generate_unary_op(unary_op, expr, *span)?;
+                Ok(())
+            }
+            Expr::BinaryOp(binary_op, lhs, rhs, span) => {
+                self.generate_binary_op(binary_op, lhs, rhs, *span)?;
+                Ok(())
+            }
+            Expr::Var(var_name, span) => {
+                let codegen_var = self.get_var(var_name, *span)?;
 
                 match codegen_var {
                     CodegenVar::StackVar(stack_var) => {
-                        self.asm
-                            .push(format!("ldr w0, [sp, #{}]", stack_var.offset));
+                        self.asm.push(format!(
+                            "ldr {}, [sp, #{}]",
+                            stack_var.ty.register(0),
+                            stack_var.offset
+                        ))?;
                     }
                 }
                 Ok(())
             }
-            Expr::Assignment(name, expr) => {
-                self.generate_expr(expr)?;
+            Expr::Assignment(name, expr, span) => {
+                let var_ty = self.get_var(name, *span)?.get_type();
+                self.generate_expr_as(expr, var_ty)?;
 
-                let codegen_var = self
-                    .get_current_func()?
-                    .stack
-                    .var_map
-                    .get(name)
-                    .ok_or(CodegenError::VarNotFound(name.clone()))?;
+                let codegen_var = self.get_var(name, *span)?;
 
                 match codegen_var {
                     CodegenVar::StackVar(stack_var) => {
-                        self.asm
-                            .push(format!("str w0, [sp, #{}]", stack_var.offset));
+                        self.asm.push(format!(
+                            "str {}, [sp, #{}]",
+                            stack_var.ty.register(0),
+                            stack_var.offset
+                        ))?;
                     }
                 }
                 Ok(())
@@ -213,24 +485,84 @@ impl ARMCodegen {
                 self.generate_ternary_cond_expr(ternary)?;
                 Ok(())
             }
+            Expr::FunctionCall(name, args, span) => {
+                self.generate_function_call(name, args, *span)?;
+                Ok(())
+            }
             Expr::Null => Ok(()),
         }
     }
 
-    fn generate_unary_op(&mut self, unary_op: &UnaryOp, expr: &Expr) -> CodegenResult<()> {
+    fn generate_function_call(
+        &mut self,
+        name: &str,
+        args: &[Expr],
+        span: Span,
+    ) -> CodegenResult<()> {
+        let param_types = match self.signatures.get(name) {
+            Some(signature) => signature.params.clone(),
+            None => {
+                // Implicitly declared functions get the default argument promotions.
+                let mut types = vec![];
+                for arg in args {
+                    types.push(match self.expr_type(arg)? {
+                        Type::Float => Type::Double,
+                        other => other,
+                    });
+                }
+                types
+            }
+        };
+        let registers = arg_registers(name, &param_types, span)?;
+
+        // Evaluate all the arguments first and keep them in the stack, because
+        // evaluating an argument can clobber the argument registers.
+        let mut offsets = vec![];
+        for (arg, ty) in args.iter().zip(&param_types) {
+            self.generate_expr_as(arg, *ty)?;
+            let stack_offset = self.push_op_slot()?;
+            self.asm
+                .push(format!("str {}, [sp, #{}]", ty.register(0), stack_offset))?;
+            offsets.push(stack_offset);
+        }
+
+        for (register, offset) in registers.iter().zip(&offsets) {
+            self.asm
+                .push(format!("ldr {}, [sp, #{}]", register, offset))?;
+        }
+        self.pop_op_slots(args.len())?;
+
+        self.asm.push(format!("bl _{}", name))?;
+        Ok(())
+    }
+
+    fn generate_unary_op(
+        &mut self,
+        unary_op: &UnaryOp,
+        expr: &Expr,
+        span: Span,
+    ) -> CodegenResult<()> {
+        let ty = self.expr_type(expr)?;
         self.generate_expr(expr)?;
 
         match unary_op {
+            UnaryOp::Negation if ty.is_floating() => {
+                self.asm
+                    .push(format!("fneg {}, {}", ty.register(0), ty.register(0)))?;
+            }
             UnaryOp::Negation => {
-                self.asm.push("neg w0, w0");
+                self.asm.push("neg w0, w0")?;
+            }
+            UnaryOp::BitwiseComplement if ty.is_floating() => {
+                return Err(CodegenError::InvalidUnaryOperand(*unary_op, ty, Some(span)));
             }
             UnaryOp::BitwiseComplement => {
-                self.asm.push("mvn w0, w0");
+                self.asm.push("mvn w0, w0")?;
             }
             UnaryOp::LogicalNegation => {
-                self.asm.push("cmp w0, #0");
-                self.asm.push("mov w0, wzr");
-                self.asm.push("cset w0, eq");
+                self.generate_compare_with_zero(ty)?;
+                self.asm.push("mov w0, wzr")?;
+                self.asm.push("cset w0, eq")?;
             }
         }
         Ok(())
@@ -241,125 +573,127 @@ impl ARMCodegen {
         binary_op: &BinaryOp,
         lhs: &Expr,
         rhs: &Expr,
+        span: Span,
     ) -> CodegenResult<()> {
-        self.generate_expr(lhs)?;
-
         if binary_op.is_short_circuiting_op() {
+            self.generate_condition(lhs)?;
             self.generate_short_circuiting_op(binary_op, rhs)?;
             return Ok(());
         }
 
-        let func = self.get_current_func_mut()?;
-        let op_var = func
-            .stack
-            .var_map
-            .get(&format!("op_{}", func.op_stack_depth))
-            .unwrap();
-        func.op_stack_depth += 1;
-        let stack_offset = op_var.get_stack_offset()?;
+        // Both of the operands are converted to their common type.
+        let ty = self.expr_type(lhs)?.common(self.expr_type(rhs)?);
+        if ty.is_floating() {
+            return self.generate_floating_binary_op(binary_op, lhs, rhs, ty, span);
+        }
 
-        // We first push the value to the stack.
-        self.asm.push(format!("str w0, [sp, #{}]", stack_offset));
-        self.generate_expr(rhs)?;
-        // And then we pop it back to w1.
-        self.asm.push(format!("ldr w1, [sp, #{}]", stack_offset));
-        self.get_current_func_mut()?.op_stack_depth -= 1;
+        self.generate_operands(lhs, rhs, ty)?;
 
         // lhs is in w1, rhs is in w0.
+        if let Some(cond) = comparison_condition(*binary_op, ty) {
+            self.asm.push("cmp w1, w0")?;
+            self.asm.push("mov w0, wzr")?;
+            self.asm.push(format!("cset w0, {}", cond))?;
+            return Ok(());
+        }
         match binary_op {
-            BinaryOp::Addition => self.asm.push("add w0, w1, w0"),
-            BinaryOp::Subtraction => self.asm.push("sub w0, w1, w0"),
-            BinaryOp::Multiplication => self.asm.push("mul w0, w1, w0"),
+            BinaryOp::Addition => self.asm.push("add w0, w1, w0")?,
+            BinaryOp::Subtraction => self.asm.push("sub w0, w1, w0")?,
+            BinaryOp::Multiplication => self.asm.push("mul w0, w1, w0")?,
             BinaryOp::Division => {
                 // We use signed division here, but we can probably add
                 // an optimization with `udiv`.
-                self.asm.push("sdiv w0, w1, w0");
-            }
-            BinaryOp::Equal => {
-                self.asm.push("cmp w1, w0");
-                self.asm.push("mov w0, wzr");
-                self.asm.push("cset w0, eq");
-            }
-            BinaryOp::NotEqual => {
-                self.asm.push("cmp w1, w0");
-                self.asm.push("mov w0, wzr");
-                self.asm.push("cset w0, ne");
-            }
-            BinaryOp::LessThan => {
-                self.asm.push("cmp w1, w0");
-                self.asm.push("mov w0, wzr");
-                self.asm.push("cset w0, lt");
-            }
-            BinaryOp::LessThanOrEq => {
-                self.asm.push("cmp w1, w0");
-                self.asm.push("mov w0, wzr");
-                self.asm.push("cset w0, le");
-            }
-            BinaryOp::GreaterThan => {
-                self.asm.push("cmp w1, w0");
-                self.asm.push("mov w0, wzr");
-                self.asm.push("cset w0, gt");
-            }
-            BinaryOp::GreaterThanOrEq => {
-                self.asm.push("cmp w1, w0");
-                self.asm.push("mov w0, wzr");
-                self.asm.push("cset w0, ge");
+                self.asm.push("sdiv w0, w1, w0")?;
             }
             BinaryOp::Modulo => {
-                self.asm.push("sdiv w2, w1, w0");
-                self.asm.push("msub w0, w2, w0, w1");
+                self.asm.push("sdiv w2, w1, w0")?;
+                self.asm.push("msub w0, w2, w0, w1")?;
             }
             BinaryOp::BitwiseAnd => {
-                self.asm.push("and w0, w1, w0");
+                self.asm.push("and w0, w1, w0")?;
             }
             BinaryOp::BitwiseOr => {
-                self.asm.push("orr w0, w1, w0");
+                self.asm.push("orr w0, w1, w0")?;
             }
             BinaryOp::BitwiseXor => {
-                self.asm.push("eor w0, w1, w0");
+                self.asm.push("eor w0, w1, w0")?;
             }
             BinaryOp::BitwiseShiftLeft => {
-                self.asm.push("lsl w0, w1, w0");
+                self.asm.push("lsl w0, w1, w0")?;
             }
+            // Right shift of a negative value is implementation defined, GCC
+            // and Clang do an arithmetic shift.
             BinaryOp::BitwiseShiftRight => {
-                self.asm.push("lsr w0, w1, w0");
+                self.asm.push("asr w0, w1, w0")?;
             }
-            // These are short circuiting operators, so we don't need to do anything here.
-            BinaryOp::And => {}
-            BinaryOp::Or => {}
+            // These are short circuiting operators, so we don't need to do
+            // anything here, and the comparisons are handled above.
+            _ => {}
         }
 
         Ok(())
     }
 
+    fn generate_floating_binary_op(
+        &mut self,
+        binary_op: &BinaryOp,
+        lhs: &Expr,
+        rhs: &Expr,
+        ty: Type,
+        span: Span,
+    ) -> CodegenResult<()> {
+        let (r0, r1) = (ty.register(0), ty.register(1));
+        self.generate_operands(lhs, rhs, ty)?;
+
+        // lhs is in the second register, rhs is in the first one.
+        if let Some(cond) = comparison_condition(*binary_op, ty) {
+            self.asm.push(format!("fcmp {}, {}", r1, r0))?;
+            self.asm.push("mov w0, wzr")?;
+            self.asm.push(format!("cset w0, {}", cond))?;
+            return Ok(());
+        }
+        let opcode = match binary_op {
+            BinaryOp::Addition => "fadd",
+            BinaryOp::Subtraction => "fsub",
+            BinaryOp::Multiplication => "fmul",
+            BinaryOp::Division => "fdiv",
+            other => return Err(CodegenError::InvalidBinaryOperands(*other, ty, Some(span))),
+        };
+        self.asm
+            .push(format!("{} {}, {}, {}", opcode, r0, r1, r0))?;
+
+        Ok(())
+    }
+
+    /// The condition flags of lhs should already be set.
     fn generate_short_circuiting_op(
         &mut self,
         binary_op: &BinaryOp,
         rhs: &Expr,
     ) -> CodegenResult<()> {
-        let end_label = unique_label();
+        let kind = match binary_op {
+            BinaryOp::And => "and_end",
+            _ => "or_end",
+        };
+        let [end_label] = self.new_labels([kind])?;
 
         match binary_op {
             BinaryOp::And => {
                 // If lhs is false, we don't need to evaluate rhs.
-                self.asm.push("cmp w0, #0");
-                self.asm.push("cset w0, ne");
-                self.asm.push(format!("cbz w0, {}", end_label));
-                self.generate_expr(rhs)?;
-                self.asm.push("cmp w0, #0");
-                self.asm.push("cset w0, ne");
-                self.asm.push(format!("{}:", end_label));
+                self.asm.push("cset w0, ne")?;
+                self.asm.push(format!("cbz w0, {}", end_label))?;
+                self.generate_condition(rhs)?;
+                self.asm.push("cset w0, ne")?;
+                self.asm.push(format!("{}:", end_label))?;
                 Ok(())
             }
             BinaryOp::Or => {
                 // If lhs is true, we don't need to evaluate rhs.
-                self.asm.push("cmp w0, #0");
-                self.asm.push("cset w0, ne");
-                self.asm.push(format!("cbnz w0, {}", end_label));
-                self.generate_expr(rhs)?;
-                self.asm.push("cmp w0, #0");
-                self.asm.push("cset w0, ne");
-                self.asm.push(format!("{}:", end_label));
+                self.asm.push("cset w0, ne")?;
+                self.asm.push(format!("cbnz w0, {}", end_label))?;
+                self.generate_condition(rhs)?;
+                self.asm.push("cset w0, ne")?;
+                self.asm.push(format!("{}:", end_label))?;
                 Ok(())
             }
             other => Err(CodegenError::UnexpectedBinaryOp(*other)),
@@ -367,29 +701,24 @@ impl ARMCodegen {
     }
 
     fn generate_conditional(&mut self, conditional: &Conditional) -> CodegenResult<()> {
-        let end_label = unique_label();
-        let else_label = unique_label();
-
-        self.generate_expr(&conditional.condition)?;
-        self.asm.push("cmp w0, #0");
-        self.asm.push(format!(
-            "beq {}",
-            if conditional.else_stmt.is_some() {
-                &else_label
-            } else {
-                &end_label
-            }
-        ));
+        let [end_label, else_label] = self.new_labels(["if_end", "if_else"])?;
+
+        let false_label = if conditional.else_stmt.is_some() {
+            &else_label
+        } else {
+            &end_label
+        };
+        self.generate_branch(&conditional.condition, false, false_label)?;
 
         self.generate_statement(&conditional.if_stmt)?;
-        self.asm.push(format!("b {}", end_label));
+        self.asm.push(format!("b {}", end_label))?;
 
         if let Some(else_stmt) = &conditional.else_stmt {
-            self.asm.push(format!("{}:", else_label));
+            self.asm.push(format!("{}:", else_label))?;
             self.generate_statement(else_stmt)?;
         }
 
-        self.asm.push(format!("{}:", end_label));
+        self.asm.push(format!("{}:", end_label))?;
         Ok(())
     }
 
@@ -401,75 +730,75 @@ impl ARMCodegen {
     }
 
     fn generate_ternary_cond_expr(&mut self, ternary: &TernaryConditional) -> CodegenResult<()> {
-        let end_label = unique_label();
-        let else_label = unique_label();
+        let [end_label, else_label] = self.new_labels(["ternary_end", "ternary_else"])?;
+        // Both of the branches are converted to their common type.
+        let ty = self
+            .expr_type(&ternary.if_expr)?
+            .common(self.expr_type(&ternary.else_expr)?);
 
-        self.generate_expr(&ternary.condition)?;
-        self.asm.push("cmp w0, #0");
-        self.asm.push(format!("beq {}", else_label));
+        self.generate_branch(&ternary.condition, false, &else_label)?;
 
-        self.generate_expr(&ternary.if_expr)?;
-        self.asm.push(format!("b {}", end_label));
+        self.generate_expr_as(&ternary.if_expr, ty)?;
+        self.asm.push(format!("b {}", end_label))?;
 
-        self.asm.push(format!("{}:", else_label));
-        self.generate_expr(&ternary.else_expr)?;
+        self.asm.push(format!("{}:", else_label))?;
+        self.generate_expr_as(&ternary.else_expr, ty)?;
 
-        self.asm.push(format!("{}:", end_label));
+        self.asm.push(format!("{}:", end_label))?;
         Ok(())
     }
 
     fn generate_while(&mut self, expr: &Expr, stmt: &Statement) -> CodegenResult<()> {
-        let start_label = unique_label();
-        let end_label = unique_label();
+        let [start_label, end_label] = self.new_labels(["while_start", "while_end"])?;
 
         // This is used for break/continue statements.
         self.funcs.last_mut().unwrap().loops.push(Loop {
-            start_label: start_label.clone(),
+            continue_label: start_label.clone(),
             end_label: end_label.clone(),
         });
 
-        self.asm.push(format!("{}:", start_label));
-        self.generate_expr(expr)?;
-        self.asm.push("cmp w0, #0");
-        self.asm.push(format!("beq {}", end_label));
+        self.asm.push(format!("{}:", start_label))?;
+        self.generate_branch(expr, false, &end_label)?;
 
         self.generate_statement(stmt)?;
-        self.asm.push(format!("b {}", start_label));
-        self.asm.push(format!("{}:", end_label));
+        self.asm.push(format!("b {}", start_label))?;
+        self.asm.push(format!("{}:", end_label))?;
 
         self.funcs.last_mut().unwrap().loops.pop();
         Ok(())
     }
 
     fn generate_do_while(&mut self, stmt: &Statement, expr: &Expr) -> CodegenResult<()> {
-        let start_label = unique_label();
-        // Even though we don't use the end label here, break statement might use it.
-        let end_label = unique_label();
+        // Continue statements skip the rest of the body but still check the
+        // condition. Even though we don't use the end label here, break
+        // statement might use it.
+        let [start_label, continue_label, end_label] =
+            self.new_labels(["do_start", "do_continue", "do_end"])?;
 
         // This is used for break/continue statements.
         self.funcs.last_mut().unwrap().loops.push(Loop {
-            start_label: start_label.clone(),
+            continue_label: continue_label.clone(),
             end_label: end_label.clone(),
         });
 
-        self.asm.push(format!("{}:", start_label));
+        self.asm.push(format!("{}:", start_label))?;
         self.generate_statement(stmt)?;
-        self.generate_expr(expr)?;
-        self.asm.push("cmp w0, #0");
-        self.asm.push(format!("bne {}", start_label));
-        self.asm.push(format!("{}:", end_label));
+        self.asm.push(format!("{}:", continue_label))?;
+        self.generate_branch(expr, true, &start_label)?;
+        self.asm.push(format!("{}:", end_label))?;
 
         self.funcs.last_mut().unwrap().loops.pop();
         Ok(())
     }
 
     fn generate_for(&mut self, for_loop: &For) -> CodegenResult<()> {
-        let start_label = unique_label();
-        let end_label = unique_label();
+        // Continue statements still need to run the increment expression.
+        let [start_label, continue_label, end_label] =
+            self.new_labels(["for_start", "for_continue", "for_end"])?;
 
         // This is used for break/continue statements.
         self.funcs.last_mut().unwrap().loops.push(Loop {
-            start_label: start_label.clone(),
+            continue_label: continue_label.clone(),
             end_label: end_label.clone(),
         });
 
@@ -482,23 +811,198 @@ impl ARMCodegen {
             }
         }
 
-        self.asm.push(format!("{}:", start_label));
+        self.asm.push(format!("{}:", start_label))?;
 
-        // If condition is a null expression, then we need to convert that into `1`.
-        if let Expr::Null = &*for_loop.condition {
-            self.asm.push("mov w0, #1");
-        } else {
-            self.generate_expr(&for_loop.condition)?;
-        }
-        self.asm.push("cmp w0, #0");
-        self.asm.push(format!("beq {}", end_label));
+        self.generate_branch(&for_loop.condition, false, &end_label)?;
 
         self.generate_statement(&for_loop.body)?;
+        self.asm.push(format!("{}:", continue_label))?;
         self.generate_expr(&for_loop.increment)?;
-        self.asm.push(format!("b {}", start_label));
-        self.asm.push(format!("{}:", end_label));
+        self.asm.push(format!("b {}", start_label))?;
+        self.asm.push(format!("{}:", end_label))?;
 
         self.funcs.last_mut().unwrap().loops.pop();
         Ok(())
     }
 }
+
+/// The condition code that a comparison of the type is true with.
+fn comparison_condition(op: BinaryOp, ty: Type) -> Option<Condition> {
+    let cond = match op {
+        BinaryOp::Equal => Condition::Eq,
+        BinaryOp::NotEqual => Condition::Ne,
+        // `lt` and `le` are also true for the unordered comparisons with
+        // NaN, `mi` and `ls` aren't.
+        BinaryOp::LessThan if ty.is_floating() => Condition::Mi,
+        BinaryOp::LessThanOrEq if ty.is_floating() => Condition::Ls,
+        BinaryOp::LessThan => Condition::Lt,
+        BinaryOp::LessThanOrEq => Condition::Le,
+        BinaryOp::GreaterThan => Condition::Gt,
+        BinaryOp::GreaterThanOrEq => Condition::Ge,
+        _ => return None,
+    };
+    Some(cond)
+}
+
+/// Assign the argument registers to the parameters as AAPCS64 does. Integer
+/// and floating point parameters use separate register files.
+fn arg_registers(func_name: &str, param_types: &[Type], span: Span) -> CodegenResult<Vec<String>> {
+    let indices = arg_register_indices(func_name, param_types, Some(span))?;
+    Ok(param_types
+        .iter()
+        .zip(indices)
+        .map(|(ty, idx)| ty.register(idx))
+        .collect())
+}
+
+/// The indices of the argument registers in their register files.
+fn arg_register_indices(
+    func_name: &str,
+    param_types: &[Type],
+    span: Option<Span>,
+) -> CodegenResult<Vec<usize>> {
+    let mut next_int = 0;
+    let mut next_floating = 0;
+    let mut indices = vec![];
+
+    for ty in param_types {
+        let next = if ty.is_floating() {
+            &mut next_floating
+        } else {
+            &mut next_int
+        };
+        // Only the arguments that fit in the registers are supported for now.
+        if *next >= MAX_REG_ARGS {
+            return Err(CodegenError::TooManyArguments(
+                func_name.to_string(),
+                MAX_REG_ARGS,
+                span,
+            ));
+        }
+        indices.push(*next);
+        *next += 1;
+    }
+
+    Ok(indices)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::tokenizer::Tokenizer;
+
+    fn generate(src: &str) -> CodegenResult<String> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        ARMCodegen::new().generate(&program)
+    }
+
+    /// A program whose main function returns the constant, the parser
+    /// doesn't produce the strings and the booleans yet.
+    pub(crate) fn returning(constant: Constant) -> Program {
+        let token_stream = Tokenizer::new("int main() { return 1; }")
+            .tokenize()
+            .unwrap();
+        let mut program = Parser::new(token_stream).parse().unwrap();
+        program.functions[0].body = Some(Block {
+            items: vec![BlockItem::Statement(
+                Statement::Return(Box::new(Expr::Constant(constant))),
+                Span::default(),
+            )],
+        });
+        program
+    }
+
+    #[test]
+    fn test_constants() {
+        let asm = ARMCodegen::new()
+            .generate(&returning(Constant::Bool(true)))
+            .unwrap();
+        assert!(asm.contains("mov w0, #1"));
+        assert!(matches!(
+            ARMCodegen::new().generate(&returning(Constant::String("a".into()))),
+            Err(CodegenError::UnsupportedConstant(Constant::String(_)))
+        ));
+    }
+
+    #[test]
+    fn test_labels_are_numbered_per_function() {
+        let src = "int f(int a) { while (a) a = a - 1; return a; }
+                   int main() { if (f(2)) return 1; while (0) {} return 0; }";
+        let asm = generate(src).unwrap();
+        assert!(asm.contains("Lf_while_start_0:"));
+        assert!(asm.contains("Lf_while_end_0:"));
+        assert!(asm.contains("Lmain_if_end_0:"));
+        assert!(asm.contains("Lmain_while_start_1:"));
+        assert!(asm.contains("Lmain_return:"));
+        // Compiling again doesn't depend on the earlier compilations.
+        assert_eq!(generate(src).unwrap(), asm);
+    }
+
+    #[test]
+    fn test_main_returns_zero_at_the_end() {
+        let asm = generate("int main() { int a = 1; if (a) return 2; }").unwrap();
+        assert!(asm.contains("mov w0, #0\nLmain_return:"));
+        // The end of the body is never reached.
+        let asm = generate("int main() { while (1) { return 2; } }").unwrap();
+        assert!(!asm.contains("mov w0, #0"));
+        let asm = generate("int main() { if (1) return 2; else return 3; }").unwrap();
+        assert!(!asm.contains("mov w0, #0"));
+    }
+
+    #[test]
+    fn test_conditions_branch_on_flags() {
+        let asm = generate("int main() { int a = 0; while (a < 3) a = a + 1; return a; }").unwrap();
+        assert!(asm.contains("cmp w1, w0\nbge Lmain_while_end_0"));
+        assert!(!asm.contains("cset"));
+
+        // The `||` jumps to the body as soon as `a` is true.
+        let asm = generate("int main() { float a = 1; if (a || !(a >= 2)) return 1; return 0; }")
+            .unwrap();
+        assert!(asm.contains("fcmp s0, #0.0\nbne Lmain_or_skip_1"));
+        assert!(asm.contains("fcmp s1, s0\nbge Lmain_if_end_0"));
+        assert!(asm.contains("Lmain_or_skip_1:"));
+        assert!(!asm.contains("cset"));
+    }
+
+    #[test]
+    fn test_floating_arithmetic_uses_fp_registers() {
+        let asm = generate("int main() { float a = 1.5f; double b = a * 2.0; return b; }").unwrap();
+        assert!(asm.contains("fmul d0, d1, d0"));
+        assert!(asm.contains("fcvt d0, s0"));
+        assert!(asm.contains("fcvtzs w0, d0"));
+    }
+
+    #[test]
+    fn test_floating_arguments_use_vector_registers() {
+        let asm = generate(
+            "double f(int a, double b, int c, float d); int main() { return f(1, 2, 3, 4); }",
+        )
+        .unwrap();
+        let loads: Vec<&str> = asm.lines().filter(|line| line.starts_with("ldr")).collect();
+        assert_eq!(loads.len(), 4);
+        assert!(loads[0].starts_with("ldr w0"));
+        assert!(loads[1].starts_with("ldr d0"));
+        assert!(loads[2].starts_with("ldr w1"));
+        assert!(loads[3].starts_with("ldr s1"));
+    }
+
+    #[test]
+    fn test_floating_modulo_is_an_error() {
+        let asm = generate("int main() { double a = 1.0; return a % 2; }");
+        assert!(matches!(
+            asm,
+            Err(CodegenError::InvalidBinaryOperands(
+                BinaryOp::Modulo,
+                Type::Double,
+                Some(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_doubles_are_aligned_in_stack() {
+        let asm = generate("int main() { int a = 1; double b = 2.0; return a; }").unwrap();
+        assert!(asm.contains("str d0, [sp, #0]"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Peephole optimization of the generated assembly. The patterns that the
+//! codegen emits all the time are rewritten to shorter code:
+//!
+//! - a load right after a store to the same address reuses the register,
+//! - the `mov wN, wzr` before a `cset wN` is dropped, `cset` writes the whole
+//!   register anyway,
+//! - a `b L` right before `L:` is dropped,
+//! - a comparison with zero and a `beq`/`bne` become a `cbz`/`cbnz`, if the
+//!   flags of the comparison aren't read after the branch.
+
+use super::asm::{Assembly, Condition, Instruction, Line, Opcode, Operand};
+
+/// Rewrite the assembly until none of the patterns match, and return the
+/// number of the removed instructions.
+pub fn optimize(asm: &mut Assembly) -> usize {
+    let lines = asm.lines_mut();
+    let before = lines.len();
+    let mut index = 0;
+    while index < lines.len() {
+        if rewrite(lines, index) {
+            // The rewritten line can be part of an earlier pattern now.
+            index = index.saturating_sub(1);
+        } else {
+            index += 1;
+        }
+    }
+    before - lines.len()
+}
+
+/// Rewrite the pattern that starts at the line, if there's one.
+fn rewrite(lines: &mut Vec<Line>, index: usize) -> bool {
+    let Some(Line::Instruction(first)) = lines.get(index) else {
+        return false;
+    };
+
+    if first.opcode == Opcode::B {
+        let [Operand::Label(target)] = &first.operands[..] else {
+            return false;
+        };
+        let falls_through = lines[index + 1..]
+            .iter()
+            .map_while(|line| match line {
+                Line::Label(label) => Some(label),
+                _ => None,
+            })
+            .any(|label| label == target);
+        if falls_through {
+            lines.remove(index);
+        }
+        return falls_through;
+    }
+
+    let Some(Line::Instruction(second)) = lines.get(index + 1) else {
+        return false;
+    };
+    let replacement = match (first.opcode, second.opcode) {
+        (Opcode::Str, Opcode::Ldr) => forward_store(first, second),
+        (Opcode::Mov, Opcode::Cset) => {
+            let zeroed = matches!(&first.operands[..], [dst, src]
+                if src.register() == Some("wzr") && Some(dst) == second.operands.first());
+            zeroed.then(|| vec![second.clone()])
+        }
+        (Opcode::Cmp, Opcode::BCond(cond @ (Condition::Eq | Condition::Ne))) => {
+            match (&first.operands[..], &second.operands[..]) {
+                (
+                    [register @ Operand::Register(name), Operand::Immediate(0)],
+                    [label @ Operand::Label(target)],
+                ) if name.starts_with(['w', 'x'])
+                    && !flags_read_after(lines, index + 1, target) =>
+                {
+                    let opcode = if cond == Condition::Eq {
+                        Opcode::Cbz
+                    } else {
+                        Opcode::Cbnz
+                    };
+                    Some(vec![Instruction::new(
+                        opcode,
+                        vec![register.clone(), label.clone()],
+                    )])
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    match replacement {
+        Some(replacement) => {
+            lines.splice(index..index + 2, replacement.into_iter().map(Line::from));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether the flags can be read after the branch at the index, on the
+/// fallthrough path or at the target, before they're set again.
+fn flags_read_after(lines: &[Line], branch: usize, target: &str) -> bool {
+    let mut visited = vec![false; lines.len()];
+    reads_flags(lines, branch + 1, &mut visited)
+        || label_index(lines, target).is_none_or(|index| reads_flags(lines, index, &mut visited))
+}
+
+/// Whether the code from the line at the index can read the flags before it
+/// sets them, following the branches.
+fn reads_flags(lines: &[Line], mut index: usize, visited: &mut [bool]) -> bool {
+    while let Some(line) = lines.get(index) {
+        if std::mem::replace(&mut visited[index], true) {
+            return false;
+        }
+        let Line::Instruction(inst) = line else {
+            index += 1;
+            continue;
+        };
+        if inst.opcode.reads_flags() {
+            return true;
+        }
+        if inst.opcode.sets_flags() || inst.opcode == Opcode::Ret {
+            return false;
+        }
+        if let (Opcode::B | Opcode::Cbz | Opcode::Cbnz, Some(Operand::Label(target))) =
+            (inst.opcode, inst.operands.last())
+        {
+            // The target of a label that isn't in the function is unknown.
+            let Some(target) = label_index(lines, target) else {
+                return true;
+            };
+            if reads_flags(lines, target, visited) {
+                return true;
+            }
+            if inst.opcode == Opcode::B {
+                return false;
+            }
+        }
+        index += 1;
+    }
+    false
+}
+
+fn label_index(lines: &[Line], label: &str) -> Option<usize> {
+    lines
+        .iter()
+        .position(|line| matches!(line, Line::Label(name) if name == label))
+}
+
+/// A `ldr` from the address that the `str` before it wrote to gets the value
+/// from the stored register instead.
+fn forward_store(store: &Instruction, load: &Instruction) -> Option<Vec<Instruction>> {
+    let [Operand::Register(src), address @ Operand::Address {
+        writeback: false, ..
+    }] = &store.operands[..]
+    else {
+        return None;
+    };
+    let [Operand::Register(dst), load_address] = &load.operands[..] else {
+        return None;
+    };
+    // Only the registers of the same size and register file, e.g. `w0`
+    // and `w1`.
+    if address != load_address || src[..1] != dst[..1] {
+        return None;
+    }
+    let mut replacement = vec![store.clone()];
+    if dst != src {
+        let opcode = if src.starts_with(['w', 'x']) {
+            Opcode::Mov
+        } else {
+            Opcode::Fmov
+        };
+        replacement.push(Instruction::new(
+            opcode,
+            vec![load.operands[0].clone(), store.operands[0].clone()],
+        ));
+    }
+    Some(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn optimize_lines(lines: &[&str]) -> (String, usize) {
+        let mut asm = Assembly::new();
+        for line in lines {
+            asm.push(line).unwrap();
+        }
+        let removed = optimize(&mut asm);
+        (asm.to_string(), removed)
+    }
+
+    #[test]
+    fn test_peephole() {
+        let (asm, removed) = optimize_lines(&[
+            "str w0, [sp, #12]",
+            "ldr w0, [sp, #12]",
+            "str d0, [sp, #16]",
+            "ldr d1, [sp, #16]",
+            "str w0, [sp, #8]",
+            "ldr x0, [sp, #8]",
+            "cmp w1, w0",
+            "mov w0, wzr",
+            "cset w0, lt",
+            "cmp w0, #0",
+            "beq Lmain_if_else_0",
+            "b Lmain_if_end_0",
+            "Lmain_if_else_0:",
+            "Lmain_if_end_0:",
+            "fcmp s0, #0.0",
+            "bne Lmain_while_start_1",
+        ]);
+        assert_eq!(
+            asm,
+            "str w0, [sp, #12]
+str d0, [sp, #16]
+fmov d1, d0
+str w0, [sp, #8]
+ldr x0, [sp, #8]
+cmp w1, w0
+cset w0, lt
+cbz w0, Lmain_if_else_0
+Lmain_if_else_0:
+Lmain_if_end_0:
+fcmp s0, #0.0
+bne Lmain_while_start_1"
+        );
+        assert_eq!(removed, 4);
+    }
+
+    #[test]
+    fn test_peephole_keeps_other_code() {
+        let lines = [
+            "stp x29, x30, [sp, #-16]!",
+            "ldp x29, x30, [sp], #16",
+            "mov w1, wzr",
+            "cset w0, eq",
+            "cmp w0, #1",
+            "beq L",
+            "b L",
+            "mov w0, #1",
+            "L:",
+        ];
+        let (asm, removed) = optimize_lines(&lines);
+        assert_eq!(asm, lines.join("\n"));
+        assert_eq!(removed, 0);
+    }
+
+    #[test]
+    fn test_peephole_keeps_the_flags_that_are_read() {
+        // The flags are read on the fallthrough path.
+        let fallthrough = ["cmp w0, #0", "beq L1", "cset w1, eq", "L1:", "ret"];
+        // They're read at the target, after a jump back.
+        let target = [
+            "L1:",
+            "cset w1, ne",
+            "cmp w0, #0",
+            "bne L2",
+            "b L1",
+            "L2:",
+            "ret",
+        ];
+        for lines in [&fallthrough[..], &target[..]] {
+            let (asm, removed) = optimize_lines(lines);
+            assert_eq!(asm, lines.join("\n"));
+            assert_eq!(removed, 0);
+        }
+
+        // Both paths set the flags again before they read them.
+        let (asm, removed) = optimize_lines(&[
+            "cmp w0, #0",
+            "beq L1",
+            "cmp w1, w2",
+            "cset w0, lt",
+            "L1:",
+            "bl _f",
+            "cset w0, eq",
+        ]);
+        assert!(asm.starts_with("cbz w0, L1\n"));
+        assert_eq!(removed, 1);
+    }
+}
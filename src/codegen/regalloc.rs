@@ -0,0 +1,303 @@
+//! Linear scan register allocation, as in Poletto and Sarkar. Every
+//! temporary gets one live interval over the blocks in the order that they
+//! are emitted in, and the temporaries whose intervals overlap get
+//! different registers. The ones that don't fit are spilled to the stack.
+//!
+//! The intervals that don't cross a call get the caller-saved registers
+//! first, they don't have to be saved in the prologue. The ones that cross a
+//! call only get the callee-saved registers, so their values survive it. The
+//! argument registers and x16 stay free as scratch registers.
+
+use crate::{
+    ir::{liveness::Liveness, BlockId, Function, Instruction, Operand, Temp},
+    parser::ast::Type,
+};
+use std::collections::BTreeSet;
+
+/// x19-x28, the other callee-saved registers are the frame pointer and the
+/// link register.
+const CALLEE_SAVED_INT: [usize; 10] = [19, 20, 21, 22, 23, 24, 25, 26, 27, 28];
+/// The lower halves of v8-v15, i.e. d8-d15 or s8-s15.
+const CALLEE_SAVED_FLOATING: [usize; 8] = [8, 9, 10, 11, 12, 13, 14, 15];
+/// x9-x15, the caller-saved registers that don't pass the arguments.
+const CALLER_SAVED_INT: [usize; 7] = [9, 10, 11, 12, 13, 14, 15];
+/// d16-d31.
+const CALLER_SAVED_FLOATING: [usize; 16] = [
+    16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+];
+
+#[derive(Debug, PartialEq)]
+pub struct Allocation {
+    /// The register of every temporary by its index in its register file,
+    /// `None` if it's spilled or not used.
+    pub registers: Vec<Option<usize>>,
+    /// The temporaries that live in the stack frame.
+    pub spilled: Vec<Temp>,
+    /// The callee-saved registers that have to be saved, in order.
+    pub used_int: Vec<usize>,
+    pub used_floating: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    temp: Temp,
+    start: usize,
+    end: usize,
+    /// Whether the temporary is live across a call, so a caller-saved
+    /// register would be overwritten.
+    crosses_call: bool,
+}
+
+/// The free registers of a register file.
+struct FreeRegisters {
+    caller_saved: BTreeSet<usize>,
+    callee_saved: BTreeSet<usize>,
+}
+
+impl FreeRegisters {
+    fn new(caller_saved: &[usize], callee_saved: &[usize]) -> FreeRegisters {
+        FreeRegisters {
+            caller_saved: caller_saved.iter().copied().collect(),
+            callee_saved: callee_saved.iter().copied().collect(),
+        }
+    }
+
+    fn take(&mut self, crosses_call: bool) -> Option<usize> {
+        if crosses_call {
+            return self.callee_saved.pop_first();
+        }
+        self.caller_saved
+            .pop_first()
+            .or_else(|| self.callee_saved.pop_first())
+    }
+
+    fn release(&mut self, register: usize, callee_saved: bool) {
+        if callee_saved {
+            self.callee_saved.insert(register);
+        } else {
+            self.caller_saved.insert(register);
+        }
+    }
+}
+
+fn is_callee_saved(register: usize, floating: bool) -> bool {
+    if floating {
+        CALLEE_SAVED_FLOATING.contains(&register)
+    } else {
+        CALLEE_SAVED_INT.contains(&register)
+    }
+}
+
+impl Allocation {
+    /// Allocate the registers of the temporaries in the given blocks, in the
+    /// order that they are emitted in.
+    pub fn new(func: &Function, order: &[BlockId]) -> Allocation {
+        let intervals = intervals(func, order);
+        let mut allocation = Allocation {
+            registers: vec![None; func.temps.len()],
+            spilled: vec![],
+            used_int: vec![],
+            used_floating: vec![],
+        };
+        let mut free_int = FreeRegisters::new(&CALLER_SAVED_INT, &CALLEE_SAVED_INT);
+        let mut free_floating = FreeRegisters::new(&CALLER_SAVED_FLOATING, &CALLEE_SAVED_FLOATING);
+        // The intervals that have registers, sorted by their ends.
+        let mut active: Vec<Interval> = vec![];
+
+        for interval in intervals {
+            let is_floating = |temp: Temp| func.temps[temp.0].is_floating();
+            // An operand keeps its register in the instruction that ends its
+            // interval, so it isn't overwritten by the result.
+            let (expired, still_active) = active.iter().partition(|a| a.end < interval.start);
+            active = still_active;
+            for expired in expired {
+                let register = allocation.registers[expired.temp.0].unwrap();
+                let floating = is_floating(expired.temp);
+                let free = if floating {
+                    &mut free_floating
+                } else {
+                    &mut free_int
+                };
+                free.release(register, is_callee_saved(register, floating));
+            }
+
+            let floating = is_floating(interval.temp);
+            let free = if floating {
+                &mut free_floating
+            } else {
+                &mut free_int
+            };
+            let register = match free.take(interval.crosses_call) {
+                Some(register) => register,
+                None => {
+                    // Spill the interval of the same class that ends last,
+                    // if its register can hold this one.
+                    let last = active.iter().rposition(|a| {
+                        let register = allocation.registers[a.temp.0].unwrap();
+                        is_floating(a.temp) == floating
+                            && (!interval.crosses_call || is_callee_saved(register, floating))
+                    });
+                    let Some(last) = last.filter(|&last| active[last].end > interval.end) else {
+                        allocation.spilled.push(interval.temp);
+                        continue;
+                    };
+                    let spilled = active.remove(last);
+                    allocation.spilled.push(spilled.temp);
+                    allocation.registers[spilled.temp.0].take().unwrap()
+                }
+            };
+            allocation.registers[interval.temp.0] = Some(register);
+            let used = if floating {
+                &mut allocation.used_floating
+            } else {
+                &mut allocation.used_int
+            };
+            if is_callee_saved(register, floating) && !used.contains(&register) {
+                used.push(register);
+            }
+            let index = active.partition_point(|a| a.end <= interval.end);
+            active.insert(index, interval);
+        }
+        allocation.used_int.sort();
+        allocation.used_floating.sort();
+        allocation
+    }
+
+    /// The register of the temporary, if it has one.
+    pub fn register(&self, temp: Temp, ty: Type) -> Option<String> {
+        self.registers[temp.0].map(|register| ty.register(register))
+    }
+}
+
+/// The live intervals of the temporaries, sorted by their starts. The
+/// positions are numbered from the start of the first block, each block has
+/// a position for its start, its instructions and its terminator.
+fn intervals(func: &Function, order: &[BlockId]) -> Vec<Interval> {
+    let liveness = Liveness::new(func);
+    let mut ranges: Vec<Option<(usize, usize)>> = vec![None; func.temps.len()];
+    let mut extend = |temp: Temp, position: usize| {
+        let range = ranges[temp.0].get_or_insert((position, position));
+        range.0 = range.0.min(position);
+        range.1 = range.1.max(position);
+    };
+
+    let mut calls = vec![];
+    let mut position = 0;
+    for &id in order {
+        let block = &func.blocks[id];
+        let start = position;
+        for temp in &liveness.live_in[id] {
+            extend(*temp, start);
+        }
+        for inst in &block.instructions {
+            position += 1;
+            if let Instruction::Call(..) = inst {
+                calls.push(position);
+            }
+            for operand in inst.operands() {
+                if let Operand::Temp(temp) = operand {
+                    extend(*temp, position);
+                }
+            }
+            if let Some(dest) = inst.dest() {
+                extend(dest, position);
+            }
+        }
+        position += 1;
+        if let Some(Operand::Temp(temp)) = block.terminator.operand() {
+            extend(*temp, position);
+        }
+        for temp in &liveness.live_out[id] {
+            extend(*temp, position);
+        }
+        position += 1;
+    }
+
+    let mut intervals: Vec<Interval> = ranges
+        .into_iter()
+        .enumerate()
+        .filter_map(|(temp, range)| {
+            // The arguments are read before the call and the result is
+            // written after it, only the values that are live on both sides
+            // cross it.
+            range.map(|(start, end)| Interval {
+                temp: Temp(temp),
+                start,
+                end,
+                crosses_call: calls.iter().any(|&call| start < call && call < end),
+            })
+        })
+        .collect();
+    intervals.sort_by_key(|interval| interval.start);
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ir::{self, OptLevel, PassManager},
+        parser::Parser,
+        tokenizer::Tokenizer,
+    };
+
+    fn allocate(src: &str) -> Allocation {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let mut module = ir::lower(&Parser::new(token_stream).parse().unwrap()).unwrap();
+        PassManager::new(OptLevel::O2).run(&mut module);
+        let func = &mut module.functions[0];
+        ir::ssa::destruct(func);
+        let order: Vec<BlockId> = (0..func.blocks.len())
+            .filter(|id| func.reachable()[*id])
+            .collect();
+        Allocation::new(func, &order)
+    }
+
+    #[test]
+    fn test_allocation() {
+        // Nothing crosses a call, so nothing has to be saved.
+        let allocation = allocate("int f(int a, int b) { return a * b + a; }");
+        assert!(allocation.spilled.is_empty());
+        assert!(allocation.used_int.is_empty());
+        assert!(allocation.used_floating.is_empty());
+        assert!(allocation
+            .registers
+            .iter()
+            .flatten()
+            .all(|register| CALLER_SAVED_INT.contains(register)));
+    }
+
+    #[test]
+    fn test_allocation_across_calls() {
+        // `a` and `b` are live across the call, the sum isn't.
+        let allocation = allocate(
+            "int g(int x);
+            int f(int a) { int b = a + 1; int c = g(a); return b + c + a; }",
+        );
+        assert!(allocation.spilled.is_empty());
+        assert_eq!(allocation.used_int, [19, 20]);
+        assert!(allocation
+            .registers
+            .iter()
+            .flatten()
+            .any(|register| CALLER_SAVED_INT.contains(register)));
+    }
+
+    #[test]
+    fn test_spilling() {
+        // Twenty values are live at the same time.
+        let vars: Vec<String> = (1..20).map(|i| format!("v{}", i)).collect();
+        let decls: String = vars
+            .iter()
+            .enumerate()
+            .map(|(i, var)| format!("int {} = a + {}; ", var, i + 1))
+            .collect();
+        let allocation = allocate(&format!(
+            "int f(int a) {{ {}return a + {}; }}",
+            decls,
+            vars.join(" + ")
+        ));
+        assert_eq!(allocation.used_int, CALLEE_SAVED_INT);
+        assert!(!allocation.spilled.is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+use std::{io, path::PathBuf};
+
+use c_compiler::{
+    cfg::CfgError, emulator::EmulatorError, interpreter::InterpreterError, Diagnostic, Severity,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DriverError {
+    #[error("{0} of the inputs failed to compile")]
+    CompilationFailed(usize),
+    #[error("Interpreting has failed: {0}")]
+    Interpreter(#[from] InterpreterError),
+    #[error("Emulation has failed: {0}")]
+    Emulator(#[from] EmulatorError),
+    #[error("Building the control-flow graph has failed: {0}")]
+    Cfg(#[from] CfgError),
+
+    #[error("Cannot specify '-o' with '-c', '-S' or '-E' with multiple files")]
+    MultipleOutputs,
+    #[error("Only one source file can be run")]
+    MultipleRunInputs,
+    #[error("Invalid config file {0:?} at line {1}: {2}")]
+    InvalidConfig(PathBuf, usize, String),
+
+    #[error("Couldn't read {0:?}: {1}")]
+    Read(PathBuf, io::Error),
+    #[error("Couldn't write {0:?}: {1}")]
+    Write(PathBuf, io::Error),
+
+    #[error("Couldn't run {0:?}: {1}")]
+    ToolNotFound(String, io::Error),
+    #[error("Couldn't find the macOS SDK:\n{0}")]
+    SdkNotFound(String),
+    #[error("Assembling {0:?} has failed:\n{1}")]
+    AssemblerFailed(PathBuf, String),
+    #[error("Linking has failed:\n{0}")]
+    LinkerFailed(String),
+}
+
+impl DriverError {
+    /// The exit code of the driver, each kind of failure has its own. Clap
+    /// exits with 2 for the invalid arguments, so the usage errors do too.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DriverError::CompilationFailed(_)
+            | DriverError::Interpreter(_)
+            | DriverError::Emulator(_)
+            | DriverError::Cfg(_) => 1,
+            DriverError::MultipleOutputs
+            | DriverError::MultipleRunInputs
+            | DriverError::InvalidConfig(..) => 2,
+            DriverError::Read(..) | DriverError::Write(..) => 3,
+            DriverError::ToolNotFound(..) | DriverError::SdkNotFound(_) => 4,
+            DriverError::AssemblerFailed(..) => 5,
+            DriverError::LinkerFailed(_) => 6,
+        }
+    }
+}
+
+impl From<&DriverError> for Diagnostic {
+    fn from(err: &DriverError) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: Diagnostic::code_for("driver", err),
+            message: err.to_string(),
+            span: None,
+            notes: vec![],
+        }
+    }
+}
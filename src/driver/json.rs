@@ -0,0 +1,119 @@
+//! The diagnostics as JSON objects, one per line, for the editors and the CI.
+//!
+//! ```json
+//! {"severity":"error","code":"parse::UnexpectedToken","message":"Expected token Semicolon but got RBrace","file":"a.c","span":{"byte_start":22,"byte_end":23,"line_start":1,"column_start":23,"line_end":1,"column_end":24},"notes":[]}
+//! ```
+//!
+//! The lines and the columns start from 1, and the columns count characters.
+//! The span is `null` if the location isn't known, and the errors of the
+//! driver itself, e.g. a missing input, have a `null` file too.
+
+use std::{fmt::Write, path::Path};
+
+use c_compiler::{tokenizer::Span, Diagnostic, Severity};
+
+pub fn render(diagnostic: &Diagnostic, file: Option<&Path>, source: &str) -> String {
+    let severity = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+    let span = match diagnostic.span {
+        Some(ref span) => render_span(span, source),
+        None => "null".to_string(),
+    };
+    let file = match file {
+        Some(file) => string(&file.to_string_lossy()),
+        None => "null".to_string(),
+    };
+    let notes: Vec<String> = diagnostic.notes.iter().map(|note| string(note)).collect();
+
+    format!(
+        r#"{{"severity":{},"code":{},"message":{},"file":{},"span":{},"notes":[{}]}}"#,
+        string(severity),
+        string(&diagnostic.code),
+        string(&diagnostic.message),
+        file,
+        span,
+        notes.join(",")
+    )
+}
+
+fn render_span(span: &Span, source: &str) -> String {
+    let (line_start, column_start) = line_column(source, span.lo);
+    let (line_end, column_end) = line_column(source, span.hi);
+    format!(
+        r#"{{"byte_start":{},"byte_end":{},"line_start":{},"column_start":{},"line_end":{},"column_end":{}}}"#,
+        span.lo, span.hi, line_start, column_start, line_end, column_end
+    )
+}
+
+/// The line and the column of a byte offset.
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// A JSON string with the escapes.
+fn string(value: &str) -> String {
+    let mut result = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(result, "\\u{:04x}", c as u32);
+            }
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let diagnostic = Diagnostic {
+            severity: Severity::Error,
+            code: "parse::UnexpectedToken".into(),
+            message: "Expected \"x\"\tnow".into(),
+            span: Some(Span { lo: 14, hi: 16 }),
+            notes: vec!["A note".into()],
+        };
+        assert_eq!(
+            render(&diagnostic, Some(Path::new("a.c")), "int main() {\n é }"),
+            r#"{"severity":"error","code":"parse::UnexpectedToken","message":"Expected \"x\"\tnow","file":"a.c","span":{"byte_start":14,"byte_end":16,"line_start":2,"column_start":2,"line_end":2,"column_end":3},"notes":["A note"]}"#
+        );
+
+        let diagnostic = Diagnostic {
+            severity: Severity::Error,
+            code: "driver::MultipleRunInputs".into(),
+            message: "Only one source file can be run".into(),
+            span: None,
+            notes: vec![],
+        };
+        assert_eq!(
+            render(&diagnostic, None, ""),
+            r#"{"severity":"error","code":"driver::MultipleRunInputs","message":"Only one source file can be run","file":null,"span":null,"notes":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_line_column() {
+        let source = "a\nbc\n\u{e9}d";
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 3), (2, 2));
+        assert_eq!(line_column(source, 5), (3, 1));
+        // The columns count characters, not bytes.
+        assert_eq!(line_column(source, 7), (3, 2));
+    }
+}
@@ -0,0 +1,530 @@
+//! Runs the compiler like `cc` does: the inputs go through preprocessing,
+//! compilation, assembling and linking, and the driver can stop after any of
+//! them with `-E`, `-S` or `-c`.
+
+mod error;
+mod json;
+mod toolchain;
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+};
+
+use c_compiler::{
+    cfg::{self, CfgBuilder},
+    parser::Program,
+    tokenizer::Span,
+    CompileOptions, CompileWarning, Diagnostic, Diagnostics, Output, WarningKind,
+};
+use clap::ValueEnum;
+
+pub use self::{
+    error::DriverError,
+    toolchain::{LinkerKind, Toolchain, ToolchainConfig, DEFAULT_CONFIG_FILE},
+};
+
+pub type DriverResult<T> = Result<T, DriverError>;
+
+/// The output path that means stdout, e.g. `-S -o -`.
+const STDOUT_PATH: &str = "-";
+/// The default name of the executable.
+const DEFAULT_EXECUTABLE: &str = "a.out";
+
+/// The last stage that the driver runs.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Stage {
+    /// `-E`, there is no preprocessor yet, so the sources are written as they are.
+    Preprocess,
+    /// `-S`, write the assembly.
+    Compile,
+    /// `-c`, write the object files.
+    Assemble,
+    /// Write the executable.
+    Link,
+}
+
+/// The kind of an input file, from its extension like `cc` does.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum InputKind {
+    Source,
+    Assembly,
+    /// Everything else is passed to the linker.
+    Object,
+}
+
+impl InputKind {
+    fn of(path: &Path) -> InputKind {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("c") => InputKind::Source,
+            Some("s") => InputKind::Assembly,
+            _ => InputKind::Object,
+        }
+    }
+
+    /// The first stage that doesn't apply to this kind.
+    fn stage(self) -> Stage {
+        match self {
+            InputKind::Source => Stage::Preprocess,
+            InputKind::Assembly => Stage::Assemble,
+            InputKind::Object => Stage::Link,
+        }
+    }
+}
+
+/// How the errors and the warnings are printed.
+#[derive(Debug, PartialEq, Clone, Copy, ValueEnum)]
+pub enum ErrorFormat {
+    /// Messages for the people.
+    Human,
+    /// A JSON object per line, see [`json`].
+    Json,
+}
+
+impl ErrorFormat {
+    /// Print an error of the driver, the compile errors are printed by
+    /// [`Driver::report`].
+    pub fn report_error(self, err: &DriverError) {
+        match self {
+            ErrorFormat::Human => eprintln!("Error: {}", err),
+            ErrorFormat::Json => eprintln!("{}", json::render(&Diagnostic::from(err), None, "")),
+        }
+    }
+}
+
+/// A `-W` option, e.g. `-Wuninitialized`, `-Wno-unused-variable` or `-Werror`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum WarningFlag {
+    Enable(WarningKind),
+    Disable(WarningKind),
+    /// `-Wall` or `-Wextra`, every warning is on.
+    All,
+    Error,
+    /// The warnings of the other compilers are ignored, so the build scripts
+    /// for `cc` work.
+    Unknown(String),
+}
+
+impl FromStr for WarningFlag {
+    type Err = String;
+
+    fn from_str(flag: &str) -> Result<WarningFlag, String> {
+        match flag {
+            "error" => return Ok(WarningFlag::Error),
+            "all" | "extra" => return Ok(WarningFlag::All),
+            _ => {}
+        }
+        let (name, enable) = match flag.strip_prefix("no-") {
+            Some(name) => (name, false),
+            None => (flag, true),
+        };
+        Ok(match WarningKind::from_name(name) {
+            Some(kind) if enable => WarningFlag::Enable(kind),
+            Some(kind) => WarningFlag::Disable(kind),
+            None => WarningFlag::Unknown(flag.to_string()),
+        })
+    }
+}
+
+impl WarningFlag {
+    pub fn apply(&self, options: &mut CompileOptions) {
+        match self {
+            WarningFlag::Enable(kind) => {
+                options.disabled_warnings.remove(kind);
+            }
+            WarningFlag::Disable(kind) => {
+                options.disabled_warnings.insert(*kind);
+            }
+            WarningFlag::All => options.disabled_warnings.clear(),
+            WarningFlag::Error => options.warnings_as_errors = true,
+            WarningFlag::Unknown(name) => {
+                let names: Vec<_> = WarningKind::ALL.iter().map(|kind| kind.name()).collect();
+                eprintln!(
+                    "Note: ignoring the unknown warning -W{}, the warnings are: {}",
+                    name,
+                    names.join(", ")
+                );
+            }
+        }
+    }
+}
+
+/// The extra outputs of the compiled sources.
+#[derive(Debug, PartialEq, Clone, Copy, ValueEnum)]
+pub enum Emit {
+    /// The control-flow graphs of the functions as a Graphviz file.
+    CfgDot,
+}
+
+#[derive(Debug)]
+pub struct Driver {
+    options: CompileOptions,
+    stage: Stage,
+    output: Option<PathBuf>,
+    print_ast: bool,
+    emit: Vec<Emit>,
+    error_format: ErrorFormat,
+    toolchain: Toolchain,
+}
+
+impl Driver {
+    pub fn new(options: CompileOptions) -> Driver {
+        Driver {
+            options,
+            stage: Stage::Link,
+            output: None,
+            print_ast: false,
+            emit: vec![],
+            error_format: ErrorFormat::Human,
+            toolchain: Toolchain::default(),
+        }
+    }
+
+    pub fn with_stage(mut self, stage: Stage) -> Driver {
+        self.stage = stage;
+        self
+    }
+
+    pub fn with_output(mut self, output: Option<PathBuf>) -> Driver {
+        self.output = output;
+        self
+    }
+
+    pub fn with_print_ast(mut self, print_ast: bool) -> Driver {
+        self.print_ast = print_ast;
+        self
+    }
+
+    pub fn with_emit(mut self, emit: Vec<Emit>) -> Driver {
+        self.emit = emit;
+        self
+    }
+
+    pub fn with_error_format(mut self, error_format: ErrorFormat) -> Driver {
+        self.error_format = error_format;
+        self
+    }
+
+    pub fn with_toolchain(mut self, toolchain: Toolchain) -> Driver {
+        self.toolchain = toolchain;
+        self
+    }
+
+    /// Run every input until the last stage, and link them together if the
+    /// stage is `Link`. The inputs that fail to compile are reported, and the
+    /// other ones are still compiled.
+    pub fn run(&self, inputs: &[PathBuf]) -> DriverResult<()> {
+        if self.output.is_some() && self.stage < Stage::Link && inputs.len() > 1 {
+            return Err(DriverError::MultipleOutputs);
+        }
+
+        let mut failed = 0;
+        let mut objects = vec![];
+        let mut temporaries = Temporaries::default();
+        for (index, input) in inputs.iter().enumerate() {
+            let kind = InputKind::of(input);
+            if self.stage < kind.stage() {
+                eprintln!(
+                    "Warning: {}: input file unused because linking not done",
+                    input.display()
+                );
+                continue;
+            }
+
+            let mut path = input.clone();
+            if kind == InputKind::Source {
+                let source = read_source(input)?;
+                if self.stage == Stage::Preprocess {
+                    write_output(&self.output_path(input, "i"), &source)?;
+                    continue;
+                }
+
+                let Some(output) = self.compile(input, &source) else {
+                    failed += 1;
+                    continue;
+                };
+                if self.emit.contains(&Emit::CfgDot) {
+                    self.emit_cfg_dot(input, &output.program)?;
+                }
+                path = self.intermediate_path(input, index, Stage::Compile, "s");
+                if self.stage > Stage::Compile {
+                    temporaries.0.push(path.clone());
+                }
+                write_output(&path, &output.asm)?;
+                if self.stage == Stage::Compile {
+                    continue;
+                }
+            }
+
+            if kind != InputKind::Object {
+                let object = self.intermediate_path(input, index, Stage::Assemble, "o");
+                if self.stage > Stage::Assemble {
+                    temporaries.0.push(object.clone());
+                }
+                self.toolchain.assemble(&path, &object)?;
+                path = object;
+            }
+            objects.push(path);
+        }
+
+        if failed > 0 {
+            return Err(DriverError::CompilationFailed(failed));
+        }
+        if self.stage == Stage::Link {
+            let executable = self
+                .output
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_EXECUTABLE));
+            self.toolchain.link(&objects, &executable)?;
+        }
+        Ok(())
+    }
+
+    /// Compile a source file and report its diagnostics.
+    fn compile(&self, file: &Path, source: &str) -> Option<Output> {
+        match c_compiler::compile(source, &self.options) {
+            Ok(output) => {
+                self.print_ast(&output.program);
+                self.report_warnings(file, source, &output.warnings);
+                self.print_ir(&output.ir_dumps);
+                Some(output)
+            }
+            Err(diagnostics) => {
+                self.report(file, source, &diagnostics);
+                None
+            }
+        }
+    }
+
+    /// Write the control-flow graphs of a source to `<input>.dot` in the
+    /// current directory.
+    fn emit_cfg_dot(&self, input: &Path, program: &Program) -> DriverResult<()> {
+        let cfgs = CfgBuilder::new().build(program)?;
+        let path = Path::new(input.file_name().unwrap_or_default()).with_extension("dot");
+        write_output(&path, &cfg::to_dot(&cfgs))
+    }
+
+    pub fn print_ast(&self, program: &Program) {
+        if self.print_ast {
+            println!("AST output:\n{:#?}\n", program);
+        }
+    }
+
+    /// Print the IR that is dumped after the passes of `--print-after`.
+    pub fn print_ir(&self, ir_dumps: &[String]) {
+        for dump in ir_dumps {
+            eprintln!("{}\n", dump);
+        }
+    }
+
+    /// Print the AST, the warnings and the errors of a program that failed.
+    pub fn report(&self, file: &Path, source: &str, diagnostics: &Diagnostics) {
+        // The AST is printed even if there are errors, the failed statements
+        // show up as `Error` nodes.
+        if let Some(ref program) = diagnostics.program {
+            self.print_ast(program);
+        }
+        match self.error_format {
+            ErrorFormat::Human => {
+                self.report_warnings(file, source, &diagnostics.warnings);
+                for err in &diagnostics.errors {
+                    eprintln!("{}: {}", location(file, source, err.span()), err);
+                }
+            }
+            ErrorFormat::Json => {
+                for diagnostic in diagnostics.to_diagnostics() {
+                    eprintln!("{}", json::render(&diagnostic, Some(file), source));
+                }
+            }
+        }
+    }
+
+    pub fn report_warnings(&self, file: &Path, source: &str, warnings: &[CompileWarning]) {
+        for warning in warnings {
+            match self.error_format {
+                ErrorFormat::Human => eprintln!(
+                    "{}: Warning: {} [-W{}]",
+                    location(file, source, Some(warning.span())),
+                    warning,
+                    warning.kind().name()
+                ),
+                ErrorFormat::Json => {
+                    let diagnostic = Diagnostic::from(warning);
+                    eprintln!("{}", json::render(&diagnostic, Some(file), source));
+                }
+            }
+        }
+    }
+
+    /// The path of the file that is written when the driver stops after the
+    /// stage, `-o` or the input's name in the current directory.
+    fn output_path(&self, input: &Path, extension: &str) -> PathBuf {
+        if let Some(ref output) = self.output {
+            return output.clone();
+        }
+        if self.stage == Stage::Preprocess {
+            return PathBuf::from(STDOUT_PATH);
+        }
+        Path::new(input.file_name().unwrap_or_default()).with_extension(extension)
+    }
+
+    /// The path of a file that the stage writes. It's a temporary file if
+    /// the driver goes on with the next stages.
+    fn intermediate_path(
+        &self,
+        input: &Path,
+        index: usize,
+        stage: Stage,
+        extension: &str,
+    ) -> PathBuf {
+        if self.stage == stage {
+            return self.output_path(input, extension);
+        }
+        env::temp_dir().join(format!(
+            "c_compiler-{}-{}.{}",
+            process::id(),
+            index,
+            extension
+        ))
+    }
+}
+
+/// Where a diagnostic is in the human format, `file:line:column:` like `cc`
+/// prints it, or only the file if the span isn't known.
+fn location(file: &Path, source: &str, span: Option<Span>) -> String {
+    match span {
+        Some(span) => {
+            let (line, column) = json::line_column(source, span.lo);
+            format!("{}:{}:{}", file.display(), line, column)
+        }
+        None => file.display().to_string(),
+    }
+}
+
+/// The intermediate files, they are removed when the driver is done even if
+/// it fails.
+#[derive(Debug, Default)]
+struct Temporaries(Vec<PathBuf>);
+
+impl Drop for Temporaries {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+pub fn read_source(path: &Path) -> DriverResult<String> {
+    fs::read_to_string(path).map_err(|err| DriverError::Read(path.to_path_buf(), err))
+}
+
+fn write_output(path: &Path, content: &str) -> DriverResult<()> {
+    let mut content = content.to_string();
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    let result = if path == Path::new(STDOUT_PATH) {
+        io::stdout().lock().write_all(content.as_bytes())
+    } else {
+        fs::write(path, content)
+    };
+    result.map_err(|err| DriverError::Write(path.to_path_buf(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_kinds() {
+        assert_eq!(InputKind::of(Path::new("a.c")), InputKind::Source);
+        assert_eq!(InputKind::of(Path::new("dir/a.s")), InputKind::Assembly);
+        assert_eq!(InputKind::of(Path::new("a.o")), InputKind::Object);
+        assert_eq!(InputKind::of(Path::new("libfoo.a")), InputKind::Object);
+    }
+
+    #[test]
+    fn test_output_paths() {
+        let driver = Driver::new(CompileOptions::default()).with_stage(Stage::Compile);
+        let input = Path::new("src/dir/prog.c");
+        assert_eq!(
+            driver.intermediate_path(input, 0, Stage::Compile, "s"),
+            PathBuf::from("prog.s")
+        );
+        // The assembly is a temporary file when the object file is written.
+        let driver = driver.with_stage(Stage::Assemble);
+        assert_ne!(
+            driver.intermediate_path(input, 0, Stage::Compile, "s"),
+            PathBuf::from("prog.s")
+        );
+        assert_eq!(
+            driver.intermediate_path(input, 0, Stage::Assemble, "o"),
+            PathBuf::from("prog.o")
+        );
+
+        let driver = driver.with_output(Some("out.o".into()));
+        assert_eq!(
+            driver.intermediate_path(input, 0, Stage::Assemble, "o"),
+            PathBuf::from("out.o")
+        );
+
+        let driver = Driver::new(CompileOptions::default()).with_stage(Stage::Preprocess);
+        assert_eq!(driver.output_path(input, "i"), PathBuf::from(STDOUT_PATH));
+    }
+
+    #[test]
+    fn test_warning_flags() {
+        let mut options = CompileOptions::default();
+        let flags = [
+            "no-unused-variable",
+            "no-uninitialized",
+            "uninitialized",
+            "error",
+        ];
+        for flag in flags {
+            flag.parse::<WarningFlag>().unwrap().apply(&mut options);
+        }
+        assert_eq!(
+            options
+                .disabled_warnings
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            [WarningKind::UnusedVariable]
+        );
+        assert!(options.warnings_as_errors);
+
+        // The warnings of the other compilers are ignored.
+        assert_eq!(
+            "no-such-warning".parse::<WarningFlag>(),
+            Ok(WarningFlag::Unknown("no-such-warning".into()))
+        );
+        "all".parse::<WarningFlag>().unwrap().apply(&mut options);
+        assert!(options.disabled_warnings.is_empty());
+    }
+
+    #[test]
+    fn test_locations() {
+        let source = "int main() {\n  return x;\n}";
+        let span = Span { lo: 22, hi: 23 };
+        assert_eq!(location(Path::new("a.c"), source, Some(span)), "a.c:2:10");
+        assert_eq!(location(Path::new("dir/b.c"), source, None), "dir/b.c");
+    }
+
+    #[test]
+    fn test_errors() {
+        let driver = Driver::new(CompileOptions::default())
+            .with_stage(Stage::Compile)
+            .with_output(Some("out.s".into()));
+        let err = driver.run(&["a.c".into(), "b.c".into()]).unwrap_err();
+        assert!(matches!(err, DriverError::MultipleOutputs));
+        assert_eq!(err.exit_code(), 2);
+
+        let err = driver.run(&["does/not/exist.c".into()]).unwrap_err();
+        assert!(matches!(err, DriverError::Read(..)));
+        assert_eq!(err.exit_code(), 3);
+    }
+}
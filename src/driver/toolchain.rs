@@ -0,0 +1,292 @@
+//! The external assembler and linker. They are configured with the command
+//! line options, then the environment variables, then the config file, e.g.
+//!
+//! ```toml
+//! # c_compiler.toml
+//! as = "clang --target=arm64-apple-macos13 -c"
+//! linker = "cc"
+//! ld = "clang --target=arm64-apple-macos13"
+//! sysroot = "/opt/MacOSX13.3.sdk"
+//! ```
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use toml::Spanned;
+
+use super::{DriverError, DriverResult};
+
+/// The config file that is read from the current directory if there is no
+/// `--config`.
+pub const DEFAULT_CONFIG_FILE: &str = "c_compiler.toml";
+const DEFAULT_MACOS_VERSION_MIN: &str = "13.0.0";
+
+/// How the object files are linked.
+#[derive(Debug, PartialEq, Clone, Copy, ValueEnum)]
+pub enum LinkerKind {
+    /// Call `ld` directly with the arguments of the macOS linker.
+    Ld,
+    /// Link with a compiler driver like `cc`, it knows where the system
+    /// libraries are.
+    Cc,
+}
+
+/// The keys of the config file, they are the names of the options.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+    #[serde(rename = "as")]
+    assembler: Option<String>,
+    ld: Option<String>,
+    linker: Option<Spanned<String>>,
+    sysroot: Option<PathBuf>,
+    macos_version_min: Option<String>,
+}
+
+/// The toolchain options that are set, the other ones use the defaults.
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct ToolchainConfig {
+    pub assembler: Option<String>,
+    pub linker: Option<String>,
+    pub linker_kind: Option<LinkerKind>,
+    pub sysroot: Option<PathBuf>,
+    pub macos_version_min: Option<String>,
+}
+
+impl ToolchainConfig {
+    /// Read a TOML config file.
+    pub fn load(path: &Path) -> DriverResult<ToolchainConfig> {
+        let src =
+            fs::read_to_string(path).map_err(|err| DriverError::Read(path.to_path_buf(), err))?;
+        ToolchainConfig::parse(&src)
+            .map_err(|(line, msg)| DriverError::InvalidConfig(path.to_path_buf(), line, msg))
+    }
+
+    /// Parse a config file, or return the line number and the reason why it's
+    /// invalid.
+    fn parse(src: &str) -> Result<ToolchainConfig, (usize, String)> {
+        let line = |offset: usize| src[..offset].matches('\n').count() + 1;
+        let file: ConfigFile = toml::from_str(src).map_err(|err| {
+            let offset = err.span().map_or(0, |span| span.start);
+            (line(offset), err.message().to_string())
+        })?;
+        let linker_kind = file
+            .linker
+            .map(|linker| {
+                LinkerKind::from_str(linker.get_ref(), false).map_err(|_| {
+                    (
+                        line(linker.span().start),
+                        format!("Unknown linker {:?}", linker.get_ref()),
+                    )
+                })
+            })
+            .transpose()?;
+        Ok(ToolchainConfig {
+            assembler: file.assembler,
+            linker: file.ld,
+            linker_kind,
+            sysroot: file.sysroot,
+            macos_version_min: file.macos_version_min,
+        })
+    }
+
+    /// Use the options of the other config that aren't set in this one.
+    pub fn or(self, other: ToolchainConfig) -> ToolchainConfig {
+        ToolchainConfig {
+            assembler: self.assembler.or(other.assembler),
+            linker: self.linker.or(other.linker),
+            linker_kind: self.linker_kind.or(other.linker_kind),
+            sysroot: self.sysroot.or(other.sysroot),
+            macos_version_min: self.macos_version_min.or(other.macos_version_min),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Toolchain {
+    /// The assembler command, it can have arguments, e.g. `clang -c`.
+    assembler: String,
+    /// The linker command, it can have arguments too.
+    linker: String,
+    linker_kind: LinkerKind,
+    /// The SDK to link against, `xcrun` finds it if it's not set.
+    sysroot: Option<PathBuf>,
+    macos_version_min: String,
+}
+
+impl Default for Toolchain {
+    fn default() -> Toolchain {
+        Toolchain::from(ToolchainConfig::default())
+    }
+}
+
+impl From<ToolchainConfig> for Toolchain {
+    fn from(config: ToolchainConfig) -> Toolchain {
+        let linker_kind = config.linker_kind.unwrap_or(LinkerKind::Ld);
+        let default_linker = match linker_kind {
+            LinkerKind::Ld => "ld",
+            LinkerKind::Cc => "cc",
+        };
+        Toolchain {
+            assembler: config.assembler.unwrap_or_else(|| "as".into()),
+            linker: config.linker.unwrap_or_else(|| default_linker.into()),
+            linker_kind,
+            sysroot: config.sysroot,
+            macos_version_min: config
+                .macos_version_min
+                .unwrap_or_else(|| DEFAULT_MACOS_VERSION_MIN.into()),
+        }
+    }
+}
+
+impl Toolchain {
+    pub fn assemble(&self, asm_file: &Path, obj_file: &Path) -> DriverResult<()> {
+        // as -o output.o output.s
+        let output = run_tool(
+            command(&self.assembler)
+                .arg("-o")
+                .arg(obj_file)
+                .arg(asm_file),
+        )?;
+        if !output.status.success() {
+            return Err(DriverError::AssemblerFailed(
+                asm_file.to_path_buf(),
+                failure_message(&output),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn link(&self, obj_files: &[PathBuf], executable_file: &Path) -> DriverResult<()> {
+        let mut linker = command(&self.linker);
+        linker.arg("-o").arg(executable_file).args(obj_files);
+        match self.linker_kind {
+            LinkerKind::Ld => {
+                // ld -macosx_version_min 13.0.0 -o output output.o -lSystem -syslibroot `xcrun -sdk macosx --show-sdk-path` -e _main -arch arm64
+                let sysroot = match self.sysroot {
+                    Some(ref sysroot) => sysroot.clone(),
+                    None => sdk_path()?,
+                };
+                linker
+                    .args(["-macosx_version_min", &self.macos_version_min])
+                    .args(["-lSystem", "-syslibroot"])
+                    .arg(sysroot)
+                    .args(["-e", "_main", "-arch", "arm64"]);
+            }
+            LinkerKind::Cc => {
+                if let Some(ref sysroot) = self.sysroot {
+                    linker.arg(format!("--sysroot={}", sysroot.display()));
+                }
+            }
+        }
+
+        let output = run_tool(&mut linker)?;
+        if !output.status.success() {
+            return Err(DriverError::LinkerFailed(failure_message(&output)));
+        }
+        Ok(())
+    }
+}
+
+/// A command with its arguments, split by the whitespace like `make` does.
+fn command(tool: &str) -> Command {
+    let mut words = tool.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or_default());
+    command.args(words);
+    command
+}
+
+fn sdk_path() -> DriverResult<PathBuf> {
+    let output = run_tool(Command::new("xcrun").args(["-sdk", "macosx", "--show-sdk-path"]))?;
+    if !output.status.success() {
+        return Err(DriverError::SdkNotFound(failure_message(&output)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().into())
+}
+
+/// Run a tool and return its output. Its stderr is shown if it succeeds,
+/// e.g. for the warnings.
+fn run_tool(command: &mut Command) -> DriverResult<Output> {
+    let tool = command.get_program().to_string_lossy().into_owned();
+    let output = command
+        .output()
+        .map_err(|err| DriverError::ToolNotFound(tool, err))?;
+    if output.status.success() {
+        let _ = io::stderr().write_all(&output.stderr);
+    }
+    Ok(output)
+}
+
+/// The stderr of a tool that failed, or its exit status if it's empty.
+fn failure_message(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr)
+        .trim_end()
+        .to_string();
+    if stderr.is_empty() {
+        output.status.to_string()
+    } else {
+        stderr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file() {
+        let config = ToolchainConfig::parse(
+            "# Cross toolchain\n\
+             as = \"clang --target=arm64-apple-macos13 -c\"\n\
+             linker = \"cc\"\n\
+             \n\
+             sysroot = \"/opt/sdk\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            ToolchainConfig {
+                assembler: Some("clang --target=arm64-apple-macos13 -c".into()),
+                linker_kind: Some(LinkerKind::Cc),
+                sysroot: Some("/opt/sdk".into()),
+                ..ToolchainConfig::default()
+            }
+        );
+
+        assert_eq!(
+            ToolchainConfig::parse("as = \"as\"\nlinker = \"gold\"").unwrap_err(),
+            (2, "Unknown linker \"gold\"".into())
+        );
+        let (line, msg) = ToolchainConfig::parse("as = \"as\"\nassembler = \"as\"").unwrap_err();
+        assert_eq!(line, 2);
+        assert!(msg.starts_with("unknown field `assembler`"), "{}", msg);
+        // The values are TOML strings.
+        let (line, msg) = ToolchainConfig::parse("ld = cc").unwrap_err();
+        assert_eq!(line, 1);
+        assert!(msg.contains("string"), "{}", msg);
+    }
+
+    #[test]
+    fn test_precedence() {
+        let options = ToolchainConfig {
+            linker_kind: Some(LinkerKind::Cc),
+            ..ToolchainConfig::default()
+        };
+        let file = ToolchainConfig {
+            assembler: Some("aarch64-as".into()),
+            linker_kind: Some(LinkerKind::Ld),
+            ..ToolchainConfig::default()
+        };
+        let toolchain = Toolchain::from(options.or(file));
+        assert_eq!(toolchain.assembler, "aarch64-as");
+        assert_eq!(toolchain.linker_kind, LinkerKind::Cc);
+        // The default linker depends on the kind.
+        assert_eq!(toolchain.linker, "cc");
+    }
+}
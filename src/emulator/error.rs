@@ -0,0 +1,24 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EmulatorError {
+    #[error("Line {0}: unknown instruction {1:?}")]
+    UnknownInstruction(usize, String),
+    #[error("Line {0}: invalid operands in {1:?}")]
+    InvalidOperands(usize, String),
+    #[error("Line {0}: immediate {1} can't be encoded")]
+    InvalidImmediate(usize, i64),
+    #[error("Label {0:?} is defined more than once")]
+    DuplicateLabel(String),
+    #[error("Label {0:?} is not defined")]
+    UndefinedLabel(String),
+
+    #[error("Invalid memory access at {0:#x}")]
+    InvalidMemoryAccess(u64),
+    #[error("Stack pointer {0:#x} isn't 16 byte aligned")]
+    MisalignedStackPointer(u64),
+    #[error("Jump to an invalid code address {0:#x}")]
+    InvalidCodeAddress(u64),
+    #[error("Program didn't finish in {0} steps")]
+    StepLimitExceeded(u64),
+}
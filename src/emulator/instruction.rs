@@ -0,0 +1,644 @@
+use crate::emulator::{EmulatorError, EmulatorResult};
+
+/// A register operand. The register number 31 is the zero register or the
+/// stack pointer depending on the instruction, so they have their own variants.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Reg {
+    W(u8),
+    X(u8),
+    Wzr,
+    Xzr,
+    Sp,
+    S(u8),
+    D(u8),
+}
+
+impl Reg {
+    pub fn is_64bit(self) -> bool {
+        matches!(self, Reg::X(_) | Reg::Xzr | Reg::Sp | Reg::D(_))
+    }
+
+    pub fn is_floating(self) -> bool {
+        matches!(self, Reg::S(_) | Reg::D(_))
+    }
+
+    pub fn bits(self) -> u32 {
+        if self.is_64bit() {
+            64
+        } else {
+            32
+        }
+    }
+
+    fn parse(operand: &str) -> Option<Reg> {
+        let reg = match operand {
+            "wzr" => Reg::Wzr,
+            "xzr" => Reg::Xzr,
+            "sp" => Reg::Sp,
+            "fp" => Reg::X(29),
+            "lr" => Reg::X(30),
+            _ => {
+                let (kind, index) = operand.split_at(1);
+                let index: u8 = index.parse().ok()?;
+                match kind {
+                    "w" if index < 31 => Reg::W(index),
+                    "x" if index < 31 => Reg::X(index),
+                    "s" if index < 32 => Reg::S(index),
+                    "d" if index < 32 => Reg::D(index),
+                    _ => return None,
+                }
+            }
+        };
+        Some(reg)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operand {
+    Reg(Reg),
+    Imm(i64),
+}
+
+/// Condition codes of the conditional instructions.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Cond {
+    Eq,
+    Ne,
+    Hs,
+    Lo,
+    Mi,
+    Pl,
+    Vs,
+    Vc,
+    Hi,
+    Ls,
+    Ge,
+    Lt,
+    Gt,
+    Le,
+    Al,
+}
+
+impl Cond {
+    fn parse(cond: &str) -> Option<Cond> {
+        let cond = match cond {
+            "eq" => Cond::Eq,
+            "ne" => Cond::Ne,
+            "hs" | "cs" => Cond::Hs,
+            "lo" | "cc" => Cond::Lo,
+            "mi" => Cond::Mi,
+            "pl" => Cond::Pl,
+            "vs" => Cond::Vs,
+            "vc" => Cond::Vc,
+            "hi" => Cond::Hi,
+            "ls" => Cond::Ls,
+            "ge" => Cond::Ge,
+            "lt" => Cond::Lt,
+            "gt" => Cond::Gt,
+            "le" => Cond::Le,
+            "al" => Cond::Al,
+            _ => return None,
+        };
+        Some(cond)
+    }
+}
+
+/// Addressing modes of the loads and stores.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AddressMode {
+    /// `[base, #offset]`
+    Offset,
+    /// `[base, #offset]!`, the base is updated before the access.
+    PreIndex,
+    /// `[base], #offset`, the base is updated after the access.
+    PostIndex,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Address {
+    pub base: Reg,
+    pub offset: i64,
+    pub mode: AddressMode,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AluOp {
+    Add,
+    Sub,
+    Mul,
+    Sdiv,
+    And,
+    Orr,
+    Eor,
+    Lsl,
+    Lsr,
+    Asr,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FpOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// The subset of the A64 instructions that the codegen emits.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Instruction {
+    Mov(Reg, Operand),
+    /// `movk` if the flag is set, `movz` otherwise.
+    MovWide(Reg, u16, u32, bool),
+    Alu(AluOp, Reg, Reg, Operand),
+    Msub(Reg, Reg, Reg, Reg),
+    Neg(Reg, Reg),
+    Mvn(Reg, Reg),
+    Cmp(Reg, Operand),
+    Cset(Reg, Cond),
+    Ldr(Reg, Address),
+    Str(Reg, Address),
+    Ldp(Reg, Reg, Address),
+    Stp(Reg, Reg, Address),
+    B(String),
+    BCond(Cond, String),
+    Cbz(Reg, String),
+    Cbnz(Reg, String),
+    Bl(String),
+    Ret,
+    Fmov(Reg, Reg),
+    Fp(FpOp, Reg, Reg, Reg),
+    Fneg(Reg, Reg),
+    /// Compare with zero if the second register is missing.
+    Fcmp(Reg, Option<Reg>),
+    Scvtf(Reg, Reg),
+    Fcvtzs(Reg, Reg),
+    Fcvt(Reg, Reg),
+}
+
+impl Instruction {
+    /// Parse a single instruction and check that it can be encoded, like an
+    /// assembler would.
+    pub fn parse(line_no: usize, line: &str) -> EmulatorResult<Instruction> {
+        let (mnemonic, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let operands = split_operands(rest);
+        let parser = OperandParser {
+            line_no,
+            line,
+            operands: &operands,
+        };
+
+        let instruction = match mnemonic {
+            "mov" => {
+                let dst = parser.reg(0)?;
+                match parser.operand(1)? {
+                    Operand::Imm(imm) if !is_mov_immediate(imm, dst.bits()) => {
+                        return Err(parser.invalid_immediate(imm))
+                    }
+                    src => Instruction::Mov(dst, src),
+                }
+            }
+            "movz" | "movk" => {
+                parser.expect_count(&[2, 3])?;
+                let imm = parser.imm(1)?;
+                let shift = if operands.len() == 3 {
+                    parser.shift(2)?
+                } else {
+                    0
+                };
+                let dst = parser.reg(0)?;
+                if !(0..=0xffff).contains(&imm) || shift % 16 != 0 || shift >= dst.bits() as i64 {
+                    return Err(parser.invalid_immediate(imm));
+                }
+                Instruction::MovWide(dst, imm as u16, shift as u32, mnemonic == "movk")
+            }
+            "add" | "sub" => {
+                parser.expect_count(&[3])?;
+                let op = if mnemonic == "add" {
+                    AluOp::Add
+                } else {
+                    AluOp::Sub
+                };
+                let rhs = parser.operand(2)?;
+                if let Operand::Imm(imm) = rhs {
+                    if !is_arith_immediate(imm) {
+                        return Err(parser.invalid_immediate(imm));
+                    }
+                }
+                Instruction::Alu(op, parser.reg(0)?, parser.reg(1)?, rhs)
+            }
+            "mul" | "sdiv" | "and" | "orr" | "eor" | "lsl" | "lsr" | "asr" => {
+                parser.expect_count(&[3])?;
+                let op = match mnemonic {
+                    "mul" => AluOp::Mul,
+                    "sdiv" => AluOp::Sdiv,
+                    "and" => AluOp::And,
+                    "orr" => AluOp::Orr,
+                    "eor" => AluOp::Eor,
+                    "lsl" => AluOp::Lsl,
+                    "lsr" => AluOp::Lsr,
+                    _ => AluOp::Asr,
+                };
+                Instruction::Alu(
+                    op,
+                    parser.reg(0)?,
+                    parser.reg(1)?,
+                    Operand::Reg(parser.reg(2)?),
+                )
+            }
+            "msub" => {
+                parser.expect_count(&[4])?;
+                Instruction::Msub(
+                    parser.reg(0)?,
+                    parser.reg(1)?,
+                    parser.reg(2)?,
+                    parser.reg(3)?,
+                )
+            }
+            "neg" | "mvn" => {
+                parser.expect_count(&[2])?;
+                let (dst, src) = (parser.reg(0)?, parser.reg(1)?);
+                if mnemonic == "neg" {
+                    Instruction::Neg(dst, src)
+                } else {
+                    Instruction::Mvn(dst, src)
+                }
+            }
+            "cmp" => {
+                parser.expect_count(&[2])?;
+                let rhs = parser.operand(1)?;
+                if let Operand::Imm(imm) = rhs {
+                    if !is_arith_immediate(imm) {
+                        return Err(parser.invalid_immediate(imm));
+                    }
+                }
+                Instruction::Cmp(parser.reg(0)?, rhs)
+            }
+            "cset" => {
+                parser.expect_count(&[2])?;
+                let cond = Cond::parse(&operands[1]).ok_or_else(|| parser.invalid())?;
+                Instruction::Cset(parser.reg(0)?, cond)
+            }
+            "ldr" | "str" => {
+                let reg = parser.reg(0)?;
+                let address = parser.address(1, reg)?;
+                if mnemonic == "ldr" {
+                    Instruction::Ldr(reg, address)
+                } else {
+                    Instruction::Str(reg, address)
+                }
+            }
+            "ldp" | "stp" => {
+                let (first, second) = (parser.reg(0)?, parser.reg(1)?);
+                let address = parser.address(2, first)?;
+                if mnemonic == "ldp" {
+                    Instruction::Ldp(first, second, address)
+                } else {
+                    Instruction::Stp(first, second, address)
+                }
+            }
+            "b" => Instruction::B(parser.label(0)?),
+            "bl" => Instruction::Bl(parser.label(0)?),
+            "cbz" | "cbnz" => {
+                parser.expect_count(&[2])?;
+                let (reg, label) = (parser.reg(0)?, parser.label(1)?);
+                if mnemonic == "cbz" {
+                    Instruction::Cbz(reg, label)
+                } else {
+                    Instruction::Cbnz(reg, label)
+                }
+            }
+            "ret" => {
+                parser.expect_count(&[0])?;
+                Instruction::Ret
+            }
+            "fmov" => {
+                parser.expect_count(&[2])?;
+                Instruction::Fmov(parser.reg(0)?, parser.reg(1)?)
+            }
+            "fadd" | "fsub" | "fmul" | "fdiv" => {
+                parser.expect_count(&[3])?;
+                let op = match mnemonic {
+                    "fadd" => FpOp::Add,
+                    "fsub" => FpOp::Sub,
+                    "fmul" => FpOp::Mul,
+                    _ => FpOp::Div,
+                };
+                Instruction::Fp(op, parser.reg(0)?, parser.reg(1)?, parser.reg(2)?)
+            }
+            "fneg" => {
+                parser.expect_count(&[2])?;
+                Instruction::Fneg(parser.reg(0)?, parser.reg(1)?)
+            }
+            "fcmp" => {
+                parser.expect_count(&[2])?;
+                let rhs = match operands[1].as_str() {
+                    "#0.0" | "#0" => None,
+                    _ => Some(parser.reg(1)?),
+                };
+                Instruction::Fcmp(parser.reg(0)?, rhs)
+            }
+            "scvtf" | "fcvtzs" | "fcvt" => {
+                parser.expect_count(&[2])?;
+                let (dst, src) = (parser.reg(0)?, parser.reg(1)?);
+                match mnemonic {
+                    "scvtf" => Instruction::Scvtf(dst, src),
+                    "fcvtzs" => Instruction::Fcvtzs(dst, src),
+                    _ => Instruction::Fcvt(dst, src),
+                }
+            }
+            _ => {
+                // Conditional branches can be written as `b.eq` or `beq`.
+                let cond = mnemonic
+                    .strip_prefix("b.")
+                    .or_else(|| mnemonic.strip_prefix('b'))
+                    .and_then(Cond::parse)
+                    .ok_or_else(|| EmulatorError::UnknownInstruction(line_no, line.to_string()))?;
+                Instruction::BCond(cond, parser.label(0)?)
+            }
+        };
+
+        instruction.check_registers(line_no, line)?;
+        Ok(instruction)
+    }
+
+    /// Reject the register combinations that aren't valid for the instruction.
+    fn check_registers(&self, line_no: usize, line: &str) -> EmulatorResult<()> {
+        let same_width = |regs: &[Reg]| regs.iter().all(|reg| reg.bits() == regs[0].bits());
+        let is_int = |regs: &[Reg]| regs.iter().all(|reg| !reg.is_floating());
+        let is_fp = |regs: &[Reg]| regs.iter().all(|reg| reg.is_floating());
+
+        let valid = match self {
+            Instruction::Mov(dst, Operand::Reg(src)) => {
+                is_int(&[*dst, *src]) && same_width(&[*dst, *src])
+            }
+            Instruction::Mov(dst, Operand::Imm(_)) | Instruction::MovWide(dst, ..) => {
+                is_int(&[*dst]) && *dst != Reg::Sp
+            }
+            Instruction::Alu(_, dst, lhs, Operand::Reg(rhs)) => {
+                is_int(&[*dst, *lhs, *rhs]) && same_width(&[*dst, *lhs, *rhs])
+            }
+            Instruction::Alu(_, dst, lhs, Operand::Imm(_)) => {
+                is_int(&[*dst, *lhs]) && same_width(&[*dst, *lhs])
+            }
+            Instruction::Msub(a, b, c, d) => {
+                is_int(&[*a, *b, *c, *d]) && same_width(&[*a, *b, *c, *d])
+            }
+            Instruction::Neg(dst, src) | Instruction::Mvn(dst, src) => {
+                is_int(&[*dst, *src]) && same_width(&[*dst, *src])
+            }
+            Instruction::Cmp(lhs, Operand::Reg(rhs)) => {
+                is_int(&[*lhs, *rhs]) && same_width(&[*lhs, *rhs])
+            }
+            Instruction::Cmp(lhs, Operand::Imm(_)) => is_int(&[*lhs]),
+            Instruction::Cset(dst, _) | Instruction::Cbz(dst, _) | Instruction::Cbnz(dst, _) => {
+                is_int(&[*dst])
+            }
+            Instruction::Ldr(_, address) | Instruction::Str(_, address) => {
+                is_int(&[address.base]) && address.base.is_64bit()
+            }
+            Instruction::Ldp(first, second, address) | Instruction::Stp(first, second, address) => {
+                same_width(&[*first, *second]) && is_int(&[address.base]) && address.base.is_64bit()
+            }
+            // Moves between the register files need the same width.
+            Instruction::Fmov(dst, src) => {
+                same_width(&[*dst, *src]) && (dst.is_floating() || src.is_floating())
+            }
+            Instruction::Fp(_, dst, lhs, rhs) => {
+                is_fp(&[*dst, *lhs, *rhs]) && same_width(&[*dst, *lhs, *rhs])
+            }
+            Instruction::Fneg(dst, src) => is_fp(&[*dst, *src]) && same_width(&[*dst, *src]),
+            Instruction::Fcmp(lhs, rhs) => match rhs {
+                Some(rhs) => is_fp(&[*lhs, *rhs]) && same_width(&[*lhs, *rhs]),
+                None => is_fp(&[*lhs]),
+            },
+            Instruction::Scvtf(dst, src) => is_fp(&[*dst]) && is_int(&[*src]),
+            Instruction::Fcvtzs(dst, src) => is_int(&[*dst]) && is_fp(&[*src]),
+            Instruction::Fcvt(dst, src) => is_fp(&[*dst, *src]) && !same_width(&[*dst, *src]),
+            Instruction::B(_) | Instruction::BCond(..) | Instruction::Bl(_) | Instruction::Ret => {
+                true
+            }
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(EmulatorError::InvalidOperands(line_no, line.to_string()))
+        }
+    }
+}
+
+/// Split the operands at the commas that are outside of the brackets.
+fn split_operands(operands: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    for c in operands.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        result.push(current.trim().to_string());
+    }
+    result
+}
+
+fn parse_imm(operand: &str) -> Option<i64> {
+    let imm = operand.strip_prefix('#')?;
+    let (negative, imm) = match imm.strip_prefix('-') {
+        Some(imm) => (true, imm),
+        None => (false, imm),
+    };
+    let value = match imm.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => imm.parse().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// Whether `add`/`sub`/`cmp` can encode the immediate: 12 bits, optionally
+/// shifted by 12. Negative values are encoded with the opposite instruction.
+fn is_arith_immediate(imm: i64) -> bool {
+    let imm = imm.unsigned_abs();
+    imm < (1 << 12) || (imm.is_multiple_of(1 << 12) && imm < (1 << 24))
+}
+
+/// Whether `mov` can encode the immediate as `movz`, `movn` or `orr` with a
+/// bitmask immediate.
+fn is_mov_immediate(imm: i64, bits: u32) -> bool {
+    let mask = if bits == 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    };
+    // Values that don't fit in the register are rejected, but the negative
+    // ones are accepted as their two's complement representation.
+    if bits == 32 && !(i32::MIN as i64..=u32::MAX as i64).contains(&imm) {
+        return false;
+    }
+    let value = imm as u64 & mask;
+    let halfwords = |value: u64| {
+        (0..bits / 16)
+            .filter(|i| (value >> (i * 16)) & 0xffff != 0)
+            .count()
+    };
+    halfwords(value) <= 1 || halfwords(!value & mask) <= 1 || is_bitmask_immediate(value, bits)
+}
+
+/// Whether the value is a repeating pattern of a rotated run of ones, which
+/// is what the logical instructions can encode.
+fn is_bitmask_immediate(value: u64, bits: u32) -> bool {
+    let mask = if bits == 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    };
+    if value == 0 || value == mask {
+        return false;
+    }
+
+    let mut size = 2;
+    while size <= bits {
+        let element_mask = if size == 64 {
+            u64::MAX
+        } else {
+            (1 << size) - 1
+        };
+        let element = value & element_mask;
+        let replicated = (0..bits / size).fold(0, |acc, i| acc | (element << (i * size)));
+        if replicated == value {
+            let ones = element.count_ones();
+            let run = (1u64 << ones) - 1;
+            return (0..size).any(|rotation| {
+                let rotated = if rotation == 0 {
+                    element
+                } else {
+                    ((element >> rotation) | (element << (size - rotation))) & element_mask
+                };
+                rotated == run
+            });
+        }
+        size *= 2;
+    }
+    false
+}
+
+struct OperandParser<'a> {
+    line_no: usize,
+    line: &'a str,
+    operands: &'a [String],
+}
+
+impl OperandParser<'_> {
+    fn invalid(&self) -> EmulatorError {
+        EmulatorError::InvalidOperands(self.line_no, self.line.to_string())
+    }
+
+    fn invalid_immediate(&self, imm: i64) -> EmulatorError {
+        EmulatorError::InvalidImmediate(self.line_no, imm)
+    }
+
+    fn expect_count(&self, counts: &[usize]) -> EmulatorResult<()> {
+        if counts.contains(&self.operands.len()) {
+            Ok(())
+        } else {
+            Err(self.invalid())
+        }
+    }
+
+    fn get(&self, index: usize) -> EmulatorResult<&str> {
+        self.operands
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| self.invalid())
+    }
+
+    fn reg(&self, index: usize) -> EmulatorResult<Reg> {
+        Reg::parse(self.get(index)?).ok_or_else(|| self.invalid())
+    }
+
+    fn imm(&self, index: usize) -> EmulatorResult<i64> {
+        parse_imm(self.get(index)?).ok_or_else(|| self.invalid())
+    }
+
+    fn operand(&self, index: usize) -> EmulatorResult<Operand> {
+        let operand = self.get(index)?;
+        if operand.starts_with('#') {
+            Ok(Operand::Imm(self.imm(index)?))
+        } else {
+            Ok(Operand::Reg(self.reg(index)?))
+        }
+    }
+
+    /// Parse a `lsl #n` shift.
+    fn shift(&self, index: usize) -> EmulatorResult<i64> {
+        self.get(index)?
+            .strip_prefix("lsl")
+            .and_then(|shift| parse_imm(shift.trim()))
+            .ok_or_else(|| self.invalid())
+    }
+
+    fn label(&self, index: usize) -> EmulatorResult<String> {
+        self.expect_count(&[index + 1])?;
+        Ok(self.get(index)?.to_string())
+    }
+
+    /// Parse the address starting from the given operand. The register that
+    /// is loaded or stored decides the valid offsets.
+    fn address(&self, index: usize, reg: Reg) -> EmulatorResult<Address> {
+        let operand = self.get(index)?;
+        let (inner, mode) = if let Some(inner) = operand.strip_suffix("]!") {
+            (inner, AddressMode::PreIndex)
+        } else if let Some(inner) = operand.strip_suffix(']') {
+            let mode = if self.operands.len() == index + 2 {
+                AddressMode::PostIndex
+            } else {
+                AddressMode::Offset
+            };
+            (inner, mode)
+        } else {
+            return Err(self.invalid());
+        };
+        let inner = inner.strip_prefix('[').ok_or_else(|| self.invalid())?;
+        let parts = split_operands(inner);
+
+        let base = parts
+            .first()
+            .and_then(|base| Reg::parse(base))
+            .ok_or_else(|| self.invalid())?;
+        let offset = match (mode, parts.get(1)) {
+            (AddressMode::PostIndex, None) => self.imm(index + 1)?,
+            (AddressMode::PostIndex, Some(_)) => return Err(self.invalid()),
+            (_, Some(offset)) => parse_imm(offset).ok_or_else(|| self.invalid())?,
+            (_, None) => 0,
+        };
+        if parts.len() > 2 || self.operands.len() > index + 2 {
+            return Err(self.invalid());
+        }
+
+        let size = reg.bits() as i64 / 8;
+        let is_pair = index == 2;
+        let valid = if is_pair {
+            offset % size == 0 && (-64..64).contains(&(offset / size))
+        } else if mode == AddressMode::Offset {
+            // Scaled unsigned offset, or the unscaled signed offset of `ldur`/`stur`.
+            (offset % size == 0 && (0..4096).contains(&(offset / size)))
+                || (-256..256).contains(&offset)
+        } else {
+            (-256..256).contains(&offset)
+        };
+        if !valid {
+            return Err(self.invalid_immediate(offset));
+        }
+
+        Ok(Address { base, offset, mode })
+    }
+}
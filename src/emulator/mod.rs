@@ -0,0 +1,621 @@
+mod error;
+mod instruction;
+
+pub use self::error::EmulatorError;
+use self::instruction::*;
+use std::collections::HashMap;
+
+type EmulatorResult<T> = Result<T, EmulatorError>;
+
+/// Default number of instructions to execute before giving up, so that
+/// programs with infinite loops don't hang the tests.
+pub const DEFAULT_STEP_LIMIT: u64 = 100_000_000;
+/// Size of the simulated stack, same as the default main thread stack on macOS.
+const STACK_SIZE: u64 = 8 * 1024 * 1024;
+/// Lowest address of the stack.
+const STACK_BASE: u64 = 0x7000_0000;
+/// Address of the first instruction, instructions are 4 bytes each.
+const CODE_BASE: u64 = 0x1_0000_0000;
+/// Return address that `main` returns to, which ends the program.
+const EXIT_ADDRESS: u64 = 0xdead_0000;
+/// Library functions that the programs can call without defining them.
+const BUILTINS: &[&str] = &["_putchar"];
+/// Value that the builtins leave in the caller-saved registers, so that the
+/// code that expects them to survive a call is caught.
+const CLOBBERED: u64 = 0xdead_beef_dead_beef;
+
+/// Condition flags that the comparisons set.
+#[derive(Debug, Default)]
+struct Flags {
+    n: bool,
+    z: bool,
+    c: bool,
+    v: bool,
+}
+
+impl Flags {
+    fn holds(&self, cond: Cond) -> bool {
+        match cond {
+            Cond::Eq => self.z,
+            Cond::Ne => !self.z,
+            Cond::Hs => self.c,
+            Cond::Lo => !self.c,
+            Cond::Mi => self.n,
+            Cond::Pl => !self.n,
+            Cond::Vs => self.v,
+            Cond::Vc => !self.v,
+            Cond::Hi => self.c && !self.z,
+            Cond::Ls => !self.c || self.z,
+            Cond::Ge => self.n == self.v,
+            Cond::Lt => self.n != self.v,
+            Cond::Gt => !self.z && self.n == self.v,
+            Cond::Le => self.z || self.n != self.v,
+            Cond::Al => true,
+        }
+    }
+}
+
+/// Executes the assembly that the codegen generates, so the compiled programs
+/// can be tested without an AArch64 machine. Only the instructions that the
+/// codegen emits are supported, and the operands are checked like an
+/// assembler would, e.g. an immediate that doesn't fit is an error.
+pub struct Emulator {
+    instructions: Vec<Instruction>,
+    labels: HashMap<String, usize>,
+    /// General purpose registers x0-x30.
+    x: [u64; 31],
+    sp: u64,
+    /// Lower 64 bits of the SIMD/FP registers v0-v31.
+    v: [u64; 32],
+    flags: Flags,
+    pc: u64,
+    stack: Vec<u8>,
+    step_limit: u64,
+    /// What the program wrote with `putchar`.
+    stdout: String,
+}
+
+impl Emulator {
+    /// Parse the assembly text. Directives are ignored.
+    pub fn new(asm: &str) -> EmulatorResult<Emulator> {
+        let mut instructions = vec![];
+        let mut labels = HashMap::new();
+
+        for (index, line) in asm.lines().enumerate() {
+            let line = match line.find("//") {
+                Some(comment) => &line[..comment],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() || line.starts_with('.') {
+                continue;
+            }
+
+            if let Some(label) = line.strip_suffix(':') {
+                if labels
+                    .insert(label.to_string(), instructions.len())
+                    .is_some()
+                {
+                    return Err(EmulatorError::DuplicateLabel(label.to_string()));
+                }
+                continue;
+            }
+            instructions.push(Instruction::parse(index + 1, line)?);
+        }
+
+        // Check the branch targets before running anything.
+        for instruction in &instructions {
+            match instruction {
+                Instruction::B(label)
+                | Instruction::BCond(_, label)
+                | Instruction::Cbz(_, label)
+                | Instruction::Cbnz(_, label)
+                    if !labels.contains_key(label) =>
+                {
+                    return Err(EmulatorError::UndefinedLabel(label.clone()));
+                }
+                Instruction::Bl(label)
+                    if !labels.contains_key(label) && !BUILTINS.contains(&label.as_str()) =>
+                {
+                    return Err(EmulatorError::UndefinedLabel(label.clone()));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Emulator {
+            instructions,
+            labels,
+            x: [0; 31],
+            sp: 0,
+            v: [0; 32],
+            flags: Flags::default(),
+            pc: 0,
+            stack: vec![],
+            step_limit: DEFAULT_STEP_LIMIT,
+            stdout: String::new(),
+        })
+    }
+
+    /// Set the maximum number of instructions to execute, 0 means no limit.
+    pub fn with_step_limit(mut self, step_limit: u64) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    /// Run the program from `_main` until it returns, and return the value in
+    /// `w0`, which is the exit value of the program.
+    pub fn run(&mut self) -> EmulatorResult<i32> {
+        self.stack = vec![0; STACK_SIZE as usize];
+        self.sp = STACK_BASE + STACK_SIZE;
+        self.x[30] = EXIT_ADDRESS;
+        self.pc = self.label_address("_main")?;
+
+        let mut steps = 0;
+        while self.pc != EXIT_ADDRESS {
+            steps += 1;
+            if self.step_limit != 0 && steps > self.step_limit {
+                return Err(EmulatorError::StepLimitExceeded(self.step_limit));
+            }
+
+            let index = self.pc.wrapping_sub(CODE_BASE) / 4;
+            let instruction = match self.instructions.get(index as usize) {
+                Some(instruction) if self.pc >= CODE_BASE && self.pc.is_multiple_of(4) => {
+                    instruction.clone()
+                }
+                _ => return Err(EmulatorError::InvalidCodeAddress(self.pc)),
+            };
+            self.pc += 4;
+            self.execute(&instruction)?;
+        }
+
+        Ok(self.read(Reg::W(0)) as u32 as i32)
+    }
+
+    /// What the program has written to the standard output.
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    fn label_address(&self, label: &str) -> EmulatorResult<u64> {
+        self.labels
+            .get(label)
+            .map(|index| CODE_BASE + *index as u64 * 4)
+            .ok_or_else(|| EmulatorError::UndefinedLabel(label.to_string()))
+    }
+
+    fn read(&self, reg: Reg) -> u64 {
+        match reg {
+            Reg::W(n) => self.x[n as usize] & 0xffff_ffff,
+            Reg::X(n) => self.x[n as usize],
+            Reg::Wzr | Reg::Xzr => 0,
+            Reg::Sp => self.sp,
+            Reg::S(n) => self.v[n as usize] & 0xffff_ffff,
+            Reg::D(n) => self.v[n as usize],
+        }
+    }
+
+    /// Write the register, 32-bit writes clear the upper half.
+    fn write(&mut self, reg: Reg, value: u64) {
+        match reg {
+            Reg::W(n) => self.x[n as usize] = value & 0xffff_ffff,
+            Reg::X(n) => self.x[n as usize] = value,
+            Reg::Wzr | Reg::Xzr => {}
+            Reg::Sp => self.sp = value,
+            Reg::S(n) => self.v[n as usize] = value & 0xffff_ffff,
+            Reg::D(n) => self.v[n as usize] = value,
+        }
+    }
+
+    /// Read the register as a signed integer of its width.
+    fn read_signed(&self, reg: Reg) -> i64 {
+        let value = self.read(reg);
+        if reg.is_64bit() {
+            value as i64
+        } else {
+            value as u32 as i32 as i64
+        }
+    }
+
+    fn read_float(&self, reg: Reg) -> f64 {
+        match reg {
+            Reg::S(_) => f32::from_bits(self.read(reg) as u32) as f64,
+            _ => f64::from_bits(self.read(reg)),
+        }
+    }
+
+    fn write_float(&mut self, reg: Reg, value: f64) {
+        match reg {
+            Reg::S(_) => self.write(reg, (value as f32).to_bits() as u64),
+            _ => self.write(reg, value.to_bits()),
+        }
+    }
+
+    fn operand(&self, operand: Operand) -> u64 {
+        match operand {
+            Operand::Reg(reg) => self.read(reg),
+            Operand::Imm(imm) => imm as u64,
+        }
+    }
+
+    fn execute(&mut self, instruction: &Instruction) -> EmulatorResult<()> {
+        match instruction {
+            Instruction::Mov(dst, src) => self.write(*dst, self.operand(*src)),
+            Instruction::MovWide(dst, imm, shift, keep) => {
+                let imm = (*imm as u64) << shift;
+                let value = if *keep {
+                    (self.read(*dst) & !(0xffff << shift)) | imm
+                } else {
+                    imm
+                };
+                self.write(*dst, value);
+            }
+            Instruction::Alu(op, dst, lhs, rhs) => {
+                let value = alu(*op, dst.bits(), self.read(*lhs), self.operand(*rhs));
+                self.write(*dst, value);
+            }
+            Instruction::Msub(dst, lhs, rhs, acc) => {
+                let product = self.read(*lhs).wrapping_mul(self.read(*rhs));
+                self.write(*dst, self.read(*acc).wrapping_sub(product));
+            }
+            Instruction::Neg(dst, src) => self.write(*dst, self.read(*src).wrapping_neg()),
+            Instruction::Mvn(dst, src) => self.write(*dst, !self.read(*src)),
+            Instruction::Cmp(lhs, rhs) => {
+                self.flags = compare(lhs.bits(), self.read(*lhs), self.operand(*rhs));
+            }
+            Instruction::Cset(dst, cond) => self.write(*dst, self.flags.holds(*cond) as u64),
+            Instruction::Ldr(reg, address) => {
+                let addr = self.address(address)?;
+                let value = self.load(addr, reg.bits() / 8)?;
+                self.write(*reg, value);
+            }
+            Instruction::Str(reg, address) => {
+                let addr = self.address(address)?;
+                self.store(addr, reg.bits() / 8, self.read(*reg))?;
+            }
+            Instruction::Ldp(first, second, address) => {
+                let addr = self.address(address)?;
+                let size = first.bits() / 8;
+                let first_value = self.load(addr, size)?;
+                let second_value = self.load(addr + size as u64, size)?;
+                self.write(*first, first_value);
+                self.write(*second, second_value);
+            }
+            Instruction::Stp(first, second, address) => {
+                let addr = self.address(address)?;
+                let size = first.bits() / 8;
+                self.store(addr, size, self.read(*first))?;
+                self.store(addr + size as u64, size, self.read(*second))?;
+            }
+            Instruction::B(label) => self.pc = self.label_address(label)?,
+            Instruction::BCond(cond, label) => {
+                if self.flags.holds(*cond) {
+                    self.pc = self.label_address(label)?;
+                }
+            }
+            Instruction::Cbz(reg, label) => {
+                if self.read(*reg) == 0 {
+                    self.pc = self.label_address(label)?;
+                }
+            }
+            Instruction::Cbnz(reg, label) => {
+                if self.read(*reg) != 0 {
+                    self.pc = self.label_address(label)?;
+                }
+            }
+            Instruction::Bl(label) if !self.labels.contains_key(label) => {
+                self.call_builtin(label);
+            }
+            Instruction::Bl(label) => {
+                self.x[30] = self.pc;
+                self.pc = self.label_address(label)?;
+            }
+            Instruction::Ret => self.pc = self.x[30],
+            Instruction::Fmov(dst, src) => self.write(*dst, self.read(*src)),
+            Instruction::Fp(op, dst, lhs, rhs) => {
+                let (lhs, rhs) = (self.read_float(*lhs), self.read_float(*rhs));
+                // Single precision results are the same when they're computed
+                // in double precision and rounded.
+                let value = match op {
+                    FpOp::Add => lhs + rhs,
+                    FpOp::Sub => lhs - rhs,
+                    FpOp::Mul => lhs * rhs,
+                    FpOp::Div => lhs / rhs,
+                };
+                self.write_float(*dst, value);
+            }
+            Instruction::Fneg(dst, src) => {
+                let sign = 1 << (src.bits() - 1);
+                self.write(*dst, self.read(*src) ^ sign);
+            }
+            Instruction::Fcmp(lhs, rhs) => {
+                let lhs = self.read_float(*lhs);
+                let rhs = rhs.map_or(0.0, |rhs| self.read_float(rhs));
+                self.flags = match lhs.partial_cmp(&rhs) {
+                    Some(std::cmp::Ordering::Less) => Flags {
+                        n: true,
+                        ..Flags::default()
+                    },
+                    Some(std::cmp::Ordering::Equal) => Flags {
+                        z: true,
+                        c: true,
+                        ..Flags::default()
+                    },
+                    Some(std::cmp::Ordering::Greater) => Flags {
+                        c: true,
+                        ..Flags::default()
+                    },
+                    None => Flags {
+                        c: true,
+                        v: true,
+                        ..Flags::default()
+                    },
+                };
+            }
+            Instruction::Scvtf(dst, src) => {
+                let value = self.read_signed(*src);
+                match dst {
+                    // Convert directly to avoid rounding twice.
+                    Reg::S(_) => self.write(*dst, (value as f32).to_bits() as u64),
+                    _ => self.write(*dst, (value as f64).to_bits()),
+                }
+            }
+            Instruction::Fcvtzs(dst, src) => {
+                // Rust's float to int casts saturate and map NaN to 0 like `fcvtzs`.
+                let value = self.read_float(*src);
+                let value = if dst.is_64bit() {
+                    value as i64 as u64
+                } else {
+                    value as i32 as u32 as u64
+                };
+                self.write(*dst, value);
+            }
+            Instruction::Fcvt(dst, src) => self.write_float(*dst, self.read_float(*src)),
+        }
+        Ok(())
+    }
+
+    /// Run a library function like the real one would, then clobber the
+    /// registers that the callee doesn't have to preserve.
+    fn call_builtin(&mut self, label: &str) {
+        let result = match label {
+            "_putchar" => {
+                let c = self.read(Reg::W(0)) as u8;
+                self.stdout.push(c as char);
+                c as u64
+            }
+            _ => unreachable!("Builtins are checked when parsing"),
+        };
+
+        for reg in 1..=18 {
+            self.x[reg] = CLOBBERED;
+        }
+        for reg in (0..8).chain(16..32) {
+            self.v[reg] = CLOBBERED;
+        }
+        self.x[0] = result;
+        self.flags = Flags {
+            n: true,
+            ..Flags::default()
+        };
+    }
+
+    /// Compute the address of the access and apply the base register update.
+    fn address(&mut self, address: &Address) -> EmulatorResult<u64> {
+        let base = self.read(address.base);
+        if address.base == Reg::Sp && !base.is_multiple_of(16) {
+            return Err(EmulatorError::MisalignedStackPointer(base));
+        }
+
+        let updated = base.wrapping_add(address.offset as u64);
+        match address.mode {
+            AddressMode::Offset => Ok(updated),
+            AddressMode::PreIndex => {
+                self.write(address.base, updated);
+                Ok(updated)
+            }
+            AddressMode::PostIndex => {
+                self.write(address.base, updated);
+                Ok(base)
+            }
+        }
+    }
+
+    fn stack_range(&self, addr: u64, size: u32) -> EmulatorResult<std::ops::Range<usize>> {
+        let end = addr.wrapping_add(size as u64);
+        if addr < STACK_BASE || end > STACK_BASE + STACK_SIZE || end < addr {
+            return Err(EmulatorError::InvalidMemoryAccess(addr));
+        }
+        let start = (addr - STACK_BASE) as usize;
+        Ok(start..start + size as usize)
+    }
+
+    fn load(&self, addr: u64, size: u32) -> EmulatorResult<u64> {
+        let range = self.stack_range(addr, size)?;
+        let mut bytes = [0; 8];
+        bytes[..size as usize].copy_from_slice(&self.stack[range]);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn store(&mut self, addr: u64, size: u32, value: u64) -> EmulatorResult<()> {
+        let range = self.stack_range(addr, size)?;
+        self.stack[range].copy_from_slice(&value.to_le_bytes()[..size as usize]);
+        Ok(())
+    }
+}
+
+/// Compute an integer operation of the given width. The result isn't
+/// truncated, the register write does that.
+fn alu(op: AluOp, bits: u32, lhs: u64, rhs: u64) -> u64 {
+    let signed = |value: u64| {
+        if bits == 64 {
+            value as i64
+        } else {
+            value as u32 as i32 as i64
+        }
+    };
+    let shift = (rhs % bits as u64) as u32;
+    let mask = if bits == 64 { u64::MAX } else { 0xffff_ffff };
+
+    match op {
+        AluOp::Add => lhs.wrapping_add(rhs),
+        AluOp::Sub => lhs.wrapping_sub(rhs),
+        AluOp::Mul => lhs.wrapping_mul(rhs),
+        // Division by zero gives zero instead of trapping.
+        AluOp::Sdiv if signed(rhs) == 0 => 0,
+        AluOp::Sdiv if bits == 64 => (lhs as i64).wrapping_div(rhs as i64) as u64,
+        AluOp::Sdiv => (lhs as i32).wrapping_div(rhs as i32) as u64,
+        AluOp::And => lhs & rhs,
+        AluOp::Orr => lhs | rhs,
+        AluOp::Eor => lhs ^ rhs,
+        AluOp::Lsl => lhs << shift,
+        AluOp::Lsr => (lhs & mask) >> shift,
+        AluOp::Asr => (signed(lhs) >> shift) as u64,
+    }
+}
+
+/// Compute the flags of `lhs - rhs`.
+fn compare(bits: u32, lhs: u64, rhs: u64) -> Flags {
+    let mask = if bits == 64 { u64::MAX } else { 0xffff_ffff };
+    let (lhs, rhs) = (lhs & mask, rhs & mask);
+    let result = lhs.wrapping_sub(rhs) & mask;
+    let sign = 1 << (bits - 1);
+    Flags {
+        n: result & sign != 0,
+        z: result == 0,
+        // Carry is set when there's no borrow.
+        c: lhs >= rhs,
+        // Overflow if the operands have different signs and the result has
+        // the sign of the subtrahend.
+        v: ((lhs ^ rhs) & (lhs ^ result) & sign) != 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codegen::ARMCodegen, interpreter::Interpreter, parser::Parser, tokenizer::Tokenizer,
+    };
+
+    fn emulate(asm: &str) -> EmulatorResult<i32> {
+        Emulator::new(asm)?.with_step_limit(100_000).run()
+    }
+
+    /// Compile the program, run it in the emulator and check the result
+    /// against the interpreter.
+    fn compile_and_emulate(src: &str) -> EmulatorResult<i32> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        let mut interpreter = Interpreter::new(&program);
+        let expected = interpreter.run().unwrap();
+        let asm = ARMCodegen::new().generate(&program).unwrap();
+        let value = emulate(&asm)?;
+        assert_eq!(value, expected, "Emulator and interpreter disagree");
+        Ok(value)
+    }
+
+    macro_rules! emulator_test {
+        ($name:ident, $src:expr => $value:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(compile_and_emulate($src), Ok($value));
+            }
+        };
+    }
+
+    macro_rules! emulator_asm_test {
+        ($name:ident, $asm:expr => $result:pat) => {
+            #[test]
+            fn $name() {
+                let result = emulate(concat!("_main:\n", $asm, "\nret"));
+                assert!(matches!(result, $result), "{:?}", result);
+            }
+        };
+    }
+
+    emulator_test!(test_large_constants, "int main() { int a = 100000; int b = -100000; return a + b + 65536 / 4096; }" => 16);
+    emulator_test!(test_signed_shift_right, "int main() { int a = -16; return a >> 2; }" => -4);
+    emulator_test!(
+        test_continue_runs_increment,
+        "int main() {
+            int sum = 0;
+            for (int i = 0; i < 5; i = i + 1) { if (i == 2) continue; sum = sum + i; }
+            int j = 0;
+            do { j = j + 1; if (j < 3) continue; sum = sum + 10; } while (j < 4);
+            return sum;
+        }" => 28
+    );
+    emulator_test!(
+        test_call_in_condition,
+        "int one() { return 1; } int main() { if (one()) return 2; return 3; }" => 2
+    );
+
+    #[test]
+    fn test_putchar() {
+        let asm = "_main:\nstp x29, x30, [sp, #-16]!\nmov w0, #72\nbl _putchar\nmov w0, #105\nbl _putchar\nldp x29, x30, [sp], #16\nret";
+        let mut emulator = Emulator::new(asm).unwrap();
+        assert_eq!(emulator.run(), Ok(105));
+        assert_eq!(emulator.stdout(), "Hi");
+    }
+
+    emulator_test!(test_return_constant, "int main() { return 42; }" => 42);
+    emulator_test!(test_arithmetic, "int main() { int a = 7; int b = 3; return a * b - a / b + a % b; }" => 20);
+    emulator_test!(test_unary_ops, "int main() { int a = 5; return -a + ~a + !a; }" => -11);
+    emulator_test!(test_bitwise_ops, "int main() { return (12 & 10) | (1 << 4) ^ 3; }" => 27);
+    emulator_test!(test_comparisons, "int main() { return (1 < 2) + (2 <= 2) + (3 > 4) + (4 >= 5) + (5 == 5) + (5 != 5); }" => 3);
+    emulator_test!(test_short_circuit, "int main() { int a = 0; 0 && (a = 1); 1 || (a = 2); return a + (2 && 3) + (0 || 0); }" => 1);
+    emulator_test!(test_ternary, "int main() { int a = 3; return a > 2 ? a * 2 : a; }" => 6);
+    emulator_test!(
+        test_loops,
+        "int main() {
+            int sum = 0;
+            int i = 0;
+            while (i < 10) {
+                i = i + 1;
+                if (i == 3) continue;
+                if (i == 8) break;
+                sum = sum + i;
+            }
+            do { sum = sum + 100; } while (0);
+            for (int j = 0; j < 3; j = j + 1) sum = sum + j;
+            return sum;
+        }" => 128
+    );
+    emulator_test!(
+        test_recursion,
+        "int fib(int n) { return n < 2 ? n : fib(n - 1) + fib(n - 2); }
+         int main() { return fib(10); }" => 55
+    );
+    emulator_test!(
+        test_many_arguments,
+        "int f(int a, int b, int c, int d, int e, int f, int g, int h) { return a - b + c - d + e - f + g - h * 2; }
+         int main() { return f(1, 2, 3, 4, 5, 6, 7, 8); }" => -12
+    );
+    emulator_test!(
+        test_floating_point,
+        "double half(double x) { return x / 2; }
+         int main() { float f = 2.5f; double d = half(f * 3) + 0.25; return d * 4 + (f < d) + -f; }" => 14
+    );
+    emulator_test!(test_float_comparison_with_zero, "int main() { float f = 0.0f; double d = -1.5; return !f + (d ? 2 : 0); }" => 3);
+
+    emulator_asm_test!(test_negative_mov, "mov w0, #-5" => Ok(-5));
+    emulator_asm_test!(test_bitmask_mov, "mov w0, #0xff00ff00" => Ok(-16711936));
+    emulator_asm_test!(test_movz_movk, "movz w0, #0x5678\nmovk w0, #0x1234, lsl #16" => Ok(0x12345678));
+    emulator_asm_test!(test_sdiv_by_zero, "mov w1, #7\nsdiv w0, w1, wzr" => Ok(0));
+    emulator_asm_test!(test_sdiv_overflow, "mov w1, #-2147483648\nmov w2, #-1\nsdiv w0, w1, w2" => Ok(i32::MIN));
+    emulator_asm_test!(test_signed_compare, "mov w1, #-1\nmov w2, #1\ncmp w1, w2\ncset w0, lt" => Ok(1));
+    emulator_asm_test!(test_unsigned_compare, "mov w1, #-1\nmov w2, #1\ncmp w1, w2\ncset w0, hi" => Ok(1));
+    emulator_asm_test!(test_asr_and_lsr, "mov w1, #-16\nmov w2, #2\nasr w3, w1, w2\nlsr w4, w1, w2\nsub w0, w4, w3" => Ok(0x40000000));
+    emulator_asm_test!(test_fcmp_nan_is_unordered, "fmov s1, wzr\nfdiv s1, s1, s1\nfcmp s1, #0.0\ncset w0, vs" => Ok(1));
+    emulator_asm_test!(test_stack_round_trip, "sub sp, sp, #16\nmov w1, #9\nstr w1, [sp, #12]\nldr w0, [sp, #12]\nadd sp, sp, #16" => Ok(9));
+    emulator_asm_test!(test_unencodable_mov, "mov w0, #100000" => Err(EmulatorError::InvalidImmediate(2, 100000)));
+    emulator_asm_test!(test_unencodable_add, "add sp, sp, #4097" => Err(EmulatorError::InvalidImmediate(..)));
+    emulator_asm_test!(test_misaligned_offset, "ldr w0, [sp, #257]" => Err(EmulatorError::InvalidImmediate(..)));
+    emulator_asm_test!(test_misaligned_stack_pointer, "sub sp, sp, #8\nstr w0, [sp]" => Err(EmulatorError::MisalignedStackPointer(_)));
+    emulator_asm_test!(test_stack_underflow, "ldr w0, [sp, #16]" => Err(EmulatorError::InvalidMemoryAccess(_)));
+    emulator_asm_test!(test_undefined_label, "b L1" => Err(EmulatorError::UndefinedLabel(_)));
+    emulator_asm_test!(test_unknown_instruction, "udiv w0, w0, w0" => Err(EmulatorError::UnknownInstruction(..)));
+    emulator_asm_test!(test_mixed_register_widths, "add w0, x1, w2" => Err(EmulatorError::InvalidOperands(..)));
+    emulator_asm_test!(test_infinite_loop, "L1:\nb L1" => Err(EmulatorError::StepLimitExceeded(_)));
+}
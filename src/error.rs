@@ -0,0 +1,282 @@
+use thiserror::Error;
+
+use crate::{
+    cfg::CfgWarning,
+    codegen::CodegenError,
+    fold::FoldWarning,
+    parser::{ParserError, ParserWarning, Program},
+    semantic::{SemanticError, SemanticWarning},
+    tokenizer::{Span, TokenizerError},
+};
+
+#[derive(Error, Debug)]
+pub enum CompileError {
+    #[error("Tokenizing phase has failed: {0}")]
+    Tokenizer(TokenizerError, Span),
+    #[error("Parsing phase has failed: {0}")]
+    Parser(ParserError, Option<Span>),
+    #[error("Semantic analysis phase has failed: {0}")]
+    Semantic(#[from] SemanticError),
+    #[error("Codegen phase has failed: {0}")]
+    Codegen(#[from] CodegenError),
+    /// A warning with `-Werror`.
+    #[error("{} [-Werror={}]", .0, .0.kind().name())]
+    Warning(#[from] CompileWarning),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CompileWarning {
+    #[error(transparent)]
+    Parser(#[from] ParserWarning),
+    #[error(transparent)]
+    Semantic(#[from] SemanticWarning),
+    #[error(transparent)]
+    Fold(#[from] FoldWarning),
+    #[error(transparent)]
+    Cfg(#[from] CfgWarning),
+}
+
+impl From<(TokenizerError, Span)> for CompileError {
+    fn from((err, span): (TokenizerError, Span)) -> CompileError {
+        CompileError::Tokenizer(err, span)
+    }
+}
+
+impl From<(ParserError, Option<Span>)> for CompileError {
+    fn from((err, span): (ParserError, Option<Span>)) -> CompileError {
+        CompileError::Parser(err, span)
+    }
+}
+
+/// The kinds of warnings that can be turned on and off, e.g. with
+/// `-Wno-unused-variable`. They are named like the flags of GCC.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum WarningKind {
+    Overflow,
+    ImplicitFunctionDeclaration,
+    DivByZero,
+    ReturnType,
+    UnreachableCode,
+    Uninitialized,
+    UnusedVariable,
+    UnusedButSetVariable,
+}
+
+impl WarningKind {
+    pub const ALL: [WarningKind; 8] = [
+        WarningKind::Overflow,
+        WarningKind::ImplicitFunctionDeclaration,
+        WarningKind::DivByZero,
+        WarningKind::ReturnType,
+        WarningKind::UnreachableCode,
+        WarningKind::Uninitialized,
+        WarningKind::UnusedVariable,
+        WarningKind::UnusedButSetVariable,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WarningKind::Overflow => "overflow",
+            WarningKind::ImplicitFunctionDeclaration => "implicit-function-declaration",
+            WarningKind::DivByZero => "div-by-zero",
+            WarningKind::ReturnType => "return-type",
+            WarningKind::UnreachableCode => "unreachable-code",
+            WarningKind::Uninitialized => "uninitialized",
+            WarningKind::UnusedVariable => "unused-variable",
+            WarningKind::UnusedButSetVariable => "unused-but-set-variable",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<WarningKind> {
+        WarningKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+}
+
+impl CompileWarning {
+    pub fn kind(&self) -> WarningKind {
+        match self {
+            CompileWarning::Parser(ParserWarning::IntegerOverflow(..)) => WarningKind::Overflow,
+            CompileWarning::Semantic(SemanticWarning::ImplicitDeclaration(..)) => {
+                WarningKind::ImplicitFunctionDeclaration
+            }
+            CompileWarning::Fold(FoldWarning::DivisionByZero(..)) => WarningKind::DivByZero,
+            CompileWarning::Cfg(warning) => match warning {
+                CfgWarning::MissingReturn(..) => WarningKind::ReturnType,
+                CfgWarning::UnreachableCode(..) => WarningKind::UnreachableCode,
+                CfgWarning::Uninitialized(..) => WarningKind::Uninitialized,
+                CfgWarning::UnusedVariable(..) => WarningKind::UnusedVariable,
+                CfgWarning::UnusedButSetVariable(..) => WarningKind::UnusedButSetVariable,
+            },
+        }
+    }
+
+    /// Where the warning is in the source.
+    pub fn span(&self) -> Span {
+        match self {
+            CompileWarning::Parser(warning) => warning.span(),
+            CompileWarning::Semantic(warning) => warning.span(),
+            CompileWarning::Fold(warning) => warning.span(),
+            CompileWarning::Cfg(warning) => warning.span(),
+        }
+    }
+}
+
+impl CompileError {
+    /// Where the error is in the source. Only the internal errors of the
+    /// codegen don't have a span.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CompileError::Tokenizer(_, span) => Some(*span),
+            CompileError::Parser(_, span) => *span,
+            CompileError::Semantic(err) => Some(err.span()),
+            CompileError::Codegen(err) => err.span(),
+            CompileError::Warning(warning) => Some(warning.span()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// An error or a warning in the form that the tools can consume.
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The phase and the name of the error, e.g. `parse::UnexpectedToken`.
+    pub code: String,
+    pub message: String,
+    pub span: Option<Span>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    /// The code of an error is the phase and the name of its variant, e.g.
+    /// `parse::UnexpectedToken`.
+    pub fn code_for<E: std::fmt::Debug>(phase: &str, err: &E) -> String {
+        let debug = format!("{:?}", err);
+        let name: String = debug
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect();
+        format!("{}::{}", phase, name)
+    }
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(err: &CompileError) -> Diagnostic {
+        let (code, message, notes) = match err {
+            CompileError::Tokenizer(err, _) => (
+                Diagnostic::code_for("tokenize", err),
+                err.to_string(),
+                vec![],
+            ),
+            CompileError::Parser(err, _) => {
+                let notes = match err {
+                    ParserError::TooManyErrors(_) => {
+                        vec!["Use --ferror-limit=0 to report all of them".to_string()]
+                    }
+                    _ => vec![],
+                };
+                (Diagnostic::code_for("parse", err), err.to_string(), notes)
+            }
+            CompileError::Semantic(err) => {
+                let notes = match err {
+                    SemanticError::UndeclaredFunction(..) => {
+                        vec!["Use --implicit-decl to allow calls to undeclared functions"
+                            .to_string()]
+                    }
+                    _ => vec![],
+                };
+                (
+                    Diagnostic::code_for("semantic", err),
+                    err.to_string(),
+                    notes,
+                )
+            }
+            CompileError::Codegen(err) => (
+                Diagnostic::code_for("codegen", err),
+                err.to_string(),
+                vec![],
+            ),
+            CompileError::Warning(warning) => {
+                let diagnostic = Diagnostic::from(warning);
+                (diagnostic.code, err.to_string(), diagnostic.notes)
+            }
+        };
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message,
+            span: err.span(),
+            notes,
+        }
+    }
+}
+
+impl From<&CompileWarning> for Diagnostic {
+    fn from(warning: &CompileWarning) -> Diagnostic {
+        let (code, notes) = match warning {
+            CompileWarning::Parser(warning) => (Diagnostic::code_for("parse", warning), vec![]),
+            CompileWarning::Semantic(warning) => {
+                let notes = match warning {
+                    SemanticWarning::ImplicitDeclaration(..) => {
+                        vec!["The function is assumed to return int".to_string()]
+                    }
+                };
+                (Diagnostic::code_for("semantic", warning), notes)
+            }
+            CompileWarning::Fold(warning) => (Diagnostic::code_for("fold", warning), vec![]),
+            CompileWarning::Cfg(warning) => (Diagnostic::code_for("cfg", warning), vec![]),
+        };
+        Diagnostic {
+            severity: Severity::Warning,
+            code,
+            message: warning.to_string(),
+            span: Some(warning.span()),
+            notes,
+        }
+    }
+}
+
+/// Everything that was reported while compiling a program that failed.
+#[derive(Debug)]
+pub struct Diagnostics {
+    pub errors: Vec<CompileError>,
+    pub warnings: Vec<CompileWarning>,
+    /// The AST if the program could be tokenized. The statements that failed
+    /// to parse show up as `Error` nodes.
+    pub program: Option<Program>,
+}
+
+impl Diagnostics {
+    pub(crate) fn from_errors<E: Into<CompileError>>(errors: Vec<E>) -> Diagnostics {
+        Diagnostics {
+            errors: errors.into_iter().map(Into::into).collect(),
+            warnings: Vec::new(),
+            program: None,
+        }
+    }
+
+    pub(crate) fn with_program(mut self, program: Program) -> Diagnostics {
+        self.program = Some(program);
+        self
+    }
+
+    /// The warnings and then the errors.
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        let warnings = self.warnings.iter().map(Diagnostic::from);
+        warnings
+            .chain(self.errors.iter().map(Diagnostic::from))
+            .collect()
+    }
+
+    pub(crate) fn with_warnings(mut self, warnings: Vec<CompileWarning>) -> Diagnostics {
+        self.warnings = warnings;
+        self
+    }
+}
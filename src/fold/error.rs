@@ -0,0 +1,18 @@
+use thiserror::Error;
+
+use crate::tokenizer::Span;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FoldWarning {
+    /// The span is the one of the division.
+    #[error("Division by zero in function {0:?}")]
+    DivisionByZero(String, Span),
+}
+
+impl FoldWarning {
+    pub fn span(&self) -> Span {
+        match self {
+            FoldWarning::DivisionByZero(_, span) => *span,
+        }
+    }
+}
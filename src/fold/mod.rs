@@ -0,0 +1,442 @@
+mod error;
+
+pub use self::error::FoldWarning;
+use crate::{
+    interpreter::{evaluate_binary_op, evaluate_unary_op, InterpreterError, Value},
+    parser::ast::*,
+    tokenizer::Span,
+};
+use std::{collections::HashMap, mem};
+
+/// Evaluates the constant subexpressions and simplifies the identities like
+/// `x * 1` before the codegen. The constants are evaluated by the interpreter,
+/// so they follow the same rules as the generated code, e.g. signed integer
+/// overflow wraps around.
+pub struct ConstantFolder {
+    /// Return types of the functions, the undeclared ones return `int`.
+    return_types: HashMap<String, Type>,
+    /// Variable types of the current function, the innermost scope is at the end.
+    scopes: Vec<HashMap<String, Type>>,
+    function: String,
+    warnings: Vec<FoldWarning>,
+}
+
+impl ConstantFolder {
+    pub fn new() -> ConstantFolder {
+        ConstantFolder {
+            return_types: HashMap::new(),
+            scopes: vec![],
+            function: String::new(),
+            warnings: vec![],
+        }
+    }
+
+    /// Fold the program in place and return the warnings.
+    pub fn fold(mut self, program: &mut Program) -> Vec<FoldWarning> {
+        for func in &program.functions {
+            self.return_types.insert(func.name.clone(), func.return_ty);
+        }
+
+        for func in &mut program.functions {
+            let Some(body) = &mut func.body else {
+                continue;
+            };
+            self.function = func.name.clone();
+            let params = func
+                .params
+                .iter()
+                .filter_map(|param| Some((param.name.clone()?, param.ty)));
+            self.scopes = vec![params.collect()];
+            self.fold_block(body);
+        }
+        self.warnings
+    }
+
+    fn fold_block(&mut self, block: &mut Block) {
+        self.scopes.push(HashMap::new());
+        for item in &mut block.items {
+            match item {
+                BlockItem::Statement(stmt, _) => self.fold_statement(stmt),
+                BlockItem::Declaration(decl) => self.fold_declaration(decl),
+            }
+        }
+        self.scopes.pop();
+    }
+
+    fn fold_declaration(&mut self, decl: &mut VarDecl) {
+        if let Some(initializer) = &mut decl.initializer {
+            self.fold_expr(initializer);
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(decl.name.clone(), decl.ty);
+        }
+    }
+
+    fn fold_statement(&mut self, stmt: &mut Statement) {
+        let folded = match stmt {
+            Statement::Block(block) => {
+                self.fold_block(block);
+                None
+            }
+            Statement::Return(expr) | Statement::Expression(expr) => {
+                self.fold_expr(expr);
+                None
+            }
+            Statement::Conditional(conditional) => {
+                self.fold_expr(&mut conditional.condition);
+                self.fold_statement(&mut conditional.if_stmt);
+                if let Some(else_stmt) = &mut conditional.else_stmt {
+                    self.fold_statement(else_stmt);
+                }
+
+                // Only the branch that is taken is left.
+                constant(&conditional.condition).map(|condition| {
+                    if condition.is_true() {
+                        take_statement(&mut conditional.if_stmt)
+                    } else {
+                        conditional
+                            .else_stmt
+                            .as_mut()
+                            .map_or(Statement::Null, take_statement)
+                    }
+                })
+            }
+            Statement::While(condition, body) | Statement::DoWhile(body, condition) => {
+                self.fold_expr(condition);
+                self.fold_statement(body);
+                None
+            }
+            Statement::For(for_loop) => {
+                self.scopes.push(HashMap::new());
+                match for_loop.init.as_mut() {
+                    DeclOrExpr::Declaration(decl) => self.fold_declaration(decl),
+                    DeclOrExpr::Expression(expr) => self.fold_expr(expr),
+                }
+                self.fold_expr(&mut for_loop.condition);
+                self.fold_expr(&mut for_loop.increment);
+                self.fold_statement(&mut for_loop.body);
+                self.scopes.pop();
+                None
+            }
+            Statement::Break(_) | Statement::Continue(_) | Statement::Null | Statement::Error => {
+                None
+            }
+        };
+
+        if let Some(folded) = folded {
+            *stmt = folded;
+        }
+    }
+
+    fn fold_expr(&mut self, expr: &mut Expr) {
+        let folded = match expr {
+            Expr::Assignment(_, value, _) => {
+                self.fold_expr(value);
+                None
+            }
+            Expr::UnaryOp(op, operand, _) => {
+                self.fold_expr(operand);
+                constant(operand)
+                    .and_then(|value| evaluate_unary_op(*op, value).ok())
+                    .map(constant_expr)
+            }
+            Expr::BinaryOp(op, lhs, rhs, span) => {
+                self.fold_expr(lhs);
+                self.fold_expr(rhs);
+                self.fold_binary_op(*op, lhs, rhs, *span)
+            }
+            Expr::TernaryConditional(ternary) => {
+                self.fold_expr(&mut ternary.condition);
+                self.fold_expr(&mut ternary.if_expr);
+                self.fold_expr(&mut ternary.else_expr);
+
+                // The result has the common type of the branches, so they can
+                // only be replaced with one of them if they have the same type.
+                let same_type =
+                    self.static_type(&ternary.if_expr) == self.static_type(&ternary.else_expr);
+                match constant(&ternary.condition) {
+                    Some(condition) if same_type => Some(if condition.is_true() {
+                        take_expr(&mut ternary.if_expr)
+                    } else {
+                        take_expr(&mut ternary.else_expr)
+                    }),
+                    _ => None,
+                }
+            }
+            Expr::FunctionCall(_, args, _) => {
+                for arg in args {
+                    self.fold_expr(arg);
+                }
+                None
+            }
+            Expr::Var(..) | Expr::Constant(_) | Expr::Null => None,
+        };
+
+        if let Some(folded) = folded {
+            *expr = folded;
+        }
+    }
+
+    fn fold_binary_op(
+        &mut self,
+        op: BinaryOp,
+        lhs: &mut Expr,
+        rhs: &mut Expr,
+        span: Span,
+    ) -> Option<Expr> {
+        match (constant(lhs), constant(rhs)) {
+            // The right hand side isn't evaluated if the left hand side decides.
+            (Some(lhs), _) if op == BinaryOp::And && !lhs.is_true() => {
+                Some(Expr::Constant(Constant::Int(0)))
+            }
+            (Some(lhs), _) if op == BinaryOp::Or && lhs.is_true() => {
+                Some(Expr::Constant(Constant::Int(1)))
+            }
+            (Some(_), Some(rhs)) if op.is_short_circuiting_op() => {
+                Some(Expr::Constant(Constant::Int(rhs.is_true() as i32)))
+            }
+            (_, _) if op.is_short_circuiting_op() => None,
+            (Some(lhs), Some(rhs)) => match evaluate_binary_op(op, lhs, rhs) {
+                Ok(value) => Some(constant_expr(value)),
+                Err(InterpreterError::DivisionByZero) => {
+                    // It's left to fail at runtime.
+                    self.warnings
+                        .push(FoldWarning::DivisionByZero(self.function.clone(), span));
+                    None
+                }
+                // The codegen reports the invalid operands.
+                Err(_) => None,
+            },
+            (Some(Value::Int(value)), None) => self.simplify(op, value, rhs, true),
+            (None, Some(Value::Int(value))) => self.simplify(op, value, lhs, false),
+            _ => None,
+        }
+    }
+
+    /// Simplify the identities of an integer operation with a constant, e.g.
+    /// `x + 0`. The constant is on the left hand side if `constant_lhs` is set.
+    fn simplify(
+        &self,
+        op: BinaryOp,
+        constant: i32,
+        other: &mut Expr,
+        constant_lhs: bool,
+    ) -> Option<Expr> {
+        // `x + 0` isn't `x` if `x` is a float, e.g. `-0.0 + 0` is `0.0`.
+        if self.static_type(other) != Type::Int {
+            return None;
+        }
+
+        let is_identity = match (op, constant) {
+            (BinaryOp::Addition | BinaryOp::BitwiseOr | BinaryOp::BitwiseXor, 0) => true,
+            (BinaryOp::Multiplication, 1) => true,
+            (BinaryOp::BitwiseAnd, -1) => true,
+            (
+                BinaryOp::Subtraction | BinaryOp::BitwiseShiftLeft | BinaryOp::BitwiseShiftRight,
+                0,
+            )
+            | (BinaryOp::Division, 1) => !constant_lhs,
+            _ => false,
+        };
+        if is_identity {
+            return Some(take_expr(other));
+        }
+
+        // `x * 0` is `0` only if `x` doesn't have any side effects.
+        let is_zero = matches!(
+            (op, constant),
+            (BinaryOp::Multiplication | BinaryOp::BitwiseAnd, 0)
+        );
+        if is_zero && is_pure(other) {
+            return Some(Expr::Constant(Constant::Int(0)));
+        }
+        None
+    }
+
+    /// Type of the expression like the interpreter finds it.
+    fn static_type(&self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Constant(Constant::Float(_)) => Type::Float,
+            Expr::Constant(Constant::Double(_)) => Type::Double,
+            Expr::Constant(_) | Expr::Null => Type::Int,
+            Expr::Var(name, _) | Expr::Assignment(name, _, _) => self
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(name).copied())
+                .unwrap_or(Type::Int),
+            Expr::UnaryOp(UnaryOp::Negation, expr, _) => self.static_type(expr),
+            Expr::UnaryOp(..) => Type::Int,
+            Expr::BinaryOp(op, lhs, rhs, _) if op.is_arithmetic_op() => {
+                self.static_type(lhs).common(self.static_type(rhs))
+            }
+            Expr::BinaryOp(..) => Type::Int,
+            Expr::TernaryConditional(ternary) => self
+                .static_type(&ternary.if_expr)
+                .common(self.static_type(&ternary.else_expr)),
+            Expr::FunctionCall(name, _, _) => {
+                self.return_types.get(name).copied().unwrap_or(Type::Int)
+            }
+        }
+    }
+}
+
+impl Default for ConstantFolder {
+    fn default() -> ConstantFolder {
+        ConstantFolder::new()
+    }
+}
+
+/// The value of an expression if it's a constant.
+fn constant(expr: &Expr) -> Option<Value> {
+    match expr {
+        Expr::Constant(Constant::Int(v)) => Some(Value::Int(*v)),
+        Expr::Constant(Constant::Float(v)) => Some(Value::Float(*v)),
+        Expr::Constant(Constant::Double(v)) => Some(Value::Double(*v)),
+        Expr::Constant(Constant::Bool(v)) => Some(Value::Int(*v as i32)),
+        _ => None,
+    }
+}
+
+/// The value of an expression that only has constants, e.g. `1 == 1`.
+pub(crate) fn evaluate(expr: &Expr) -> Option<Value> {
+    match expr {
+        Expr::UnaryOp(op, operand, _) => evaluate_unary_op(*op, evaluate(operand)?).ok(),
+        Expr::BinaryOp(op, lhs, rhs, _) if op.is_short_circuiting_op() => {
+            let lhs = evaluate(lhs)?;
+            // The right hand side isn't evaluated if the left hand side decides.
+            let value = match op {
+                BinaryOp::And if !lhs.is_true() => false,
+                BinaryOp::Or if lhs.is_true() => true,
+                _ => evaluate(rhs)?.is_true(),
+            };
+            Some(Value::Int(value as i32))
+        }
+        Expr::BinaryOp(op, lhs, rhs, _) => {
+            evaluate_binary_op(*op, evaluate(lhs)?, evaluate(rhs)?).ok()
+        }
+        expr => constant(expr),
+    }
+}
+
+fn constant_expr(value: Value) -> Expr {
+    Expr::Constant(match value {
+        Value::Int(v) => Constant::Int(v),
+        Value::Float(v) => Constant::Float(v),
+        Value::Double(v) => Constant::Double(v),
+    })
+}
+
+/// Whether the expression can be removed without changing what the program does.
+fn is_pure(expr: &Expr) -> bool {
+    match expr {
+        Expr::Var(..) | Expr::Constant(_) | Expr::Null => true,
+        Expr::Assignment(..) | Expr::FunctionCall(..) => false,
+        Expr::UnaryOp(_, expr, _) => is_pure(expr),
+        // Division by zero traps on some targets, it's not removed either.
+        Expr::BinaryOp(BinaryOp::Division | BinaryOp::Modulo, ..) => false,
+        Expr::BinaryOp(_, lhs, rhs, _) => is_pure(lhs) && is_pure(rhs),
+        Expr::TernaryConditional(ternary) => {
+            is_pure(&ternary.condition) && is_pure(&ternary.if_expr) && is_pure(&ternary.else_expr)
+        }
+    }
+}
+
+fn take_expr(expr: &mut Expr) -> Expr {
+    mem::replace(expr, Expr::Null)
+}
+
+fn take_statement(stmt: &mut Box<Statement>) -> Statement {
+    mem::replace(stmt.as_mut(), Statement::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::Parser, tokenizer::Tokenizer};
+
+    /// Fold the program and return the statements of `main` and the warnings.
+    fn fold(src: &str) -> (Vec<BlockItem>, Vec<FoldWarning>) {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let mut program = Parser::new(token_stream).parse().unwrap();
+        let warnings = ConstantFolder::new().fold(&mut program);
+        let main = program.functions.pop().unwrap();
+        (main.body.unwrap().items, warnings)
+    }
+
+    /// Parse the expected statements of `main`.
+    fn parse(src: &str) -> Vec<BlockItem> {
+        fold(src).0
+    }
+
+    /// The statements without their spans, the expected ones are parsed
+    /// from a different source.
+    fn without_spans(items: &[BlockItem]) -> String {
+        let debug = format!("{:?}", items);
+        let mut result = String::new();
+        let mut rest = debug.as_str();
+        while let Some(start) = rest.find("Span {") {
+            result.push_str(&rest[..start]);
+            let end = start + rest[start..].find('}').unwrap() + 1;
+            rest = &rest[end..];
+        }
+        result.push_str(rest);
+        result
+    }
+
+    macro_rules! fold_test {
+        ($name:ident, $src:expr => $expected:expr) => {
+            #[test]
+            fn $name() {
+                let (items, warnings) = fold($src);
+                assert_eq!(without_spans(&items), without_spans(&parse($expected)));
+                assert!(warnings.is_empty());
+            }
+        };
+    }
+
+    fold_test!(test_fold_arithmetic,
+        "int main() { return 2 * 3 + 4; }" => "int main() { return 10; }");
+    fold_test!(test_fold_wrapping,
+        "int main() { return 2147483647 + 1; }" => "int main() { return -2147483647 - 1; }");
+    fold_test!(test_fold_mixed_types,
+        "int main() { return 1 / 2.0f + 0.25; }" => "int main() { return 0.75; }");
+    fold_test!(test_fold_unary,
+        "int main() { return -(~0) + !5; }" => "int main() { return 1; }");
+    fold_test!(test_fold_short_circuit,
+        "int main() { int a; return 0 && (a = 1) || 2; }" => "int main() { int a; return 1; }");
+    fold_test!(test_simplify_identities,
+        "int main() { int a = 3; return (a * 1 + 0) | 0; }" => "int main() { int a = 3; return a; }");
+    fold_test!(test_simplify_zero,
+        "int main() { int a = 3; return a & 0; }" => "int main() { int a = 3; return 0; }");
+    fold_test!(test_simplify_keeps_side_effects,
+        "int main() { int a = 3; return (a = 4) * 0; }" => "int main() { int a = 3; return (a = 4) * 0; }");
+    fold_test!(test_simplify_keeps_floats,
+        "int main() { float a = -0.0f; return a + 0; }" => "int main() { float a = -0.0f; return a + 0; }");
+    fold_test!(test_simplify_not_commutative,
+        "int main() { int a = 3; return 0 - a; }" => "int main() { int a = 3; return 0 - a; }");
+    fold_test!(test_fold_if,
+        "int main() { if (1 + 1 == 2) return 1; else return 2; }" => "int main() { return 1; }");
+    fold_test!(test_fold_if_without_else,
+        "int main() { if (0) return 1; return 2; }" => "int main() { ; return 2; }");
+    fold_test!(test_fold_ternary,
+        "int main() { int a = 1; return 0 ? a : 3; }" => "int main() { int a = 1; return 3; }");
+    fold_test!(test_fold_ternary_keeps_common_type,
+        "int main() { return (1 ? 5 : 2.0) / 2; }" => "int main() { return (1 ? 5 : 2.0) / 2; }");
+
+    #[test]
+    fn test_division_by_zero() {
+        let (items, warnings) = fold("int main() { return 1 / (2 - 2); }");
+        assert_eq!(
+            without_spans(&items),
+            without_spans(&parse("int main() { return 1 / 0; }"))
+        );
+        assert_eq!(
+            warnings,
+            vec![FoldWarning::DivisionByZero(
+                "main".into(),
+                Span { lo: 20, hi: 31 }
+            )]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use thiserror::Error;
+
+use crate::parser::ast::{BinaryOp, Constant, Type, UnaryOp};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InterpreterError {
+    #[error("No main function found")]
+    NoMainFunction,
+    #[error("Function {0:?} is declared but not defined")]
+    UndefinedFunction(String),
+    #[error("Function {name:?} expects {expected} arguments, but {got} were given")]
+    ArgumentCountMismatch {
+        name: String,
+        expected: usize,
+        got: usize,
+    },
+
+    #[error("Variable {0:?} not found")]
+    VarNotFound(String),
+    #[error("Constant {0} isn't supported")]
+    UnsupportedConstant(Constant),
+
+    #[error("Division by zero")]
+    DivisionByZero,
+    #[error("Invalid operand of type {1:?} for unary operator {0:?}")]
+    InvalidUnaryOperand(UnaryOp, Type),
+    #[error("Invalid operands of type {1:?} for binary operator {0:?}")]
+    InvalidBinaryOperands(BinaryOp, Type),
+
+    #[error("'break' statement is not in a loop or switch statement")]
+    NoLoopFoundForBreak,
+    #[error("'continue' statement is not in a loop or switch statement")]
+    NoLoopFoundForContinue,
+    #[error("Can't execute a statement that failed to parse")]
+    UnexpectedErrorNode,
+
+    #[error("Call stack is deeper than {0} frames")]
+    StackOverflow(usize),
+    #[error("Program didn't finish in {0} steps")]
+    StepLimitExceeded(u64),
+}
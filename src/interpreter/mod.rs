@@ -0,0 +1,622 @@
+mod error;
+
+pub use self::error::InterpreterError;
+use crate::parser::ast::*;
+use std::collections::HashMap;
+
+type InterpreterResult<T> = Result<T, InterpreterError>;
+
+/// Maximum number of nested function calls before giving up.
+pub const MAX_CALL_DEPTH: usize = 1000;
+/// Stack size of the thread that runs the interpreter. Every interpreted call
+/// takes a few kilobytes of native stack, so the default size isn't enough
+/// for [`MAX_CALL_DEPTH`] calls.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+/// Default number of statements and expressions to evaluate before giving up,
+/// so that programs with infinite loops don't hang the tests.
+pub const DEFAULT_STEP_LIMIT: u64 = 100_000_000;
+
+/// Runtime value of an expression.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Value {
+    Int(i32),
+    Float(f32),
+    Double(f64),
+}
+
+impl Value {
+    pub fn ty(self) -> Type {
+        match self {
+            Value::Int(_) => Type::Int,
+            Value::Float(_) => Type::Float,
+            Value::Double(_) => Type::Double,
+        }
+    }
+
+    fn zero(ty: Type) -> Value {
+        match ty {
+            Type::Int => Value::Int(0),
+            Type::Float => Value::Float(0.0),
+            Type::Double => Value::Double(0.0),
+        }
+    }
+
+    fn from_bool(value: bool) -> Value {
+        Value::Int(value as i32)
+    }
+
+    /// Convert the value like an implicit conversion in C. Floating values are
+    /// truncated towards zero and saturated when converted to integers, which
+    /// matches what `fcvtzs` does.
+    pub fn convert(self, ty: Type) -> Value {
+        match (self, ty) {
+            (Value::Int(v), Type::Int) => Value::Int(v),
+            (Value::Int(v), Type::Float) => Value::Float(v as f32),
+            (Value::Int(v), Type::Double) => Value::Double(v as f64),
+            (Value::Float(v), Type::Int) => Value::Int(v as i32),
+            (Value::Float(v), Type::Float) => Value::Float(v),
+            (Value::Float(v), Type::Double) => Value::Double(v as f64),
+            (Value::Double(v), Type::Int) => Value::Int(v as i32),
+            (Value::Double(v), Type::Float) => Value::Float(v as f32),
+            (Value::Double(v), Type::Double) => Value::Double(v),
+        }
+    }
+
+    fn to_double(self) -> f64 {
+        match self.convert(Type::Double) {
+            Value::Double(v) => v,
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn is_true(self) -> bool {
+        match self {
+            Value::Int(v) => v != 0,
+            Value::Float(v) => v != 0.0,
+            Value::Double(v) => v != 0.0,
+        }
+    }
+}
+
+/// What the execution of a statement asks its enclosing statements to do.
+enum ControlFlow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+/// Reference execution engine that runs the AST directly. It's used to check
+/// the generated code against, so it follows what the codegen does where C
+/// leaves the behavior undefined, e.g. signed integer overflow wraps around.
+pub struct Interpreter<'a> {
+    functions: HashMap<&'a str, &'a Function>,
+    /// Variable scopes of each function call, the innermost ones are at the end.
+    frames: Vec<Vec<HashMap<&'a str, Value>>>,
+    steps: u64,
+    step_limit: u64,
+    /// What the program wrote with `putchar`.
+    stdout: String,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(program: &'a Program) -> Interpreter<'a> {
+        let functions = program
+            .functions
+            .iter()
+            .filter(|func| func.is_definition())
+            .map(|func| (func.name.as_str(), func))
+            .collect();
+
+        Interpreter {
+            functions,
+            frames: vec![],
+            steps: 0,
+            step_limit: DEFAULT_STEP_LIMIT,
+            stdout: String::new(),
+        }
+    }
+
+    /// Set the maximum number of steps, 0 means no limit.
+    pub fn with_step_limit(mut self, step_limit: u64) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    /// Run the `main` function and return its value, which is the exit value
+    /// of the program.
+    pub fn run(&mut self) -> InterpreterResult<i32> {
+        std::thread::scope(|scope| {
+            std::thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn_scoped(scope, || self.run_main())
+                .expect("Couldn't spawn the interpreter thread")
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    }
+
+    /// What the program has written to the standard output.
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    fn run_main(&mut self) -> InterpreterResult<i32> {
+        if !self.functions.contains_key("main") {
+            return Err(InterpreterError::NoMainFunction);
+        }
+        match self.call("main", vec![])?.convert(Type::Int) {
+            Value::Int(value) => Ok(value),
+            _ => unreachable!(),
+        }
+    }
+
+    fn step(&mut self) -> InterpreterResult<()> {
+        self.steps += 1;
+        if self.step_limit != 0 && self.steps > self.step_limit {
+            return Err(InterpreterError::StepLimitExceeded(self.step_limit));
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, name: &str, args: Vec<Value>) -> InterpreterResult<Value> {
+        let Some(func) = self.functions.get(name).copied() else {
+            return self.call_builtin(name, args);
+        };
+        if func.params.len() != args.len() {
+            return Err(InterpreterError::ArgumentCountMismatch {
+                name: name.to_string(),
+                expected: func.params.len(),
+                got: args.len(),
+            });
+        }
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err(InterpreterError::StackOverflow(MAX_CALL_DEPTH));
+        }
+
+        let mut params = HashMap::new();
+        for (param, arg) in func.params.iter().zip(args) {
+            // Definitions always have named parameters after the semantic analysis.
+            if let Some(param_name) = &param.name {
+                params.insert(param_name.as_str(), arg.convert(param.ty));
+            }
+        }
+
+        self.frames.push(vec![params]);
+        let result = self.execute_block(func.body.as_ref().unwrap());
+        self.frames.pop();
+
+        match result? {
+            ControlFlow::Return(value) => Ok(value.convert(func.return_ty)),
+            ControlFlow::Break => Err(InterpreterError::NoLoopFoundForBreak),
+            ControlFlow::Continue => Err(InterpreterError::NoLoopFoundForContinue),
+            // Reaching the end of main returns 0. Using the value of other
+            // functions is undefined, the codegen leaves w0 as it is.
+            ControlFlow::Normal => Ok(Value::zero(func.return_ty)),
+        }
+    }
+
+    /// Run the library functions that the programs can declare and call.
+    fn call_builtin(&mut self, name: &str, args: Vec<Value>) -> InterpreterResult<Value> {
+        match (name, args.as_slice()) {
+            ("putchar", [c]) => {
+                let Value::Int(c) = c.convert(Type::Int) else {
+                    unreachable!()
+                };
+                self.stdout.push(c as u8 as char);
+                Ok(Value::Int(c as u8 as i32))
+            }
+            _ => Err(InterpreterError::UndefinedFunction(name.to_string())),
+        }
+    }
+
+    fn scopes(&mut self) -> &mut Vec<HashMap<&'a str, Value>> {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn lookup_var(&mut self, name: &str) -> InterpreterResult<&mut Value> {
+        self.scopes()
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+            .ok_or_else(|| InterpreterError::VarNotFound(name.to_string()))
+    }
+
+    fn execute_block(&mut self, block: &'a Block) -> InterpreterResult<ControlFlow> {
+        self.scopes().push(HashMap::new());
+        let result = self.execute_block_items(block);
+        self.scopes().pop();
+        result
+    }
+
+    fn execute_block_items(&mut self, block: &'a Block) -> InterpreterResult<ControlFlow> {
+        for item in &block.items {
+            let flow = match item {
+                BlockItem::Statement(stmt, _) => self.execute_statement(stmt)?,
+                BlockItem::Declaration(decl) => {
+                    self.execute_declaration(decl)?;
+                    ControlFlow::Normal
+                }
+            };
+            if !matches!(flow, ControlFlow::Normal) {
+                return Ok(flow);
+            }
+        }
+        Ok(ControlFlow::Normal)
+    }
+
+    fn execute_declaration(&mut self, decl: &'a VarDecl) -> InterpreterResult<()> {
+        // Uninitialized variables are zeroed like the codegen does.
+        let value = match &decl.initializer {
+            Some(initializer) => self.evaluate(initializer)?.convert(decl.ty),
+            None => Value::zero(decl.ty),
+        };
+        self.scopes()
+            .last_mut()
+            .unwrap()
+            .insert(decl.name.as_str(), value);
+        Ok(())
+    }
+
+    fn execute_statement(&mut self, stmt: &'a Statement) -> InterpreterResult<ControlFlow> {
+        self.step()?;
+        match stmt {
+            Statement::Block(block) => self.execute_block(block),
+            Statement::Return(expr) => Ok(ControlFlow::Return(self.evaluate(expr)?)),
+            Statement::Expression(expr) => {
+                self.evaluate(expr)?;
+                Ok(ControlFlow::Normal)
+            }
+            Statement::Conditional(cond) => {
+                if self.evaluate(&cond.condition)?.is_true() {
+                    self.execute_statement(&cond.if_stmt)
+                } else if let Some(else_stmt) = &cond.else_stmt {
+                    self.execute_statement(else_stmt)
+                } else {
+                    Ok(ControlFlow::Normal)
+                }
+            }
+            Statement::While(condition, body) => {
+                while self.evaluate(condition)?.is_true() {
+                    match self.execute_statement(body)? {
+                        ControlFlow::Break => break,
+                        flow @ ControlFlow::Return(_) => return Ok(flow),
+                        ControlFlow::Normal | ControlFlow::Continue => {}
+                    }
+                }
+                Ok(ControlFlow::Normal)
+            }
+            Statement::DoWhile(body, condition) => {
+                loop {
+                    match self.execute_statement(body)? {
+                        ControlFlow::Break => break,
+                        flow @ ControlFlow::Return(_) => return Ok(flow),
+                        ControlFlow::Normal | ControlFlow::Continue => {}
+                    }
+                    if !self.evaluate(condition)?.is_true() {
+                        break;
+                    }
+                }
+                Ok(ControlFlow::Normal)
+            }
+            Statement::For(for_loop) => {
+                self.scopes().push(HashMap::new());
+                let result = self.execute_for(for_loop);
+                self.scopes().pop();
+                result
+            }
+            Statement::Break(_) => Ok(ControlFlow::Break),
+            Statement::Continue(_) => Ok(ControlFlow::Continue),
+            Statement::Null => Ok(ControlFlow::Normal),
+            Statement::Error => Err(InterpreterError::UnexpectedErrorNode),
+        }
+    }
+
+    fn execute_for(&mut self, for_loop: &'a For) -> InterpreterResult<ControlFlow> {
+        match for_loop.init.as_ref() {
+            DeclOrExpr::Declaration(decl) => self.execute_declaration(decl)?,
+            DeclOrExpr::Expression(expr) => {
+                self.evaluate(expr)?;
+            }
+        }
+
+        loop {
+            // A missing condition is always true.
+            let condition = match for_loop.condition.as_ref() {
+                Expr::Null => true,
+                condition => self.evaluate(condition)?.is_true(),
+            };
+            if !condition {
+                break;
+            }
+
+            match self.execute_statement(&for_loop.body)? {
+                ControlFlow::Break => break,
+                flow @ ControlFlow::Return(_) => return Ok(flow),
+                ControlFlow::Normal | ControlFlow::Continue => {}
+            }
+            self.evaluate(&for_loop.increment)?;
+        }
+        Ok(ControlFlow::Normal)
+    }
+
+    fn evaluate(&mut self, expr: &'a Expr) -> InterpreterResult<Value> {
+        self.step()?;
+        match expr {
+            Expr::Assignment(name, expr, _) => {
+                let value = self.evaluate(expr)?;
+                let var = self.lookup_var(name)?;
+                *var = value.convert(var.ty());
+                Ok(*var)
+            }
+            Expr::Var(name, _) => Ok(*self.lookup_var(name)?),
+            Expr::Constant(constant) => match constant {
+                Constant::Int(v) => Ok(Value::Int(*v)),
+                Constant::Float(v) => Ok(Value::Float(*v)),
+                Constant::Double(v) => Ok(Value::Double(*v)),
+                Constant::Bool(v) => Ok(Value::from_bool(*v)),
+                Constant::String(_) => Err(InterpreterError::UnsupportedConstant(constant.clone())),
+            },
+            Expr::UnaryOp(op, expr, _) => {
+                let value = self.evaluate(expr)?;
+                evaluate_unary_op(*op, value)
+            }
+            Expr::BinaryOp(op, lhs, rhs, _) if op.is_short_circuiting_op() => {
+                let lhs = self.evaluate(lhs)?.is_true();
+                let result = match op {
+                    BinaryOp::And => lhs && self.evaluate(rhs)?.is_true(),
+                    BinaryOp::Or => lhs || self.evaluate(rhs)?.is_true(),
+                    _ => unreachable!(),
+                };
+                Ok(Value::from_bool(result))
+            }
+            Expr::BinaryOp(op, lhs, rhs, _) => {
+                let lhs = self.evaluate(lhs)?;
+                let rhs = self.evaluate(rhs)?;
+                evaluate_binary_op(*op, lhs, rhs)
+            }
+            Expr::TernaryConditional(ternary) => {
+                let branch = if self.evaluate(&ternary.condition)?.is_true() {
+                    &ternary.if_expr
+                } else {
+                    &ternary.else_expr
+                };
+                let value = self.evaluate(branch)?;
+                // Both of the branches have the common type.
+                let ty = self
+                    .static_type(&ternary.if_expr)?
+                    .common(self.static_type(&ternary.else_expr)?);
+                Ok(value.convert(ty))
+            }
+            Expr::FunctionCall(name, args, _) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.evaluate(arg))
+                    .collect::<InterpreterResult<Vec<_>>>()?;
+                self.call(name, args)
+            }
+            Expr::Null => Ok(Value::Int(0)),
+        }
+    }
+
+    /// Type of the expression without evaluating it.
+    fn static_type(&mut self, expr: &'a Expr) -> InterpreterResult<Type> {
+        match expr {
+            Expr::Constant(Constant::Float(_)) => Ok(Type::Float),
+            Expr::Constant(Constant::Double(_)) => Ok(Type::Double),
+            Expr::Constant(_) | Expr::Null => Ok(Type::Int),
+            Expr::Var(name, _) | Expr::Assignment(name, _, _) => Ok(self.lookup_var(name)?.ty()),
+            Expr::UnaryOp(UnaryOp::Negation, expr, _) => self.static_type(expr),
+            Expr::UnaryOp(..) => Ok(Type::Int),
+            Expr::BinaryOp(op, lhs, rhs, _) if op.is_arithmetic_op() => {
+                Ok(self.static_type(lhs)?.common(self.static_type(rhs)?))
+            }
+            Expr::BinaryOp(..) => Ok(Type::Int),
+            Expr::TernaryConditional(ternary) => Ok(self
+                .static_type(&ternary.if_expr)?
+                .common(self.static_type(&ternary.else_expr)?)),
+            Expr::FunctionCall(name, _, _) => Ok(self
+                .functions
+                .get(name.as_str())
+                .map_or(Type::Int, |func| func.return_ty)),
+        }
+    }
+}
+
+pub(crate) fn evaluate_unary_op(op: UnaryOp, value: Value) -> InterpreterResult<Value> {
+    match (op, value) {
+        (UnaryOp::Negation, Value::Int(v)) => Ok(Value::Int(v.wrapping_neg())),
+        (UnaryOp::Negation, Value::Float(v)) => Ok(Value::Float(-v)),
+        (UnaryOp::Negation, Value::Double(v)) => Ok(Value::Double(-v)),
+        (UnaryOp::LogicalNegation, value) => Ok(Value::from_bool(!value.is_true())),
+        (UnaryOp::BitwiseComplement, Value::Int(v)) => Ok(Value::Int(!v)),
+        (UnaryOp::BitwiseComplement, value) => {
+            Err(InterpreterError::InvalidUnaryOperand(op, value.ty()))
+        }
+    }
+}
+
+pub(crate) fn evaluate_binary_op(op: BinaryOp, lhs: Value, rhs: Value) -> InterpreterResult<Value> {
+    let ty = lhs.ty().common(rhs.ty());
+    match (lhs.convert(ty), rhs.convert(ty)) {
+        (Value::Int(lhs), Value::Int(rhs)) => evaluate_int_binary_op(op, lhs, rhs),
+        (lhs, rhs) => evaluate_floating_binary_op(op, ty, lhs.to_double(), rhs.to_double()),
+    }
+}
+
+fn evaluate_int_binary_op(op: BinaryOp, lhs: i32, rhs: i32) -> InterpreterResult<Value> {
+    let value = match op {
+        BinaryOp::Addition => lhs.wrapping_add(rhs),
+        BinaryOp::Subtraction => lhs.wrapping_sub(rhs),
+        BinaryOp::Multiplication => lhs.wrapping_mul(rhs),
+        BinaryOp::Division | BinaryOp::Modulo if rhs == 0 => {
+            return Err(InterpreterError::DivisionByZero)
+        }
+        // `INT_MIN / -1` wraps around like `sdiv` does.
+        BinaryOp::Division => lhs.wrapping_div(rhs),
+        BinaryOp::Modulo => lhs.wrapping_rem(rhs),
+        BinaryOp::Equal => (lhs == rhs) as i32,
+        BinaryOp::NotEqual => (lhs != rhs) as i32,
+        BinaryOp::LessThan => (lhs < rhs) as i32,
+        BinaryOp::LessThanOrEq => (lhs <= rhs) as i32,
+        BinaryOp::GreaterThan => (lhs > rhs) as i32,
+        BinaryOp::GreaterThanOrEq => (lhs >= rhs) as i32,
+        BinaryOp::BitwiseAnd => lhs & rhs,
+        BinaryOp::BitwiseOr => lhs | rhs,
+        BinaryOp::BitwiseXor => lhs ^ rhs,
+        // Shift amounts are taken modulo 32 like the AArch64 shift instructions do.
+        BinaryOp::BitwiseShiftLeft => lhs.wrapping_shl(rhs as u32),
+        // Right shift of a negative value is implementation defined, it's an
+        // arithmetic shift in GCC and Clang.
+        BinaryOp::BitwiseShiftRight => lhs.wrapping_shr(rhs as u32),
+        BinaryOp::And | BinaryOp::Or => unreachable!("Short circuiting ops are evaluated lazily"),
+    };
+    Ok(Value::Int(value))
+}
+
+/// Evaluate a floating point operation in double precision and round the
+/// result to the given type. This gives the same results for `float` since
+/// `double` has more than twice the precision of it.
+fn evaluate_floating_binary_op(
+    op: BinaryOp,
+    ty: Type,
+    lhs: f64,
+    rhs: f64,
+) -> InterpreterResult<Value> {
+    let value = match op {
+        BinaryOp::Addition => lhs + rhs,
+        BinaryOp::Subtraction => lhs - rhs,
+        BinaryOp::Multiplication => lhs * rhs,
+        BinaryOp::Division => lhs / rhs,
+        BinaryOp::Equal => return Ok(Value::from_bool(lhs == rhs)),
+        BinaryOp::NotEqual => return Ok(Value::from_bool(lhs != rhs)),
+        BinaryOp::LessThan => return Ok(Value::from_bool(lhs < rhs)),
+        BinaryOp::LessThanOrEq => return Ok(Value::from_bool(lhs <= rhs)),
+        BinaryOp::GreaterThan => return Ok(Value::from_bool(lhs > rhs)),
+        BinaryOp::GreaterThanOrEq => return Ok(Value::from_bool(lhs >= rhs)),
+        BinaryOp::Modulo
+        | BinaryOp::BitwiseAnd
+        | BinaryOp::BitwiseOr
+        | BinaryOp::BitwiseXor
+        | BinaryOp::BitwiseShiftLeft
+        | BinaryOp::BitwiseShiftRight => {
+            return Err(InterpreterError::InvalidBinaryOperands(op, ty))
+        }
+        BinaryOp::And | BinaryOp::Or => unreachable!("Short circuiting ops are evaluated lazily"),
+    };
+    Ok(Value::Double(value).convert(ty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parser::Parser,
+        tokenizer::{Span, Tokenizer},
+    };
+
+    fn interpret(src: &str) -> InterpreterResult<i32> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        Interpreter::new(&program).with_step_limit(100_000).run()
+    }
+
+    macro_rules! interpreter_test {
+        ($name:ident, $src:expr => $value:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(interpret($src), Ok($value));
+            }
+        };
+    }
+
+    macro_rules! interpreter_error_test {
+        ($name:ident, $src:expr => $err:pat) => {
+            #[test]
+            fn $name() {
+                assert!(matches!(interpret($src), Err($err)));
+            }
+        };
+    }
+
+    interpreter_test!(test_return_constant, "int main() { return 42; }" => 42);
+    interpreter_test!(test_implicit_return_from_main, "int main() { int a = 1; }" => 0);
+    interpreter_test!(test_precedence, "int main() { return 2 + 3 * 4 - 10 / 5 % 3; }" => 12);
+    interpreter_test!(test_unary_ops, "int main() { return -~!0 + !5; }" => 2);
+    interpreter_test!(test_wrapping_overflow, "int main() { int a = 2147483647; return a + 1 == -2147483647 - 1; }" => 1);
+    interpreter_test!(test_arithmetic_shift_right, "int main() { return -16 >> 2; }" => -4);
+    interpreter_test!(test_short_circuit, "int main() { int a = 0; 0 && (a = 1); 1 || (a = 2); return a; }" => 0);
+    interpreter_test!(test_shadowing, "int main() { int a = 1; { int a = 2; a = 3; } return a; }" => 1);
+    interpreter_test!(test_ternary, "int main() { int a = 3; return a > 2 ? a * 2 : a; }" => 6);
+    interpreter_test!(
+        test_loops,
+        "int main() {
+            int sum = 0;
+            for (int i = 0; i < 10; i = i + 1) {
+                if (i == 2) continue;
+                if (i == 8) break;
+                sum = sum + i;
+            }
+            int j = 0;
+            while (j < 5) j = j + 2;
+            do { j = j + 1; } while (0);
+            return sum + j;
+        }" => 33
+    );
+    interpreter_test!(
+        test_recursion,
+        "int fib(int n) { return n < 2 ? n : fib(n - 1) + fib(n - 2); }
+         int main() { return fib(10); }" => 55
+    );
+    interpreter_test!(
+        test_forward_declared_call,
+        "int add(int a, int b); int main() { return add(3, 4); } int add(int a, int b) { return a + b; }" => 7
+    );
+    interpreter_test!(test_float_truncation, "int main() { float f = 2.75f; double d = f * 2; return d; }" => 5);
+    interpreter_test!(test_negative_float_truncation, "int main() { return -7.9; }" => -7);
+    interpreter_test!(test_float_param_conversion, "int f(float x) { return x * 4; } int main() { return f(1.3); }" => 5);
+    interpreter_test!(test_float_comparison, "int main() { double a = 0.1; return (a + 0.2 != 0.3) + (1.5f < 2); }" => 2);
+    interpreter_test!(test_int_assignment_truncates, "int main() { int a; a = 3.9; return a; }" => 3);
+
+    interpreter_error_test!(test_no_main, "int f() { return 1; }" => InterpreterError::NoMainFunction);
+    interpreter_error_test!(test_division_by_zero, "int main() { int a = 0; return 1 / a; }" => InterpreterError::DivisionByZero);
+    interpreter_error_test!(test_modulo_by_zero, "int main() { return 1 % 0; }" => InterpreterError::DivisionByZero);
+    #[test]
+    fn test_putchar() {
+        let token_stream = Tokenizer::new(
+            "int putchar(int c); int main() { putchar(72); return putchar(105) + 1; }",
+        )
+        .tokenize()
+        .unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        let mut interpreter = Interpreter::new(&program);
+        assert_eq!(interpreter.run(), Ok(106));
+        assert_eq!(interpreter.stdout(), "Hi");
+    }
+
+    #[test]
+    fn test_string_constant() {
+        let token_stream = Tokenizer::new("int main() { return 1; }")
+            .tokenize()
+            .unwrap();
+        let mut program = Parser::new(token_stream).parse().unwrap();
+        // The parser doesn't produce the string constants yet.
+        let string = Constant::String("a".into());
+        program.functions[0].body = Some(Block {
+            items: vec![BlockItem::Statement(
+                Statement::Return(Box::new(Expr::Constant(string.clone()))),
+                Span::default(),
+            )],
+        });
+        let mut interpreter = Interpreter::new(&program);
+        assert_eq!(
+            interpreter.run(),
+            Err(InterpreterError::UnsupportedConstant(string))
+        );
+    }
+
+    interpreter_error_test!(test_undefined_function, "int f(); int main() { return f(); }" => InterpreterError::UndefinedFunction(_));
+    interpreter_error_test!(test_infinite_loop, "int main() { while (1) {} }" => InterpreterError::StepLimitExceeded(_));
+    interpreter_error_test!(test_infinite_recursion, "int f() { return f(); } int main() { return f(); }" => InterpreterError::StackOverflow(_));
+    interpreter_error_test!(test_float_modulo, "int main() { return 1.5 % 2; }" => InterpreterError::InvalidBinaryOperands(BinaryOp::Modulo, Type::Double));
+}
@@ -0,0 +1,183 @@
+//! The dominator tree and the dominance frontiers of a function, computed
+//! with the iterative algorithm of Cooper, Harvey and Kennedy. A block
+//! dominates another one if every path from the entry to the other block
+//! goes through it. Only the reachable blocks are in the tree.
+
+use super::{BlockId, Function, ENTRY};
+
+#[derive(Debug, PartialEq)]
+pub struct Dominators {
+    /// The immediate dominator of every reachable block, the entry block is
+    /// its own.
+    idoms: Vec<Option<BlockId>>,
+    /// The reachable blocks in reverse postorder.
+    order: Vec<BlockId>,
+}
+
+impl Dominators {
+    pub fn new(func: &Function) -> Dominators {
+        let order = reverse_postorder(func);
+        let mut position = vec![usize::MAX; func.blocks.len()];
+        for (index, id) in order.iter().enumerate() {
+            position[*id] = index;
+        }
+        let predecessors = func.predecessors();
+
+        let mut idoms = vec![None; func.blocks.len()];
+        idoms[ENTRY] = Some(ENTRY);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &id in order.iter().skip(1) {
+                let mut processed = predecessors[id]
+                    .iter()
+                    .copied()
+                    .filter(|predecessor| idoms[*predecessor].is_some());
+                let Some(first) = processed.next() else {
+                    continue;
+                };
+                let idom = processed.fold(first, |a, b| intersect(&idoms, &position, a, b));
+                if idoms[id] != Some(idom) {
+                    idoms[id] = Some(idom);
+                    changed = true;
+                }
+            }
+        }
+        Dominators { idoms, order }
+    }
+
+    /// The immediate dominator of the block, `None` for the entry block and
+    /// the unreachable blocks.
+    pub fn idom(&self, id: BlockId) -> Option<BlockId> {
+        self.idoms[id].filter(|_| id != ENTRY)
+    }
+
+    /// The reachable blocks in reverse postorder, the dominators come before
+    /// the blocks that they dominate.
+    pub fn order(&self) -> &[BlockId] {
+        &self.order
+    }
+
+    pub fn dominates(&self, a: BlockId, mut b: BlockId) -> bool {
+        if self.idoms[b].is_none() {
+            return false;
+        }
+        loop {
+            if a == b {
+                return true;
+            }
+            match self.idom(b) {
+                Some(idom) => b = idom,
+                None => return false,
+            }
+        }
+    }
+
+    /// The blocks that every block immediately dominates.
+    pub fn children(&self) -> Vec<Vec<BlockId>> {
+        let mut children = vec![vec![]; self.idoms.len()];
+        for &id in &self.order {
+            if let Some(idom) = self.idom(id) {
+                children[idom].push(id);
+            }
+        }
+        children
+    }
+
+    /// The dominance frontier of every block: the blocks where its dominance
+    /// ends, i.e. the ones that it doesn't strictly dominate but dominates a
+    /// predecessor of.
+    pub fn frontiers(&self, func: &Function) -> Vec<Vec<BlockId>> {
+        let mut frontiers: Vec<Vec<BlockId>> = vec![vec![]; func.blocks.len()];
+        for (id, predecessors) in func.predecessors().into_iter().enumerate() {
+            if predecessors.len() < 2 {
+                continue;
+            }
+            let Some(idom) = self.idom(id) else {
+                continue;
+            };
+            for predecessor in predecessors {
+                let mut runner = predecessor;
+                while runner != idom {
+                    if !frontiers[runner].contains(&id) {
+                        frontiers[runner].push(id);
+                    }
+                    match self.idom(runner) {
+                        Some(next) => runner = next,
+                        None => break,
+                    }
+                }
+            }
+        }
+        frontiers
+    }
+}
+
+/// The closest common dominator of two blocks.
+fn intersect(
+    idoms: &[Option<BlockId>],
+    position: &[usize],
+    mut a: BlockId,
+    mut b: BlockId,
+) -> BlockId {
+    while a != b {
+        while position[a] > position[b] {
+            a = idoms[a].unwrap();
+        }
+        while position[b] > position[a] {
+            b = idoms[b].unwrap();
+        }
+    }
+    a
+}
+
+fn reverse_postorder(func: &Function) -> Vec<BlockId> {
+    let mut visited = vec![false; func.blocks.len()];
+    let mut postorder = vec![];
+    // The successors that are left to visit for every block on the path.
+    let mut stack = vec![(ENTRY, func.blocks[ENTRY].terminator.successors())];
+    visited[ENTRY] = true;
+    while let Some((id, successors)) = stack.last_mut() {
+        match successors.pop() {
+            Some(successor) if !visited[successor] => {
+                visited[successor] = true;
+                let successors = func.blocks[successor].terminator.successors();
+                stack.push((successor, successors));
+            }
+            Some(_) => {}
+            None => {
+                postorder.push(*id);
+                stack.pop();
+            }
+        }
+    }
+    postorder.reverse();
+    postorder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::passes::tests::lower_src;
+
+    #[test]
+    fn test_dominators() {
+        // bb0 -> bb2 (condition) -> bb3 (body) -> bb2, bb2 -> bb4 (end)
+        let module = lower_src("int main() { int i = 0; while (i < 3) i = i + 1; return i; }");
+        let func = &module.functions[0];
+        let dominators = Dominators::new(func);
+        assert_eq!(dominators.order()[0], ENTRY);
+        assert_eq!(dominators.idom(ENTRY), None);
+        assert_eq!(dominators.idom(2), Some(0));
+        assert_eq!(dominators.idom(3), Some(2));
+        assert_eq!(dominators.idom(4), Some(2));
+        assert!(dominators.dominates(2, 3));
+        assert!(!dominators.dominates(3, 4));
+        assert_eq!(dominators.children()[2], [3, 4]);
+
+        let frontiers = dominators.frontiers(func);
+        assert_eq!(frontiers[3], [2]);
+        assert_eq!(frontiers[2], [2]);
+        assert!(frontiers[0].is_empty());
+    }
+}
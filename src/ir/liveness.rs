@@ -0,0 +1,102 @@
+//! Liveness of the temporaries. A temporary is live at a point if its value
+//! may be used later. The operands of a phi are used at the end of their
+//! predecessors, not in the block of the phi.
+
+use super::{BlockId, Function, Instruction, Operand, Temp};
+use std::collections::BTreeSet;
+
+pub type Temps = BTreeSet<Temp>;
+
+#[derive(Debug, PartialEq)]
+pub struct Liveness {
+    /// The temporaries that are live at the start of every block, the phis
+    /// of the block aren't assigned yet.
+    pub live_in: Vec<Temps>,
+    /// The temporaries that are live at the end of every block.
+    pub live_out: Vec<Temps>,
+}
+
+impl Liveness {
+    pub fn new(func: &Function) -> Liveness {
+        let count = func.blocks.len();
+        // The temporaries that each block reads before it assigns them, and
+        // the ones that it assigns.
+        let mut uses = vec![Temps::new(); count];
+        let mut defs = vec![Temps::new(); count];
+        // The phi operands that each block passes to its successors.
+        let mut phi_uses = vec![Temps::new(); count];
+        for (id, block) in func.blocks.iter().enumerate() {
+            for inst in &block.instructions {
+                if let Instruction::Phi(_, incoming) = inst {
+                    for (predecessor, value) in incoming {
+                        if let Operand::Temp(temp) = value {
+                            phi_uses[*predecessor].insert(*temp);
+                        }
+                    }
+                } else {
+                    for temp in temps(inst.operands()) {
+                        if !defs[id].contains(&temp) {
+                            uses[id].insert(temp);
+                        }
+                    }
+                }
+                if let Some(dest) = inst.dest() {
+                    defs[id].insert(dest);
+                }
+            }
+            for temp in temps(block.terminator.operand().into_iter().collect()) {
+                if !defs[id].contains(&temp) {
+                    uses[id].insert(temp);
+                }
+            }
+        }
+
+        let mut live_in = vec![Temps::new(); count];
+        let mut live_out = vec![Temps::new(); count];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for id in (0..count).rev() {
+                let mut out = phi_uses[id].clone();
+                for successor in func.blocks[id].terminator.successors() {
+                    out.extend(live_in[successor].iter().copied());
+                }
+                let mut new_in = &out - &defs[id];
+                new_in.extend(uses[id].iter().copied());
+                if new_in != live_in[id] || out != live_out[id] {
+                    live_in[id] = new_in;
+                    live_out[id] = out;
+                    changed = true;
+                }
+            }
+        }
+        Liveness { live_in, live_out }
+    }
+
+    pub fn is_live_out(&self, block: BlockId, temp: Temp) -> bool {
+        self.live_out[block].contains(&temp)
+    }
+}
+
+fn temps(operands: Vec<&Operand>) -> impl Iterator<Item = Temp> + '_ {
+    operands.into_iter().filter_map(|operand| match operand {
+        Operand::Temp(temp) => Some(*temp),
+        Operand::Const(_) => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::passes::tests::lower_src;
+
+    #[test]
+    fn test_liveness() {
+        let module = lower_src("int main() { int i = 0; while (i < 3) i = i + 1; return i; }");
+        let liveness = Liveness::new(&module.functions[0]);
+        // The values stay in the slot between the blocks.
+        assert!(liveness.live_in.iter().all(Temps::is_empty));
+        // The condition is used by the branch at the end of its block.
+        assert!(liveness.live_out[2].is_empty());
+    }
+}
@@ -0,0 +1,498 @@
+//! Lowering of the control-flow graphs to the IR. The conversions follow the
+//! AST codegen, and the programs that it rejects are rejected with the same
+//! errors, so the optimization level doesn't change what compiles.
+
+use super::{Block, BlockId, Function, Instruction, Module, Operand, Slot, SlotId, Terminator};
+use crate::{
+    cfg::{self, Cfg, CfgBuilder, Item},
+    codegen::CodegenError,
+    interpreter::Value,
+    parser::ast::{BinaryOp, Constant, Expr, Program, Type, UnaryOp},
+    tokenizer::Span,
+};
+use std::collections::HashMap;
+
+type LowerResult<T> = Result<T, CodegenError>;
+
+/// Types of a function that the callers need to know.
+struct Signature {
+    return_ty: Type,
+    params: Vec<Type>,
+}
+
+/// Lower the function definitions of the program.
+pub fn lower(program: &Program) -> LowerResult<Module> {
+    let signatures: HashMap<&str, Signature> = program
+        .functions
+        .iter()
+        .map(|func| {
+            let signature = Signature {
+                return_ty: func.return_ty,
+                params: func.params.iter().map(|param| param.ty).collect(),
+            };
+            (func.name.as_str(), signature)
+        })
+        .collect();
+
+    let mut functions = vec![];
+    for func in program.functions.iter().filter(|func| func.is_definition()) {
+        let cfg = CfgBuilder::new().build_function(func)?;
+        let mut lowerer = Lowerer {
+            signatures: &signatures,
+            func: Function {
+                name: func.name.clone(),
+                return_ty: func.return_ty,
+                // `inline` can be on any of the declarations.
+                inline: program
+                    .functions
+                    .iter()
+                    .any(|other| other.name == func.name && other.is_inline),
+                params: vec![],
+                slots: vec![],
+                temps: vec![],
+                blocks: vec![],
+            },
+            vars: HashMap::new(),
+            current: 0,
+        };
+        for param in &func.params {
+            let name = param
+                .name
+                .as_ref()
+                .ok_or(CodegenError::UnnamedParameter(func.name.clone(), func.span))?;
+            let slot = lowerer.declare(name, param.ty, func.span)?;
+            lowerer.func.params.push(slot);
+        }
+        lowerer.lower_cfg(&cfg)?;
+        functions.push(lowerer.func);
+    }
+    Ok(Module { functions })
+}
+
+struct Lowerer<'a> {
+    signatures: &'a HashMap<&'a str, Signature>,
+    func: Function,
+    /// The slots of the variables. They can't be shadowed, like in the codegen.
+    vars: HashMap<String, SlotId>,
+    /// The block that the instructions are added to.
+    current: BlockId,
+}
+
+impl Lowerer<'_> {
+    fn declare(&mut self, name: &str, ty: Type, span: Span) -> LowerResult<SlotId> {
+        if self.vars.contains_key(name) {
+            return Err(CodegenError::VarAlreadyDeclared(name.to_string(), span));
+        }
+        let slot = self.new_slot(name.to_string(), ty);
+        self.vars.insert(name.to_string(), slot);
+        Ok(slot)
+    }
+
+    fn new_slot(&mut self, name: String, ty: Type) -> SlotId {
+        self.func.slots.push(Slot { name, ty });
+        SlotId(self.func.slots.len() - 1)
+    }
+
+    fn var(&self, name: &str, span: Span) -> LowerResult<SlotId> {
+        self.vars
+            .get(name)
+            .copied()
+            .ok_or(CodegenError::VarNotFound(name.to_string(), span))
+    }
+
+    /// Add a block, it jumps to itself until its terminator is set.
+    fn new_block(&mut self) -> BlockId {
+        let id = self.func.blocks.len();
+        self.func.blocks.push(Block {
+            instructions: vec![],
+            terminator: Terminator::Jump(id),
+        });
+        id
+    }
+
+    fn push(&mut self, inst: Instruction) {
+        self.func.blocks[self.current].instructions.push(inst);
+    }
+
+    /// End the current block and continue with the next one.
+    fn finish(&mut self, terminator: Terminator, next: BlockId) {
+        self.func.blocks[self.current].terminator = terminator;
+        self.current = next;
+    }
+
+    fn lower_cfg(&mut self, cfg: &Cfg) -> LowerResult<()> {
+        // The variables are declared first, the blocks aren't in the order of
+        // the source.
+        for block in &cfg.blocks {
+            for item in &block.items {
+                if let Item::Declaration(decl) = item {
+                    self.declare(&decl.name, decl.ty, decl.span)?;
+                }
+            }
+        }
+
+        // The blocks keep the ids that they have in the graph.
+        for _ in &cfg.blocks {
+            self.new_block();
+        }
+        // The arguments are stored to the slots of the parameters.
+        self.current = cfg::ENTRY;
+        for (index, slot) in self.func.params.clone().into_iter().enumerate() {
+            let value = self.func.new_temp(self.func.slots[slot.0].ty);
+            self.push(Instruction::Param(value, index));
+            self.push(Instruction::Store(slot, Operand::Temp(value)));
+        }
+        for (id, block) in cfg.blocks.iter().enumerate() {
+            self.current = id;
+            for item in &block.items {
+                match item {
+                    Item::Declaration(decl) => {
+                        let value = match decl.initializer {
+                            Some(ref initializer) => self.lower_expr_as(initializer, decl.ty)?,
+                            // The variables without an initializer start as 0, like in the codegen.
+                            None => Operand::Const(Value::Int(0).convert(decl.ty)),
+                        };
+                        let slot = self.var(&decl.name, decl.span)?;
+                        self.push(Instruction::Store(slot, value));
+                    }
+                    Item::Expression(expr) => {
+                        self.lower_expr(expr)?;
+                    }
+                }
+            }
+
+            let terminator = match &block.terminator {
+                cfg::Terminator::Jump(target) => Terminator::Jump(*target),
+                cfg::Terminator::Branch(condition, then_block, else_block) => {
+                    self.lower_branch(condition, *then_block, *else_block)?
+                }
+                cfg::Terminator::Return(value) => {
+                    Terminator::Return(self.lower_expr_as(value, self.func.return_ty)?)
+                }
+                // The end of the function body is reached, `main` returns 0.
+                cfg::Terminator::Exit => {
+                    Terminator::Return(Operand::Const(Value::Int(0).convert(self.func.return_ty)))
+                }
+            };
+            self.func.blocks[self.current].terminator = terminator;
+        }
+        Ok(())
+    }
+
+    fn lower_expr_as(&mut self, expr: &Expr, ty: Type) -> LowerResult<Operand> {
+        let value = self.lower_expr(expr)?;
+        Ok(self.convert(value, ty))
+    }
+
+    fn convert(&mut self, value: Operand, ty: Type) -> Operand {
+        if self.func.operand_type(&value) == ty {
+            return value;
+        }
+        let dest = self.func.new_temp(ty);
+        self.push(Instruction::Convert(dest, value));
+        Operand::Temp(dest)
+    }
+
+    fn lower_expr(&mut self, expr: &Expr) -> LowerResult<Operand> {
+        match expr {
+            Expr::Constant(Constant::Int(v)) => Ok(Operand::Const(Value::Int(*v))),
+            Expr::Constant(Constant::Float(v)) => Ok(Operand::Const(Value::Float(*v))),
+            Expr::Constant(Constant::Double(v)) => Ok(Operand::Const(Value::Double(*v))),
+            Expr::Constant(Constant::Bool(v)) => Ok(Operand::Const(Value::Int(*v as i32))),
+            Expr::Constant(constant @ Constant::String(_)) => {
+                Err(CodegenError::UnsupportedConstant(constant.clone()))
+            }
+            Expr::Var(name, span) => {
+                let slot = self.var(name, *span)?;
+                let dest = self.func.new_temp(self.func.slots[slot.0].ty);
+                self.push(Instruction::Load(dest, slot));
+                Ok(Operand::Temp(dest))
+            }
+            Expr::Assignment(name, value, span) => {
+                let slot = self.var(name, *span)?;
+                let value = self.lower_expr_as(value, self.func.slots[slot.0].ty)?;
+                self.push(Instruction::Store(slot, value));
+                Ok(value)
+            }
+            Expr::UnaryOp(op, operand, span) => {
+                let operand = self.lower_expr(operand)?;
+                let ty = self.func.operand_type(&operand);
+                let dest_ty = match op {
+                    UnaryOp::Negation => ty,
+                    UnaryOp::BitwiseComplement if ty.is_floating() => {
+                        return Err(CodegenError::InvalidUnaryOperand(*op, ty, Some(*span)))
+                    }
+                    UnaryOp::BitwiseComplement | UnaryOp::LogicalNegation => Type::Int,
+                };
+                let dest = self.func.new_temp(dest_ty);
+                self.push(Instruction::Unary(dest, *op, operand));
+                Ok(Operand::Temp(dest))
+            }
+            Expr::BinaryOp(op, lhs, rhs, _) if op.is_short_circuiting_op() => {
+                self.lower_short_circuiting_op(*op, lhs, rhs)
+            }
+            Expr::BinaryOp(op, lhs, rhs, span) => {
+                let lhs = self.lower_expr(lhs)?;
+                let rhs = self.lower_expr(rhs)?;
+                // Both of the operands are converted to their common type.
+                let ty = self
+                    .func
+                    .operand_type(&lhs)
+                    .common(self.func.operand_type(&rhs));
+                // Only the arithmetic and the comparisons work on floating values.
+                let is_floating_op = matches!(
+                    op,
+                    BinaryOp::Addition
+                        | BinaryOp::Subtraction
+                        | BinaryOp::Multiplication
+                        | BinaryOp::Division
+                        | BinaryOp::Equal
+                        | BinaryOp::NotEqual
+                        | BinaryOp::LessThan
+                        | BinaryOp::LessThanOrEq
+                        | BinaryOp::GreaterThan
+                        | BinaryOp::GreaterThanOrEq
+                );
+                if ty.is_floating() && !is_floating_op {
+                    return Err(CodegenError::InvalidBinaryOperands(*op, ty, Some(*span)));
+                }
+                let lhs = self.convert(lhs, ty);
+                let rhs = self.convert(rhs, ty);
+                let dest = self
+                    .func
+                    .new_temp(if op.is_arithmetic_op() { ty } else { Type::Int });
+                self.push(Instruction::Binary(dest, *op, lhs, rhs));
+                Ok(Operand::Temp(dest))
+            }
+            Expr::TernaryConditional(ternary) => {
+                let [then_block, else_block, end_block] =
+                    [self.new_block(), self.new_block(), self.new_block()];
+                let condition = self.lower_expr(&ternary.condition)?;
+                self.finish(
+                    Terminator::Branch(condition, then_block, else_block),
+                    then_block,
+                );
+                let if_value = self.lower_expr(&ternary.if_expr)?;
+                let if_end = self.current;
+                self.finish(Terminator::Jump(end_block), else_block);
+                let else_value = self.lower_expr(&ternary.else_expr)?;
+                let else_end = self.current;
+                self.finish(Terminator::Jump(end_block), end_block);
+
+                // Both of the branches are converted to their common type, it's
+                // only known once they are lowered.
+                let ty = self
+                    .func
+                    .operand_type(&if_value)
+                    .common(self.func.operand_type(&else_value));
+                let slot = self.new_slot(format!("ternary.{}", end_block), ty);
+                for (block, value) in [(if_end, if_value), (else_end, else_value)] {
+                    self.current = block;
+                    let value = self.convert(value, ty);
+                    self.push(Instruction::Store(slot, value));
+                }
+                self.current = end_block;
+                let dest = self.func.new_temp(ty);
+                self.push(Instruction::Load(dest, slot));
+                Ok(Operand::Temp(dest))
+            }
+            Expr::FunctionCall(name, args, _) => {
+                let (return_ty, param_types) = match self.signatures.get(name.as_str()) {
+                    Some(signature) => (signature.return_ty, signature.params.clone()),
+                    None => (Type::Int, vec![]),
+                };
+                let mut values = vec![];
+                for (index, arg) in args.iter().enumerate() {
+                    let value = self.lower_expr(arg)?;
+                    let ty = match param_types.get(index) {
+                        Some(ty) => *ty,
+                        // Implicitly declared functions get the default argument promotions.
+                        None => match self.func.operand_type(&value) {
+                            Type::Float => Type::Double,
+                            ty => ty,
+                        },
+                    };
+                    values.push(self.convert(value, ty));
+                }
+                let dest = self.func.new_temp(return_ty);
+                self.push(Instruction::Call(dest, name.clone(), values));
+                Ok(Operand::Temp(dest))
+            }
+            Expr::Null => Ok(Operand::Const(Value::Int(0))),
+        }
+    }
+
+    /// Lower the condition of a branch and return the terminator of the
+    /// current block. `&&`, `||` and `!` become a chain of branches, like in
+    /// the AST codegen, so their values are never computed.
+    fn lower_branch(
+        &mut self,
+        condition: &Expr,
+        then_block: BlockId,
+        else_block: BlockId,
+    ) -> LowerResult<Terminator> {
+        match condition {
+            Expr::BinaryOp(op @ (BinaryOp::And | BinaryOp::Or), lhs, rhs, _) => {
+                let rhs_block = self.new_block();
+                let terminator = match op {
+                    BinaryOp::And => self.lower_branch(lhs, rhs_block, else_block)?,
+                    _ => self.lower_branch(lhs, then_block, rhs_block)?,
+                };
+                self.finish(terminator, rhs_block);
+                self.lower_branch(rhs, then_block, else_block)
+            }
+            Expr::UnaryOp(UnaryOp::LogicalNegation, operand, _) => {
+                self.lower_branch(operand, else_block, then_block)
+            }
+            condition => Ok(Terminator::Branch(
+                self.lower_expr(condition)?,
+                then_block,
+                else_block,
+            )),
+        }
+    }
+
+    /// The result is kept in a slot, since it's set in two blocks.
+    fn lower_short_circuiting_op(
+        &mut self,
+        op: BinaryOp,
+        lhs: &Expr,
+        rhs: &Expr,
+    ) -> LowerResult<Operand> {
+        let [rhs_block, end_block] = [self.new_block(), self.new_block()];
+        let name = match op {
+            BinaryOp::And => "and",
+            _ => "or",
+        };
+        let slot = self.new_slot(format!("{}.{}", name, end_block), Type::Int);
+
+        // The result is decided by the left hand side unless the right hand
+        // side is evaluated.
+        let lhs = self.lower_expr(lhs)?;
+        let (short_circuit_value, terminator) = match op {
+            BinaryOp::And => (0, Terminator::Branch(lhs, rhs_block, end_block)),
+            _ => (1, Terminator::Branch(lhs, end_block, rhs_block)),
+        };
+        self.push(Instruction::Store(
+            slot,
+            Operand::Const(Value::Int(short_circuit_value)),
+        ));
+        self.finish(terminator, rhs_block);
+
+        let rhs = self.lower_expr(rhs)?;
+        let ty = self.func.operand_type(&rhs);
+        let value = self.func.new_temp(Type::Int);
+        self.push(Instruction::Binary(
+            value,
+            BinaryOp::NotEqual,
+            rhs,
+            Operand::Const(Value::Int(0).convert(ty)),
+        ));
+        self.push(Instruction::Store(slot, Operand::Temp(value)));
+        self.finish(Terminator::Jump(end_block), end_block);
+
+        let dest = self.func.new_temp(Type::Int);
+        self.push(Instruction::Load(dest, slot));
+        Ok(Operand::Temp(dest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codegen::tests::returning,
+        ir::passes::tests::{lower_src, try_lower_src},
+    };
+
+    #[test]
+    fn test_lower() {
+        let module = lower_src("int main() { int a = 1; a = a + 2.5; return a; }");
+        assert_eq!(
+            module.to_string(),
+            "function main() -> int {
+bb0:
+  store a, 1
+  %0: int = load a
+  %1: double = convert %0
+  %2: double = add %1, 2.5
+  %3: int = convert %2
+  store a, %3
+  %4: int = load a
+  ret %4
+}"
+        );
+    }
+
+    #[test]
+    fn test_lower_short_circuiting_op() {
+        let module = lower_src("int f(int a, float b) { return a && b; }");
+        assert_eq!(
+            module.to_string(),
+            "function f(a: int, b: float) -> int {
+bb0:
+  %0: int = param 0
+  store a, %0
+  %1: float = param 1
+  store b, %1
+  %2: int = load a
+  store and.4, 0
+  branch %2, bb3, bb4
+bb3:
+  %3: float = load b
+  %4: int = ne %3, 0.0f
+  store and.4, %4
+  jump bb4
+bb4:
+  %5: int = load and.4
+  ret %5
+}"
+        );
+    }
+
+    #[test]
+    fn test_lower_branch_chains() {
+        // The conditions of the branches don't keep the values of `&&`, `||`
+        // and `!` in slots.
+        let module = lower_src(
+            "int main() { int a = 1; int b = 2; if (!(a && b) || a) return 1; return 2; }",
+        );
+        let main = &module.functions[0];
+        assert!(main
+            .slots
+            .iter()
+            .all(|slot| slot.name == "a" || slot.name == "b"));
+        assert!(!main.to_string().contains("not"));
+
+        // As values they still are.
+        let module = lower_src("int main() { int a = 1; return a && 2; }");
+        assert!(module.functions[0]
+            .slots
+            .iter()
+            .any(|slot| slot.name.starts_with("and.")));
+    }
+
+    #[test]
+    fn test_lower_errors() {
+        assert!(matches!(
+            lower(&returning(Constant::String("a".into()))),
+            Err(CodegenError::UnsupportedConstant(Constant::String(_)))
+        ));
+        assert!(matches!(
+            try_lower_src("int main() { double a = 1.0; return a % 2; }"),
+            Err(CodegenError::InvalidBinaryOperands(
+                BinaryOp::Modulo,
+                Type::Double,
+                Some(_)
+            ))
+        ));
+        assert!(matches!(
+            try_lower_src("int main() { int a = 1; { int a = 2; } return a; }"),
+            Err(CodegenError::VarAlreadyDeclared(_, Span { lo: 30, hi: 31 }))
+        ));
+        assert!(matches!(
+            try_lower_src("int main() { break; }"),
+            Err(CodegenError::Cfg(_))
+        ));
+    }
+}
@@ -0,0 +1,435 @@
+//! A target independent intermediate representation that the optimization
+//! passes work on.
+//!
+//! The functions are lowered from their control-flow graphs. The local
+//! variables stay in stack slots that are loaded and stored, and the
+//! intermediate values are numbered temporaries, e.g.
+//!
+//! ```text
+//! bb0:
+//!   %0: int = load a
+//!   %1: int = add %0, 1
+//!   store a, %1
+//!   ret %1
+//! ```
+//!
+//! The operands of an instruction are already converted to the same type, so
+//! the codegen doesn't need to know the C conversion rules.
+//!
+//! The `mem2reg` pass promotes the slots to temporaries in SSA form, where
+//! every temporary is assigned once and the values that depend on the path
+//! are merged by phis. [`ssa::destruct`] replaces the phis with copies
+//! before the codegen.
+
+pub mod dominators;
+pub mod liveness;
+mod lower;
+pub mod passes;
+pub mod ssa;
+
+pub use self::{
+    lower::lower,
+    passes::{OptLevel, Pass, PassManager},
+};
+use crate::{
+    interpreter::Value,
+    parser::ast::{BinaryOp, Type, UnaryOp},
+};
+use std::{fmt, mem};
+
+pub type BlockId = usize;
+
+/// The block that the function starts with.
+pub const ENTRY: BlockId = 0;
+
+/// An intermediate value.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct Temp(pub usize);
+
+/// A local variable in the stack frame.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct SlotId(pub usize);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operand {
+    Temp(Temp),
+    Const(Value),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Instruction {
+    Copy(Temp, Operand),
+    Unary(Temp, UnaryOp, Operand),
+    /// Both of the operands have the same type. The comparisons result in an int.
+    Binary(Temp, BinaryOp, Operand, Operand),
+    /// Convert the operand to the type of the temporary.
+    Convert(Temp, Operand),
+    Load(Temp, SlotId),
+    Store(SlotId, Operand),
+    /// Call a function, the arguments have the types of its parameters.
+    Call(Temp, String, Vec<Operand>),
+    /// The value of the parameter with the given index. The parameters are
+    /// at the start of the entry block.
+    Param(Temp, usize),
+    /// The value that comes from the predecessor that the block was entered
+    /// from, in SSA form. The phis are at the start of their blocks.
+    Phi(Temp, Vec<(BlockId, Operand)>),
+}
+
+/// How a block ends.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Terminator {
+    Jump(BlockId),
+    /// Go to the first block if the operand isn't zero, to the second one otherwise.
+    Branch(Operand, BlockId, BlockId),
+    Return(Operand),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Block {
+    pub instructions: Vec<Instruction>,
+    pub terminator: Terminator,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Slot {
+    /// Name of the variable. The slots that the compiler adds have a `.` in
+    /// their names, so they don't clash with the variables.
+    pub name: String,
+    pub ty: Type,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Function {
+    pub name: String,
+    pub return_ty: Type,
+    /// Whether the function is declared `inline`, the inliner allows it to
+    /// be bigger.
+    pub inline: bool,
+    /// The slots of the parameters, in order.
+    pub params: Vec<SlotId>,
+    pub slots: Vec<Slot>,
+    /// The types of the temporaries, indexed by their numbers.
+    pub temps: Vec<Type>,
+    /// The blocks of the function, indexed by their ids. The blocks that
+    /// can't be reached anymore are left in place until
+    /// [`Function::remove_unreachable_blocks`].
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Module {
+    pub functions: Vec<Function>,
+}
+
+impl Instruction {
+    /// The temporary that the instruction assigns to.
+    pub fn dest(&self) -> Option<Temp> {
+        match self {
+            Instruction::Copy(dest, _)
+            | Instruction::Unary(dest, ..)
+            | Instruction::Binary(dest, ..)
+            | Instruction::Convert(dest, _)
+            | Instruction::Load(dest, _)
+            | Instruction::Call(dest, ..)
+            | Instruction::Param(dest, _)
+            | Instruction::Phi(dest, _) => Some(*dest),
+            Instruction::Store(..) => None,
+        }
+    }
+
+    pub fn dest_mut(&mut self) -> Option<&mut Temp> {
+        match self {
+            Instruction::Copy(dest, _)
+            | Instruction::Unary(dest, ..)
+            | Instruction::Binary(dest, ..)
+            | Instruction::Convert(dest, _)
+            | Instruction::Load(dest, _)
+            | Instruction::Call(dest, ..)
+            | Instruction::Param(dest, _)
+            | Instruction::Phi(dest, _) => Some(dest),
+            Instruction::Store(..) => None,
+        }
+    }
+
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Instruction::Copy(_, src)
+            | Instruction::Unary(_, _, src)
+            | Instruction::Convert(_, src)
+            | Instruction::Store(_, src) => vec![src],
+            Instruction::Binary(_, _, lhs, rhs) => vec![lhs, rhs],
+            Instruction::Call(_, _, args) => args.iter().collect(),
+            Instruction::Phi(_, incoming) => incoming.iter().map(|(_, value)| value).collect(),
+            Instruction::Load(..) | Instruction::Param(..) => vec![],
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Instruction::Copy(_, src)
+            | Instruction::Unary(_, _, src)
+            | Instruction::Convert(_, src)
+            | Instruction::Store(_, src) => vec![src],
+            Instruction::Binary(_, _, lhs, rhs) => vec![lhs, rhs],
+            Instruction::Call(_, _, args) => args.iter_mut().collect(),
+            Instruction::Phi(_, incoming) => incoming.iter_mut().map(|(_, value)| value).collect(),
+            Instruction::Load(..) | Instruction::Param(..) => vec![],
+        }
+    }
+
+    /// Whether the instruction can be removed if its result isn't used. It
+    /// follows the constant folder, e.g. a division may trap so it stays.
+    pub fn is_pure(&self) -> bool {
+        !matches!(
+            self,
+            Instruction::Binary(_, BinaryOp::Division | BinaryOp::Modulo, ..)
+                | Instruction::Store(..)
+                | Instruction::Call(..)
+        )
+    }
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch(_, then_block, else_block) => vec![*then_block, *else_block],
+            Terminator::Return(_) => vec![],
+        }
+    }
+
+    pub fn successors_mut(&mut self) -> Vec<&mut BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![target],
+            Terminator::Branch(_, then_block, else_block) => vec![then_block, else_block],
+            Terminator::Return(_) => vec![],
+        }
+    }
+
+    pub fn operand_mut(&mut self) -> Option<&mut Operand> {
+        match self {
+            Terminator::Branch(operand, ..) | Terminator::Return(operand) => Some(operand),
+            Terminator::Jump(_) => None,
+        }
+    }
+
+    pub fn operand(&self) -> Option<&Operand> {
+        match self {
+            Terminator::Branch(operand, ..) | Terminator::Return(operand) => Some(operand),
+            Terminator::Jump(_) => None,
+        }
+    }
+}
+
+impl Function {
+    pub fn operand_type(&self, operand: &Operand) -> Type {
+        match operand {
+            Operand::Temp(temp) => self.temps[temp.0],
+            Operand::Const(value) => value.ty(),
+        }
+    }
+
+    pub fn new_temp(&mut self, ty: Type) -> Temp {
+        self.temps.push(ty);
+        Temp(self.temps.len() - 1)
+    }
+
+    /// Whether each block can be reached from the entry block.
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut stack = vec![ENTRY];
+        while let Some(id) = stack.pop() {
+            if !mem::replace(&mut reachable[id], true) {
+                stack.extend(self.blocks[id].terminator.successors());
+            }
+        }
+        reachable
+    }
+
+    /// The predecessors of every block, only the reachable blocks count.
+    pub fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let reachable = self.reachable();
+        let mut predecessors = vec![vec![]; self.blocks.len()];
+        for (id, block) in self.blocks.iter().enumerate() {
+            if reachable[id] {
+                for successor in block.terminator.successors() {
+                    predecessors[successor].push(id);
+                }
+            }
+        }
+        predecessors
+    }
+
+    /// Remove the blocks that can't be reached and renumber the others,
+    /// return whether there were any.
+    pub fn remove_unreachable_blocks(&mut self) -> bool {
+        let reachable = self.reachable();
+        if reachable.iter().all(|reachable| *reachable) {
+            return false;
+        }
+        let mut ids = vec![None; self.blocks.len()];
+        let mut next = 0;
+        for (id, reachable) in reachable.iter().enumerate() {
+            if *reachable {
+                ids[id] = Some(next);
+                next += 1;
+            }
+        }
+        let blocks = mem::take(&mut self.blocks);
+        for (mut block, reachable) in blocks.into_iter().zip(reachable) {
+            if !reachable {
+                continue;
+            }
+            for inst in &mut block.instructions {
+                if let Instruction::Phi(_, incoming) = inst {
+                    incoming.retain_mut(|(predecessor, _)| match ids[*predecessor] {
+                        Some(id) => {
+                            *predecessor = id;
+                            true
+                        }
+                        None => false,
+                    });
+                }
+            }
+            for successor in block.terminator.successors_mut() {
+                *successor = ids[*successor].unwrap();
+            }
+            self.blocks.push(block);
+        }
+        true
+    }
+
+    /// Number of the instructions in the reachable blocks.
+    pub fn instruction_count(&self) -> usize {
+        self.blocks
+            .iter()
+            .zip(self.reachable())
+            .filter(|(_, reachable)| *reachable)
+            .map(|(block, _)| block.instructions.len())
+            .sum()
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Temp(temp) => write!(f, "%{}", temp.0),
+            Operand::Const(Value::Int(v)) => write!(f, "{}", v),
+            Operand::Const(Value::Float(v)) => write!(f, "{:?}f", v),
+            Operand::Const(Value::Double(v)) => write!(f, "{:?}", v),
+        }
+    }
+}
+
+/// The name of an operator in the printed IR.
+fn binary_op_name(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Addition => "add",
+        BinaryOp::Subtraction => "sub",
+        BinaryOp::Multiplication => "mul",
+        BinaryOp::Division => "div",
+        BinaryOp::Modulo => "mod",
+        BinaryOp::And => "and",
+        BinaryOp::Or => "or",
+        BinaryOp::Equal => "eq",
+        BinaryOp::NotEqual => "ne",
+        BinaryOp::LessThan => "lt",
+        BinaryOp::LessThanOrEq => "le",
+        BinaryOp::GreaterThan => "gt",
+        BinaryOp::GreaterThanOrEq => "ge",
+        BinaryOp::BitwiseAnd => "bitand",
+        BinaryOp::BitwiseOr => "bitor",
+        BinaryOp::BitwiseXor => "xor",
+        BinaryOp::BitwiseShiftLeft => "shl",
+        BinaryOp::BitwiseShiftRight => "shr",
+    }
+}
+
+impl Function {
+    fn fmt_instruction(&self, f: &mut fmt::Formatter<'_>, inst: &Instruction) -> fmt::Result {
+        let slot = |slot: &SlotId| &self.slots[slot.0].name;
+        if let Some(dest) = inst.dest() {
+            write!(f, "%{}: {} = ", dest.0, self.temps[dest.0])?;
+        }
+        match inst {
+            Instruction::Copy(_, src) => write!(f, "{}", src),
+            Instruction::Unary(_, op, src) => {
+                let name = match op {
+                    UnaryOp::Negation => "neg",
+                    UnaryOp::LogicalNegation => "not",
+                    UnaryOp::BitwiseComplement => "bitnot",
+                };
+                write!(f, "{} {}", name, src)
+            }
+            Instruction::Binary(_, op, lhs, rhs) => {
+                write!(f, "{} {}, {}", binary_op_name(*op), lhs, rhs)
+            }
+            Instruction::Convert(_, src) => write!(f, "convert {}", src),
+            Instruction::Load(_, src) => write!(f, "load {}", slot(src)),
+            Instruction::Store(dest, src) => write!(f, "store {}, {}", slot(dest), src),
+            Instruction::Call(_, name, args) => {
+                let args: Vec<String> = args.iter().map(Operand::to_string).collect();
+                write!(f, "call {}({})", name, args.join(", "))
+            }
+            Instruction::Param(_, index) => write!(f, "param {}", index),
+            Instruction::Phi(_, incoming) => {
+                let incoming: Vec<String> = incoming
+                    .iter()
+                    .map(|(block, value)| format!("[bb{}: {}]", block, value))
+                    .collect();
+                write!(f, "phi {}", incoming.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|param| {
+                let slot = &self.slots[param.0];
+                format!("{}: {}", slot.name, slot.ty)
+            })
+            .collect();
+        writeln!(
+            f,
+            "{}function {}({}) -> {} {{",
+            if self.inline { "inline " } else { "" },
+            self.name,
+            params.join(", "),
+            self.return_ty
+        )?;
+        let reachable = self.reachable();
+        for (id, block) in self.blocks.iter().enumerate() {
+            if !reachable[id] {
+                continue;
+            }
+            writeln!(f, "bb{}:", id)?;
+            for inst in &block.instructions {
+                write!(f, "  ")?;
+                self.fmt_instruction(f, inst)?;
+                writeln!(f)?;
+            }
+            match &block.terminator {
+                Terminator::Jump(target) => writeln!(f, "  jump bb{}", target)?,
+                Terminator::Branch(condition, then_block, else_block) => writeln!(
+                    f,
+                    "  branch {}, bb{}, bb{}",
+                    condition, then_block, else_block
+                )?,
+                Terminator::Return(value) => writeln!(f, "  ret {}", value)?,
+            }
+        }
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let functions: Vec<String> = self.functions.iter().map(Function::to_string).collect();
+        write!(f, "{}", functions.join("\n\n"))
+    }
+}
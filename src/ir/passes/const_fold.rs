@@ -0,0 +1,59 @@
+//! Constant folding. The instructions are evaluated by the interpreter, so
+//! they follow the same rules as the generated code. The ones that fail,
+//! e.g. a division by zero, are left for the program to run.
+
+use crate::{
+    interpreter::{evaluate_binary_op, evaluate_unary_op},
+    ir::{Function, Instruction, Operand},
+};
+
+pub fn run(func: &mut Function) -> bool {
+    let mut changed = false;
+    for block in &mut func.blocks {
+        for inst in &mut block.instructions {
+            let value = match inst {
+                Instruction::Unary(_, op, Operand::Const(value)) => {
+                    evaluate_unary_op(*op, *value).ok()
+                }
+                Instruction::Binary(_, op, Operand::Const(lhs), Operand::Const(rhs)) => {
+                    evaluate_binary_op(*op, *lhs, *rhs).ok()
+                }
+                Instruction::Convert(dest, Operand::Const(value)) => {
+                    Some(value.convert(func.temps[dest.0]))
+                }
+                _ => None,
+            };
+            if let (Some(value), Some(dest)) = (value, inst.dest()) {
+                *inst = Instruction::Copy(dest, Operand::Const(value));
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::passes::tests::lower_src;
+
+    #[test]
+    fn test_const_fold() {
+        let mut module = lower_src("int main() { double a = 1 + 2 * 3; return 1 / 0; }");
+        let func = &mut module.functions[0];
+        assert!(run(func));
+        assert!(!run(func));
+        assert_eq!(
+            func.to_string(),
+            "function main() -> int {
+bb0:
+  %0: int = 6
+  %1: int = add 1, %0
+  %2: double = convert %1
+  store a, %2
+  %3: int = div 1, 0
+  ret %3
+}"
+        );
+    }
+}
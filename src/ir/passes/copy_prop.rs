@@ -0,0 +1,115 @@
+//! Copy propagation. The uses of a temporary that is a copy of another
+//! operand are replaced with that operand. Only the temporaries that are
+//! assigned once are replaced, so the copied value can't change in between.
+//! A phi that gets the same value from every predecessor is a copy too.
+
+use crate::ir::{Function, Instruction, Operand, Temp};
+use std::collections::HashMap;
+
+pub fn run(func: &mut Function) -> bool {
+    let mut changed = simplify_phis(func);
+    let mut assignments: HashMap<Temp, usize> = HashMap::new();
+    for inst in func.blocks.iter().flat_map(|block| &block.instructions) {
+        if let Some(dest) = inst.dest() {
+            *assignments.entry(dest).or_default() += 1;
+        }
+    }
+    let assigned_once = |operand: &Operand| match operand {
+        Operand::Temp(temp) => assignments.get(temp) == Some(&1),
+        Operand::Const(_) => true,
+    };
+
+    let mut copies: HashMap<Temp, Operand> = HashMap::new();
+    for inst in func.blocks.iter().flat_map(|block| &block.instructions) {
+        if let Instruction::Copy(dest, src) = inst {
+            if assignments[dest] == 1 && assigned_once(src) {
+                copies.insert(*dest, *src);
+            }
+        }
+    }
+    // The copies of the copies are replaced with the original values.
+    let resolve = |mut operand: Operand| {
+        while let Operand::Temp(temp) = operand {
+            match copies.get(&temp) {
+                Some(src) => operand = *src,
+                None => break,
+            }
+        }
+        operand
+    };
+
+    for block in &mut func.blocks {
+        let operands = block
+            .instructions
+            .iter_mut()
+            .flat_map(Instruction::operands_mut)
+            .chain(block.terminator.operand_mut());
+        for operand in operands {
+            let resolved = resolve(*operand);
+            if resolved != *operand {
+                *operand = resolved;
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Replace the phis that only have one value with copies. The values from
+/// the unreachable predecessors and the phi itself, in a loop, don't count.
+fn simplify_phis(func: &mut Function) -> bool {
+    let reachable = func.reachable();
+    let mut changed = false;
+    for block in &mut func.blocks {
+        for inst in &mut block.instructions {
+            let Instruction::Phi(dest, incoming) = inst else {
+                continue;
+            };
+            let mut values = incoming
+                .iter()
+                .filter(|(predecessor, value)| {
+                    reachable[*predecessor] && *value != Operand::Temp(*dest)
+                })
+                .map(|(_, value)| value);
+            if let Some(first) = values.next() {
+                if values.all(|value| value == first) {
+                    *inst = Instruction::Copy(*dest, *first);
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            // The phis stay at the start of the block.
+            block
+                .instructions
+                .sort_by_key(|inst| !matches!(inst, Instruction::Phi(..)));
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::passes::{const_fold, tests::lower_src};
+
+    #[test]
+    fn test_copy_prop() {
+        let mut module = lower_src("int main() { return 2 * 3 + 1; }");
+        let func = &mut module.functions[0];
+        const_fold::run(func);
+        assert!(run(func));
+        const_fold::run(func);
+        assert!(run(func));
+        assert!(!run(func));
+        assert_eq!(
+            func.to_string(),
+            "function main() -> int {
+bb0:
+  %0: int = 6
+  %1: int = 7
+  ret 7
+}"
+        );
+    }
+}
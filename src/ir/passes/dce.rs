@@ -0,0 +1,107 @@
+//! Dead code elimination. The instructions whose results aren't used are
+//! removed, unless they do something else, e.g. a call or a store. The
+//! values that the calls, the stores and the terminators need are marked
+//! live first, then the values that the live ones are computed from, so a
+//! loop that only feeds itself is removed as well.
+
+use crate::ir::{Function, Instruction, Operand, Temp};
+use std::collections::{HashMap, HashSet};
+
+pub fn run(func: &mut Function) -> bool {
+    let mut definitions: HashMap<Temp, Vec<&Instruction>> = HashMap::new();
+    let mut worklist: Vec<Temp> = vec![];
+    for block in &func.blocks {
+        for inst in &block.instructions {
+            if let Some(dest) = inst.dest() {
+                definitions.entry(dest).or_default().push(inst);
+            }
+            if !inst.is_pure() {
+                worklist.extend(temps(inst.operands()));
+            }
+        }
+        worklist.extend(temps(block.terminator.operand().into_iter().collect()));
+    }
+
+    let mut live: HashSet<Temp> = HashSet::new();
+    while let Some(temp) = worklist.pop() {
+        if !live.insert(temp) {
+            continue;
+        }
+        for inst in definitions.get(&temp).into_iter().flatten() {
+            worklist.extend(temps(inst.operands()));
+        }
+    }
+
+    let mut changed = false;
+    for block in &mut func.blocks {
+        let len = block.instructions.len();
+        block
+            .instructions
+            .retain(|inst| !inst.is_pure() || inst.dest().is_some_and(|dest| live.contains(&dest)));
+        changed |= block.instructions.len() != len;
+    }
+    changed
+}
+
+fn temps(operands: Vec<&Operand>) -> impl Iterator<Item = Temp> + '_ {
+    operands.into_iter().filter_map(|operand| match operand {
+        Operand::Temp(temp) => Some(*temp),
+        Operand::Const(_) => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::passes::{copy_prop, mem2reg, tests::lower_src};
+
+    #[test]
+    fn test_dce() {
+        let mut module =
+            lower_src("int f(); int main() { int a = 1; a + 2; f(); 1 / a; return 0; }");
+        let func = &mut module.functions[0];
+        assert!(run(func));
+        assert!(!run(func));
+        assert_eq!(
+            func.to_string(),
+            "function main() -> int {
+bb0:
+  store a, 1
+  %2: int = call f()
+  %3: int = load a
+  %4: int = div 1, %3
+  ret 0
+}"
+        );
+    }
+
+    #[test]
+    fn test_dce_dead_loop_values() {
+        // `a` is only used to compute itself in the next iteration.
+        let mut module = lower_src(
+            "int main() { int a = 0; int i; for (i = 0; i < 3; i = i + 1) a = a + i; return i; }",
+        );
+        let func = &mut module.functions[0];
+        mem2reg::run(func);
+        copy_prop::run(func);
+        assert!(run(func));
+        assert_eq!(
+            func.to_string(),
+            "function main() -> int {
+bb0:
+  jump bb2
+bb2:
+  %9: int = phi [bb0: 0], [bb4: %6]
+  %1: int = lt %9, 3
+  branch %1, bb3, bb5
+bb3:
+  jump bb4
+bb4:
+  %6: int = add %9, 1
+  jump bb2
+bb5:
+  ret %9
+}"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Dead store elimination. A store to a slot is removed if the slot isn't
+//! loaded again before it's overwritten or the function returns. The slots
+//! that may be loaded later are found with a backward data-flow analysis,
+//! like the liveness of the temporaries. The slots can't be read by the
+//! other functions, so the calls don't keep the stores alive.
+
+use crate::ir::{Function, Instruction, SlotId};
+use std::collections::BTreeSet;
+
+type Slots = BTreeSet<SlotId>;
+
+pub fn run(func: &mut Function) -> bool {
+    let count = func.blocks.len();
+    // The slots that each block loads before it stores them, and the ones
+    // that it stores.
+    let mut uses = vec![Slots::new(); count];
+    let mut defs = vec![Slots::new(); count];
+    for (id, block) in func.blocks.iter().enumerate() {
+        for inst in &block.instructions {
+            match inst {
+                Instruction::Load(_, slot) if !defs[id].contains(slot) => {
+                    uses[id].insert(*slot);
+                }
+                Instruction::Store(slot, _) => {
+                    defs[id].insert(*slot);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut live_in = vec![Slots::new(); count];
+    let mut live_out = vec![Slots::new(); count];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for id in (0..count).rev() {
+            let mut out = Slots::new();
+            for successor in func.blocks[id].terminator.successors() {
+                out.extend(live_in[successor].iter().copied());
+            }
+            let mut new_in = &out - &defs[id];
+            new_in.extend(uses[id].iter().copied());
+            if new_in != live_in[id] || out != live_out[id] {
+                live_in[id] = new_in;
+                live_out[id] = out;
+                changed = true;
+            }
+        }
+    }
+
+    let mut removed = false;
+    for (block, mut live) in func.blocks.iter_mut().zip(live_out) {
+        let mut dead = vec![];
+        for (index, inst) in block.instructions.iter().enumerate().rev() {
+            match inst {
+                Instruction::Load(_, slot) => {
+                    live.insert(*slot);
+                }
+                Instruction::Store(slot, _) if !live.remove(slot) => dead.push(index),
+                _ => {}
+            }
+        }
+        removed |= !dead.is_empty();
+        for index in dead {
+            block.instructions.remove(index);
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::passes::tests::lower_src;
+
+    #[test]
+    fn test_dse() {
+        let mut module =
+            lower_src("int main() { int a = 1; a = 2; int b = a; b = 3; if (a) a = 4; return a; }");
+        let func = &mut module.functions[0];
+        let before = func.instruction_count();
+        assert!(run(func));
+        assert!(!run(func));
+        // The first store to `a` and both of the stores to `b`.
+        assert_eq!(func.instruction_count(), before - 3);
+        let text = func.to_string();
+        assert!(!text.contains("store a, 1"));
+        assert!(!text.contains("store b"));
+        assert!(text.contains("store a, 4"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Inlining of the small leaf functions. A call is replaced with a copy of
+//! the callee's blocks: the temporaries, slots and blocks of the callee are
+//! renumbered after the ones of the caller, the parameters become copies of
+//! the arguments and every return jumps to a new block with the rest of the
+//! caller's block, where a phi merges the returned values.
+//!
+//! Only the functions without calls are inlined, so a function is never
+//! inlined into itself. The pass manager optimizes the callees before their
+//! callers, so the size of a callee is the size after its own optimization,
+//! and the constants of the arguments are folded into the inlined code by
+//! the passes that run after this one.
+
+use crate::{
+    interpreter::Value,
+    ir::{Block, BlockId, Function, Instruction, Module, Operand, Slot, SlotId, Temp, Terminator},
+};
+use std::mem;
+
+/// The callees with at most this many instructions are inlined.
+const THRESHOLD: usize = 12;
+
+/// The threshold of the callees that are declared `inline`.
+const INLINE_THRESHOLD: usize = 48;
+
+/// Inline the calls of the function with the given index and return whether
+/// there were any.
+pub fn run(module: &mut Module, id: usize) -> bool {
+    let mut inlined = false;
+    let mut block = 0;
+    while block < module.functions[id].blocks.len() {
+        let call = module.functions[id].blocks[block]
+            .instructions
+            .iter()
+            .enumerate()
+            .find_map(|(index, inst)| match inst {
+                Instruction::Call(_, name, args) => {
+                    let callee = module
+                        .functions
+                        .iter()
+                        .position(|func| &func.name == name)?;
+                    let callee = &module.functions[callee];
+                    (callee.params.len() == args.len() && should_inline(callee))
+                        .then(|| (index, callee.clone()))
+                }
+                _ => None,
+            });
+        match call {
+            Some((index, callee)) => {
+                inline_call(&mut module.functions[id], block, index, &callee);
+                inlined = true;
+            }
+            // The rest of a block with a call is moved to a new block, so
+            // the block has no other calls to inline.
+            None => block += 1,
+        }
+    }
+    inlined
+}
+
+/// The order that the functions are optimized in, the callees come before
+/// their callers unless they call each other.
+pub fn bottom_up_order(module: &Module) -> Vec<usize> {
+    let mut order = vec![];
+    let mut visited = vec![false; module.functions.len()];
+    for root in 0..module.functions.len() {
+        // The functions on the stack are visited before their callees.
+        let mut stack = vec![(root, false)];
+        while let Some((id, callees_done)) = stack.pop() {
+            if callees_done {
+                order.push(id);
+                continue;
+            }
+            if mem::replace(&mut visited[id], true) {
+                continue;
+            }
+            stack.push((id, true));
+            for callee in callees(&module.functions[id]) {
+                if let Some(callee) = module.functions.iter().position(|func| func.name == callee) {
+                    stack.push((callee, false));
+                }
+            }
+        }
+    }
+    order
+}
+
+fn callees(func: &Function) -> Vec<&str> {
+    func.blocks
+        .iter()
+        .flat_map(|block| &block.instructions)
+        .filter_map(|inst| match inst {
+            Instruction::Call(_, name, _) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn should_inline(callee: &Function) -> bool {
+    let threshold = if callee.inline {
+        INLINE_THRESHOLD
+    } else {
+        THRESHOLD
+    };
+    callees(callee).is_empty() && callee.instruction_count() <= threshold
+}
+
+/// The numbers that are added to the temporaries, slots and blocks of the
+/// callee.
+struct Offsets {
+    temps: usize,
+    slots: usize,
+    blocks: usize,
+}
+
+impl Offsets {
+    fn operand(&self, operand: Operand) -> Operand {
+        match operand {
+            Operand::Temp(temp) => Operand::Temp(Temp(temp.0 + self.temps)),
+            Operand::Const(_) => operand,
+        }
+    }
+
+    fn instruction(&self, inst: &Instruction) -> Instruction {
+        let mut inst = inst.clone();
+        if let Some(dest) = inst.dest_mut() {
+            dest.0 += self.temps;
+        }
+        for operand in inst.operands_mut() {
+            *operand = self.operand(*operand);
+        }
+        match &mut inst {
+            Instruction::Load(_, slot) | Instruction::Store(slot, _) => {
+                *slot = SlotId(slot.0 + self.slots);
+            }
+            Instruction::Phi(_, incoming) => {
+                for (block, _) in incoming {
+                    *block += self.blocks;
+                }
+            }
+            _ => {}
+        }
+        inst
+    }
+}
+
+/// Replace the call at the index of the block with the body of the callee.
+fn inline_call(func: &mut Function, block: BlockId, index: usize, callee: &Function) {
+    let offsets = Offsets {
+        temps: func.temps.len(),
+        slots: func.slots.len(),
+        blocks: func.blocks.len(),
+    };
+    func.temps.extend(&callee.temps);
+    // The locals of the callee get their own slots, even if it's inlined
+    // twice into the same function.
+    func.slots.extend(callee.slots.iter().map(|slot| Slot {
+        name: format!("{}.{}", callee.name, slot.name),
+        ty: slot.ty,
+    }));
+
+    let mut rest = func.blocks[block].instructions.split_off(index);
+    let Instruction::Call(dest, _, args) = rest.remove(0) else {
+        unreachable!("Expected a call");
+    };
+    let continuation = offsets.blocks + callee.blocks.len();
+    let terminator = mem::replace(
+        &mut func.blocks[block].terminator,
+        Terminator::Jump(offsets.blocks),
+    );
+    // The successors are entered from the new block now.
+    for successor in terminator.successors() {
+        for inst in &mut func.blocks[successor].instructions {
+            if let Instruction::Phi(_, incoming) = inst {
+                for (predecessor, _) in incoming {
+                    if *predecessor == block {
+                        *predecessor = continuation;
+                    }
+                }
+            }
+        }
+    }
+
+    let reachable = callee.reachable();
+    let mut returns = vec![];
+    for (id, callee_block) in callee.blocks.iter().enumerate() {
+        let instructions = callee_block
+            .instructions
+            .iter()
+            .map(|inst| match inst {
+                Instruction::Param(temp, param) => {
+                    Instruction::Copy(Temp(temp.0 + offsets.temps), args[*param])
+                }
+                inst => offsets.instruction(inst),
+            })
+            .collect();
+        let terminator = match &callee_block.terminator {
+            Terminator::Return(value) => {
+                if reachable[id] {
+                    returns.push((offsets.blocks + id, offsets.operand(*value)));
+                }
+                Terminator::Jump(continuation)
+            }
+            Terminator::Jump(target) => Terminator::Jump(offsets.blocks + target),
+            Terminator::Branch(condition, then_block, else_block) => Terminator::Branch(
+                offsets.operand(*condition),
+                offsets.blocks + then_block,
+                offsets.blocks + else_block,
+            ),
+        };
+        func.blocks.push(Block {
+            instructions,
+            terminator,
+        });
+    }
+
+    let result = match &returns[..] {
+        [] => Instruction::Copy(
+            dest,
+            Operand::Const(Value::Int(0).convert(func.temps[dest.0])),
+        ),
+        [(_, value)] => Instruction::Copy(dest, *value),
+        _ => Instruction::Phi(dest, returns),
+    };
+    rest.insert(0, result);
+    func.blocks.push(Block {
+        instructions: rest,
+        terminator,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{
+        passes::{tests::lower_src, OptLevel, Pass, PassManager},
+        ENTRY,
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_inline() {
+        let mut module = lower_src(
+            "int get(int a) { if (a > 0) return a; return 0; }
+            int main() { int x = get(3); return x + get(-1); }",
+        );
+        // The callee isn't optimized, so the parameter is still in a slot.
+        assert!(run(&mut module, 1));
+        assert!(!run(&mut module, 1));
+        let main = &module.functions[1];
+        assert!(!main.to_string().contains("call"));
+        assert!(main.slots.iter().any(|slot| slot.name == "get.a"));
+        let Terminator::Jump(target) = main.blocks[ENTRY].terminator else {
+            panic!("Expected a jump into the callee");
+        };
+        assert!(matches!(
+            main.blocks[target].instructions[0],
+            Instruction::Copy(_, Operand::Const(Value::Int(3)))
+        ));
+    }
+
+    #[test]
+    fn test_inline_folds_constants() {
+        let mut module = lower_src(
+            "inline int clamp(int a, int low, int high) {
+                if (a < low) return low;
+                if (a > high) return high;
+                return a;
+            }
+            int main() { return clamp(12, 0, 10) + clamp(-5, 0, 10); }",
+        );
+        PassManager::new(OptLevel::O2).run(&mut module);
+        assert_eq!(
+            module.functions[1].to_string(),
+            "function main() -> int {\nbb0:\n  ret 10\n}"
+        );
+
+        // The calls stay without the inliner.
+        let mut module = lower_src("int get(int a) { return a; } int main() { return get(1); }");
+        PassManager::new(OptLevel::O2)
+            .with_disabled_passes(&HashSet::from([Pass::Inline]))
+            .run(&mut module);
+        assert!(module.functions[1].to_string().contains("call get(1)"));
+    }
+
+    #[test]
+    fn test_inline_heuristic() {
+        let body = "a = a * 3 + 1; ".repeat(8) + "return a;";
+        let src = format!(
+            "int big(int a) {{ {body} }}
+            inline int hint(int a) {{ {body} }}
+            int recursive(int a) {{ if (a) return recursive(a - 1); return 0; }}
+            int main() {{ return big(1) + hint(1) + recursive(1); }}"
+        );
+        let mut module = lower_src(&src);
+        PassManager::new(OptLevel::O2).run(&mut module);
+        let main = module.functions[3].to_string();
+        assert!(main.contains("call big(1)"));
+        assert!(!main.contains("call hint"));
+        assert!(main.contains("call recursive(1)"));
+
+        let module = lower_src("int f(); int main() { return f(); } int f() { return 1; }");
+        assert_eq!(bottom_up_order(&module), [1, 0]);
+    }
+}
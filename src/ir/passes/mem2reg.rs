@@ -0,0 +1,211 @@
+//! Promotion of the slots to temporaries in SSA form. A phi is inserted for
+//! a slot in the dominance frontiers of the blocks that store to it, then
+//! the loads are replaced with the values that reach them while the
+//! dominator tree is walked, as in Cytron et al. The language can't take
+//! the address of a variable, so every slot is promoted.
+
+use crate::{
+    interpreter::Value,
+    ir::{dominators::Dominators, BlockId, Function, Instruction, Operand, SlotId, Temp, ENTRY},
+};
+use std::collections::HashMap;
+
+pub fn run(func: &mut Function) -> bool {
+    let reachable = func.reachable();
+    let mut stores: HashMap<SlotId, Vec<BlockId>> = HashMap::new();
+    let mut has_memory_access = false;
+    for (id, block) in func.blocks.iter().enumerate() {
+        if !reachable[id] {
+            continue;
+        }
+        for inst in &block.instructions {
+            match inst {
+                Instruction::Store(slot, _) => {
+                    stores.entry(*slot).or_default().push(id);
+                    has_memory_access = true;
+                }
+                Instruction::Load(..) => has_memory_access = true,
+                _ => {}
+            }
+        }
+    }
+    if !has_memory_access {
+        return false;
+    }
+
+    let dominators = Dominators::new(func);
+    let frontiers = dominators.frontiers(func);
+
+    // The phis of every block, in the order that they are inserted in.
+    let mut phis: Vec<Vec<(SlotId, Temp)>> = vec![vec![]; func.blocks.len()];
+    let mut slots: Vec<&SlotId> = stores.keys().collect();
+    // The temporaries are numbered in the same order every time.
+    slots.sort();
+    for slot in slots {
+        let mut worklist = stores[slot].clone();
+        while let Some(id) = worklist.pop() {
+            for &frontier in &frontiers[id] {
+                if phis[frontier].iter().any(|(phi_slot, _)| phi_slot == slot) {
+                    continue;
+                }
+                let temp = func.new_temp(func.slots[slot.0].ty);
+                phis[frontier].push((*slot, temp));
+                // The phi is a new store to the slot.
+                if !stores[slot].contains(&frontier) {
+                    worklist.push(frontier);
+                }
+            }
+        }
+    }
+    for (id, block_phis) in phis.iter().enumerate() {
+        let instructions = block_phis
+            .iter()
+            .map(|(_, temp)| Instruction::Phi(*temp, vec![]));
+        func.blocks[id].instructions.splice(0..0, instructions);
+    }
+
+    Renamer {
+        func,
+        phis: &phis,
+        values: HashMap::new(),
+    }
+    .rename(&dominators);
+    true
+}
+
+struct Renamer<'a> {
+    func: &'a mut Function,
+    phis: &'a [Vec<(SlotId, Temp)>],
+    /// The values of the slots in the dominating blocks, the current one is
+    /// at the end.
+    values: HashMap<SlotId, Vec<Operand>>,
+}
+
+enum Visit {
+    Enter(BlockId),
+    /// Go back to the values before the block.
+    Exit(Vec<SlotId>),
+}
+
+impl Renamer<'_> {
+    fn value(&self, slot: SlotId) -> Operand {
+        match self.values.get(&slot).and_then(|values| values.last()) {
+            Some(value) => *value,
+            // The slot isn't assigned on this path, its value is undefined.
+            None => Operand::Const(Value::Int(0).convert(self.func.slots[slot.0].ty)),
+        }
+    }
+
+    fn rename(&mut self, dominators: &Dominators) {
+        let children = dominators.children();
+        let mut stack = vec![Visit::Enter(ENTRY)];
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Enter(id) => {
+                    let assigned = self.rename_block(id);
+                    stack.push(Visit::Exit(assigned));
+                    stack.extend(children[id].iter().rev().map(|child| Visit::Enter(*child)));
+                }
+                Visit::Exit(assigned) => {
+                    for slot in assigned {
+                        self.values.get_mut(&slot).unwrap().pop();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rename the block and return the slots that it assigns.
+    fn rename_block(&mut self, id: BlockId) -> Vec<SlotId> {
+        let mut assigned = vec![];
+        for (slot, temp) in &self.phis[id] {
+            self.values
+                .entry(*slot)
+                .or_default()
+                .push(Operand::Temp(*temp));
+            assigned.push(*slot);
+        }
+
+        let instructions = std::mem::take(&mut self.func.blocks[id].instructions);
+        let mut renamed = Vec::with_capacity(instructions.len());
+        for inst in instructions {
+            match inst {
+                Instruction::Load(dest, slot) => {
+                    renamed.push(Instruction::Copy(dest, self.value(slot)));
+                }
+                Instruction::Store(slot, value) => {
+                    self.values.entry(slot).or_default().push(value);
+                    assigned.push(slot);
+                }
+                inst => renamed.push(inst),
+            }
+        }
+        self.func.blocks[id].instructions = renamed;
+
+        let mut successors = self.func.blocks[id].terminator.successors();
+        successors.dedup();
+        for successor in successors {
+            for (index, (slot, _)) in self.phis[successor].iter().enumerate() {
+                let value = self.value(*slot);
+                if let Instruction::Phi(_, incoming) =
+                    &mut self.func.blocks[successor].instructions[index]
+                {
+                    incoming.push((id, value));
+                }
+            }
+        }
+        assigned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::passes::{copy_prop, dce, tests::lower_src};
+
+    #[test]
+    fn test_mem2reg() {
+        let mut module =
+            lower_src("int f(int n) { int i = 0; while (i < n) i = i + 1; return i; }");
+        let func = &mut module.functions[0];
+        assert!(run(func));
+        assert!(!run(func));
+        copy_prop::run(func);
+        dce::run(func);
+        assert_eq!(
+            func.to_string(),
+            "function f(n: int) -> int {
+bb0:
+  %0: int = param 0
+  jump bb2
+bb2:
+  %7: int = phi [bb0: 0], [bb3: %5]
+  %3: int = lt %7, %0
+  branch %3, bb3, bb4
+bb3:
+  %5: int = add %7, 1
+  jump bb2
+bb4:
+  ret %7
+}"
+        );
+    }
+
+    #[test]
+    fn test_mem2reg_branches() {
+        let mut module =
+            lower_src("int f(int a) { int b; if (a) b = 1; else b = 2; return a ? b : 3; }");
+        let func = &mut module.functions[0];
+        run(func);
+        copy_prop::run(func);
+        dce::run(func);
+        let phis = func
+            .blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter(|inst| matches!(inst, Instruction::Phi(..)))
+            .count();
+        // One for `b` after the `if`, and one for the result of `?:`.
+        assert_eq!(phis, 2);
+    }
+}
@@ -0,0 +1,225 @@
+//! The optimization passes and the pass manager that runs them.
+
+mod const_fold;
+mod copy_prop;
+mod dce;
+mod dse;
+mod inline;
+mod mem2reg;
+mod simplify_cfg;
+
+use super::Module;
+use std::{collections::HashSet, str::FromStr};
+
+/// Maximum number of times `-O2` runs the passes, in case they keep changing
+/// the function.
+const MAX_ITERATIONS: usize = 10;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Pass {
+    /// Substitute the bodies of the small leaf functions for their calls.
+    Inline,
+    /// Promote the slots to temporaries in SSA form.
+    Mem2Reg,
+    /// Evaluate the instructions whose operands are constants.
+    ConstFold,
+    /// Replace the uses of the copied temporaries with the copied values.
+    CopyProp,
+    /// Remove the stores to the slots that aren't loaded again.
+    Dse,
+    /// Remove the instructions whose results aren't used.
+    Dce,
+    /// Remove the constant branches, the blocks that only jump and the
+    /// unreachable blocks.
+    SimplifyCfg,
+}
+
+impl Pass {
+    pub const ALL: [Pass; 7] = [
+        Pass::Inline,
+        Pass::Mem2Reg,
+        Pass::ConstFold,
+        Pass::CopyProp,
+        Pass::Dse,
+        Pass::Dce,
+        Pass::SimplifyCfg,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Pass::Inline => "inline",
+            Pass::Mem2Reg => "mem2reg",
+            Pass::ConstFold => "const-fold",
+            Pass::CopyProp => "copy-prop",
+            Pass::Dse => "dse",
+            Pass::Dce => "dce",
+            Pass::SimplifyCfg => "simplify-cfg",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Pass> {
+        Pass::ALL.into_iter().find(|pass| pass.name() == name)
+    }
+
+    /// Run the pass on the function with the given index and return whether
+    /// it changed anything. Only the inliner looks at the other functions.
+    pub fn run(self, module: &mut Module, id: usize) -> bool {
+        let func = &mut module.functions[id];
+        match self {
+            Pass::Inline => inline::run(module, id),
+            Pass::Mem2Reg => mem2reg::run(func),
+            Pass::ConstFold => const_fold::run(func),
+            Pass::CopyProp => copy_prop::run(func),
+            Pass::Dse => dse::run(func),
+            Pass::Dce => dce::run(func),
+            Pass::SimplifyCfg => simplify_cfg::run(func),
+        }
+    }
+}
+
+impl FromStr for Pass {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Pass, String> {
+        Pass::from_name(name).ok_or_else(|| {
+            let names: Vec<_> = Pass::ALL.iter().map(|pass| pass.name()).collect();
+            format!(
+                "Unknown pass {:?}, expected one of: {}",
+                name,
+                names.join(", ")
+            )
+        })
+    }
+}
+
+/// How much the program is optimized, like `-O1` in GCC.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
+pub enum OptLevel {
+    /// The AST is compiled directly, without the IR.
+    #[default]
+    O0,
+    /// Every pass runs once.
+    O1,
+    /// The passes run until they don't change anything.
+    O2,
+}
+
+impl FromStr for OptLevel {
+    type Err = String;
+
+    fn from_str(level: &str) -> Result<OptLevel, String> {
+        match level {
+            "0" => Ok(OptLevel::O0),
+            "1" => Ok(OptLevel::O1),
+            "2" => Ok(OptLevel::O2),
+            _ => Err(format!(
+                "Unknown optimization level {:?}, expected 0, 1 or 2",
+                level
+            )),
+        }
+    }
+}
+
+/// Runs a sequence of passes on every function of a module.
+#[derive(Debug)]
+pub struct PassManager {
+    passes: Vec<Pass>,
+    iterations: usize,
+    /// The passes that the IR is printed after, for debugging.
+    print_after: HashSet<Pass>,
+}
+
+impl PassManager {
+    /// The passes of the optimization level, `-O0` doesn't run any.
+    pub fn new(level: OptLevel) -> PassManager {
+        let (passes, iterations) = match level {
+            OptLevel::O0 => (vec![], 0),
+            OptLevel::O1 => (Pass::ALL.to_vec(), 1),
+            OptLevel::O2 => (Pass::ALL.to_vec(), MAX_ITERATIONS),
+        };
+        PassManager {
+            passes,
+            iterations,
+            print_after: HashSet::new(),
+        }
+    }
+
+    pub fn with_disabled_passes(mut self, disabled: &HashSet<Pass>) -> PassManager {
+        self.passes.retain(|pass| !disabled.contains(pass));
+        self
+    }
+
+    pub fn with_print_after(mut self, print_after: HashSet<Pass>) -> PassManager {
+        self.print_after = print_after;
+        self
+    }
+
+    /// Optimize the module in place and return the IR that is printed after
+    /// the passes.
+    pub fn run(&self, module: &mut Module) -> Vec<String> {
+        let mut dumps = vec![];
+        // The inliner sees the callees after they are optimized.
+        for id in inline::bottom_up_order(module) {
+            for _ in 0..self.iterations {
+                let mut changed = false;
+                for pass in &self.passes {
+                    changed |= pass.run(module, id);
+                    if self.print_after.contains(pass) {
+                        let func = &module.functions[id];
+                        dumps.push(format!("; IR after {}\n{}", pass.name(), func));
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+        }
+        dumps
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{codegen::CodegenError, ir::lower, parser::Parser, tokenizer::Tokenizer};
+
+    /// Lower a program that parses, the lowering can still fail.
+    pub(crate) fn try_lower_src(src: &str) -> Result<Module, CodegenError> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        lower(&Parser::new(token_stream).parse().unwrap())
+    }
+
+    pub(crate) fn lower_src(src: &str) -> Module {
+        try_lower_src(src).unwrap()
+    }
+
+    #[test]
+    fn test_pass_names() {
+        for pass in Pass::ALL {
+            assert_eq!(pass.name().parse(), Ok(pass));
+        }
+        assert!("licm".parse::<Pass>().is_err());
+        assert_eq!("2".parse(), Ok(OptLevel::O2));
+        assert!("3".parse::<OptLevel>().is_err());
+    }
+
+    #[test]
+    fn test_pass_manager() {
+        let src = "int main() { int a = 2 * 3; if (a) return a + 1; return 0; }";
+        let mut module = lower_src(src);
+        let before = module.functions[0].instruction_count();
+        let dumps = PassManager::new(OptLevel::O2)
+            .with_print_after([Pass::Dce].into())
+            .run(&mut module);
+        assert!(module.functions[0].instruction_count() < before);
+        assert!(dumps[0].starts_with("; IR after dce\nfunction main() -> int {"));
+
+        // Nothing changes without the passes.
+        let mut module = lower_src(src);
+        let disabled = Pass::ALL.into_iter().collect();
+        PassManager::new(OptLevel::O2)
+            .with_disabled_passes(&disabled)
+            .run(&mut module);
+        assert_eq!(module, lower_src(src));
+    }
+}
@@ -0,0 +1,153 @@
+//! Control-flow graph simplification. The branches that only go one way
+//! become jumps, the jumps through the empty blocks go straight to their
+//! targets, and a block is merged into the one before it if that's its only
+//! predecessor. The blocks that aren't reached anymore are removed.
+//! The phis are kept up to date with the edges that are removed or moved.
+
+use crate::ir::{Block, BlockId, Function, Instruction, Operand, Terminator, ENTRY};
+use std::mem;
+
+pub fn run(func: &mut Function) -> bool {
+    let mut changed = false;
+    for id in 0..func.blocks.len() {
+        if let Terminator::Branch(condition, then_block, else_block) = func.blocks[id].terminator {
+            let (target, removed) = match condition {
+                Operand::Const(value) if value.is_true() => (then_block, else_block),
+                Operand::Const(_) => (else_block, then_block),
+                _ if then_block == else_block => (then_block, then_block),
+                _ => continue,
+            };
+            func.blocks[id].terminator = Terminator::Jump(target);
+            if removed != target {
+                for inst in &mut func.blocks[removed].instructions {
+                    if let Instruction::Phi(_, incoming) = inst {
+                        incoming.retain(|(predecessor, _)| *predecessor != id);
+                    }
+                }
+            }
+            changed = true;
+        }
+    }
+
+    let targets: Vec<BlockId> = (0..func.blocks.len())
+        .map(|id| jump_target(func, id))
+        .collect();
+    for block in &mut func.blocks {
+        for successor in block.terminator.successors_mut() {
+            if targets[*successor] != *successor {
+                *successor = targets[*successor];
+                changed = true;
+            }
+        }
+    }
+
+    while let Some((id, next)) = find_merge(func) {
+        let next_block = mem::replace(
+            &mut func.blocks[next],
+            Block {
+                instructions: vec![],
+                terminator: Terminator::Jump(next),
+            },
+        );
+        // The phis of the merged block only have one predecessor left.
+        let instructions = next_block.instructions.into_iter().map(|inst| match inst {
+            Instruction::Phi(dest, incoming) => {
+                let (_, value) = incoming
+                    .into_iter()
+                    .find(|(predecessor, _)| *predecessor == id)
+                    .unwrap();
+                Instruction::Copy(dest, value)
+            }
+            inst => inst,
+        });
+        let block = &mut func.blocks[id];
+        block.instructions.extend(instructions);
+        block.terminator = next_block.terminator;
+        for successor in func.blocks[id].terminator.successors() {
+            for inst in &mut func.blocks[successor].instructions {
+                if let Instruction::Phi(_, incoming) = inst {
+                    for (predecessor, _) in incoming {
+                        if *predecessor == next {
+                            *predecessor = id;
+                        }
+                    }
+                }
+            }
+        }
+        changed = true;
+    }
+    func.remove_unreachable_blocks() || changed
+}
+
+/// The block that a jump to the given block ends up in, after the empty
+/// blocks that only jump.
+fn jump_target(func: &Function, id: BlockId) -> BlockId {
+    let mut target = id;
+    let mut visited = vec![id];
+    while let Block {
+        instructions,
+        terminator: Terminator::Jump(next),
+    } = &func.blocks[target]
+    {
+        // An empty loop jumps around forever, and the phis of the next
+        // block depend on which block it's entered from.
+        if !instructions.is_empty() || visited.contains(next) || has_phis(func, *next) {
+            break;
+        }
+        visited.push(*next);
+        target = *next;
+    }
+    target
+}
+
+fn has_phis(func: &Function, id: BlockId) -> bool {
+    matches!(
+        func.blocks[id].instructions.first(),
+        Some(Instruction::Phi(..))
+    )
+}
+
+/// A reachable block that jumps to a block that has no other predecessors.
+fn find_merge(func: &Function) -> Option<(BlockId, BlockId)> {
+    let predecessors = func.predecessors();
+    (0..func.blocks.len()).find_map(|id| match func.blocks[id].terminator {
+        Terminator::Jump(next) if next != id && next != ENTRY && predecessors[next] == [id] => {
+            Some((id, next))
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::passes::tests::lower_src;
+
+    #[test]
+    fn test_simplify_cfg() {
+        let mut module = lower_src(
+            "int main() { int a = 0; while (1) { a = a + 1; if (a > 2) break; } return a; }",
+        );
+        let func = &mut module.functions[0];
+        assert!(run(func));
+        assert!(!run(func));
+        assert_eq!(
+            func.to_string(),
+            "function main() -> int {
+bb0:
+  store a, 0
+  jump bb1
+bb1:
+  %0: int = load a
+  %1: int = add %0, 1
+  store a, %1
+  %2: int = load a
+  %3: int = gt %2, 2
+  branch %3, bb2, bb1
+bb2:
+  %4: int = load a
+  ret %4
+}"
+        );
+    }
+}
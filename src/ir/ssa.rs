@@ -0,0 +1,62 @@
+//! Translation out of SSA form. A phi becomes a copy from a new temporary
+//! that each predecessor assigns its value to at its end. The copies read
+//! the values before any phi of the block is assigned, so the phis still
+//! happen at the same time, and the critical edges don't need to be split
+//! because the new temporary is only read in the block of the phi.
+
+use super::{Function, Instruction, Operand};
+
+pub fn destruct(func: &mut Function) {
+    let reachable = func.reachable();
+    for id in 0..func.blocks.len() {
+        for index in 0..func.blocks[id].instructions.len() {
+            let Instruction::Phi(dest, incoming) = &func.blocks[id].instructions[index] else {
+                continue;
+            };
+            let (dest, incoming) = (*dest, incoming.clone());
+            let temp = func.new_temp(func.temps[dest.0]);
+            for (predecessor, value) in incoming {
+                if reachable[predecessor] {
+                    func.blocks[predecessor]
+                        .instructions
+                        .push(Instruction::Copy(temp, value));
+                }
+            }
+            func.blocks[id].instructions[index] = Instruction::Copy(dest, Operand::Temp(temp));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{passes::tests::lower_src, OptLevel, PassManager};
+
+    #[test]
+    fn test_destruct() {
+        let src = "int f(int n) { int i = 0; while (i < n) i = i + 1; return i; }";
+        let mut module = lower_src(src);
+        PassManager::new(OptLevel::O2).run(&mut module);
+        let func = &mut module.functions[0];
+        destruct(func);
+        assert_eq!(
+            func.to_string(),
+            "function f(n: int) -> int {
+bb0:
+  %0: int = param 0
+  %8: int = 0
+  jump bb1
+bb1:
+  %7: int = %8
+  %3: int = lt %7, %0
+  branch %3, bb2, bb3
+bb2:
+  %5: int = add %7, 1
+  %8: int = %5
+  jump bb1
+bb3:
+  ret %7
+}"
+        );
+    }
+}
@@ -0,0 +1,390 @@
+//! A toy C compiler that outputs ARM64 assembly.
+//!
+//! [`compile`] runs every phase on a source file. The phases can also be used
+//! on their own, e.g. [`tokenizer::Tokenizer`], [`parser::Parser`],
+//! [`semantic::SemanticAnalyzer`] and [`codegen::ARMCodegen`].
+
+pub mod cfg;
+pub mod codegen;
+pub mod emulator;
+mod error;
+pub mod fold;
+pub mod interpreter;
+pub mod ir;
+pub mod parser;
+pub mod semantic;
+#[cfg(test)]
+mod test_suite;
+pub mod tokenizer;
+
+pub use self::error::{
+    CompileError, CompileWarning, Diagnostic, Diagnostics, Severity, WarningKind,
+};
+use self::{
+    cfg::{Cfg, CfgBuilder},
+    codegen::{ARMCodegen, CodegenError, IrCodegen},
+    fold::ConstantFolder,
+    ir::{OptLevel, Pass, PassManager},
+    parser::Program,
+    semantic::SemanticAnalyzer,
+    tokenizer::Tokenizer,
+};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    /// Maximum number of parser errors to report before stopping, 0 means no limit.
+    pub error_limit: usize,
+    /// Whether to allow calls to undeclared functions like C89 does.
+    pub implicit_decl: bool,
+    /// Whether to fold the constant expressions before the codegen.
+    pub fold_constants: bool,
+    /// Whether to run the peephole optimizer on the assembly.
+    pub peephole: bool,
+    /// The warnings that aren't reported, e.g. with `-Wno-unused-variable`.
+    pub disabled_warnings: HashSet<WarningKind>,
+    /// Whether the warnings are reported as errors, like `-Werror`.
+    pub warnings_as_errors: bool,
+    /// `-O0` compiles the AST directly, the other levels optimize the IR.
+    pub opt_level: OptLevel,
+    /// The passes that don't run even if the level has them.
+    pub disabled_passes: HashSet<Pass>,
+    /// The passes that the IR is printed after.
+    pub print_after: HashSet<Pass>,
+}
+
+impl Default for CompileOptions {
+    fn default() -> CompileOptions {
+        CompileOptions {
+            error_limit: parser::DEFAULT_ERROR_LIMIT,
+            implicit_decl: false,
+            fold_constants: true,
+            peephole: true,
+            disabled_warnings: HashSet::new(),
+            warnings_as_errors: false,
+            opt_level: OptLevel::O0,
+            disabled_passes: HashSet::new(),
+            print_after: HashSet::new(),
+        }
+    }
+}
+
+impl CompileOptions {
+    /// The warnings that are enabled, or the errors if they are turned into
+    /// errors.
+    fn check_warnings<W: Into<CompileWarning>>(
+        &self,
+        warnings: Vec<W>,
+    ) -> Result<Vec<CompileWarning>, Diagnostics> {
+        let warnings: Vec<CompileWarning> = warnings
+            .into_iter()
+            .map(Into::into)
+            .filter(|warning| !self.disabled_warnings.contains(&warning.kind()))
+            .collect();
+        if self.warnings_as_errors && !warnings.is_empty() {
+            return Err(Diagnostics::from_errors(warnings));
+        }
+        Ok(warnings)
+    }
+}
+
+/// A program that passed the semantic analysis.
+#[derive(Debug)]
+pub struct Analysis {
+    pub program: Program,
+    pub warnings: Vec<CompileWarning>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+    /// The program as it was written, before the constants are folded.
+    pub program: Program,
+    pub warnings: Vec<CompileWarning>,
+    pub asm: String,
+    /// The IR printed after the passes of `print_after`.
+    pub ir_dumps: Vec<String>,
+}
+
+/// Tokenize, parse and analyze the program without generating any code.
+pub fn analyze(source: &str, options: &CompileOptions) -> Result<Analysis, Diagnostics> {
+    let token_stream = Tokenizer::new(source)
+        .tokenize_with_span()
+        .map_err(|err| Diagnostics::from_errors(vec![err]))?;
+
+    let parser = parser::Parser::new(token_stream).with_error_limit(options.error_limit);
+    let (program, errors, parser_warnings) = parser.parse_with_spans();
+    if !errors.is_empty() {
+        return Err(Diagnostics::from_errors(errors).with_program(program));
+    }
+
+    let mut warnings: Vec<CompileWarning> = parser_warnings
+        .into_iter()
+        .map(CompileWarning::from)
+        .collect();
+    match SemanticAnalyzer::new(options.implicit_decl).analyze(&program) {
+        Ok(semantic_warnings) => {
+            warnings.extend(semantic_warnings.into_iter().map(CompileWarning::from))
+        }
+        Err(err) => return Err(Diagnostics::from_errors(vec![err]).with_program(program)),
+    }
+    // The codegen reports the programs that the graphs can't be built for,
+    // e.g. a `break` outside of a loop.
+    if let Ok(cfgs) = CfgBuilder::new().build(&program) {
+        let cfg_warnings = cfgs.iter().flat_map(Cfg::warnings);
+        warnings.extend(cfg_warnings.map(CompileWarning::from));
+    }
+    let warnings = match options.check_warnings(warnings) {
+        Ok(warnings) => warnings,
+        Err(diagnostics) => return Err(diagnostics.with_program(program)),
+    };
+    Ok(Analysis { program, warnings })
+}
+
+/// Compile the program to ARM64 assembly.
+pub fn compile(source: &str, options: &CompileOptions) -> Result<Output, Diagnostics> {
+    let Analysis {
+        program,
+        mut warnings,
+    } = analyze(source, options)?;
+
+    let mut folded = program.clone();
+    if options.fold_constants {
+        let fold_warnings = ConstantFolder::new().fold(&mut folded);
+        match options.check_warnings(fold_warnings) {
+            Ok(fold_warnings) => warnings.extend(fold_warnings),
+            Err(diagnostics) => {
+                return Err(diagnostics.with_program(program).with_warnings(warnings))
+            }
+        }
+    }
+
+    match generate(&folded, options) {
+        Ok((asm, ir_dumps)) => Ok(Output {
+            program,
+            warnings,
+            asm,
+            ir_dumps,
+        }),
+        Err(err) => Err(Diagnostics::from_errors(vec![err])
+            .with_program(program)
+            .with_warnings(warnings)),
+    }
+}
+
+/// Generate the assembly and the IR dumps of the program.
+fn generate(
+    program: &Program,
+    options: &CompileOptions,
+) -> Result<(String, Vec<String>), CodegenError> {
+    if options.opt_level == OptLevel::O0 {
+        let codegen = ARMCodegen::new().with_peephole(options.peephole);
+        return Ok((codegen.generate(program)?, vec![]));
+    }
+    let mut module = ir::lower(program)?;
+    let ir_dumps = PassManager::new(options.opt_level)
+        .with_disabled_passes(&options.disabled_passes)
+        .with_print_after(options.print_after.clone())
+        .run(&mut module);
+    let codegen = IrCodegen::new().with_peephole(options.peephole);
+    Ok((codegen.generate(&module)?, ir_dumps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile() {
+        let output = compile("int main() { return 2; }", &CompileOptions::default()).unwrap();
+        assert!(output.asm.contains("_main:"));
+        assert!(output.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_opt_levels() {
+        let src = "int main() { int a = 2; return a * 3; }";
+        let output = compile(src, &CompileOptions::default()).unwrap();
+        assert!(output.ir_dumps.is_empty());
+
+        let options = CompileOptions {
+            opt_level: OptLevel::O2,
+            print_after: [Pass::ConstFold].into(),
+            ..CompileOptions::default()
+        };
+        let output = compile(src, &options).unwrap();
+        assert!(output.asm.contains("Lmain_bb0:"));
+        assert!(output.ir_dumps[0].starts_with("; IR after const-fold"));
+    }
+
+    #[test]
+    fn test_dead_code_elimination() {
+        let src = "int main() {
+            int a = 1;
+            int b = 2;
+            a = 3;
+            while (1) {
+                b = b + a;
+                if (b > 10) break;
+                b = b + 100;
+            }
+            if (b) return b;
+            else return 0;
+            return a;
+        }";
+        let run = |disabled: &[Pass]| {
+            let options = CompileOptions {
+                opt_level: OptLevel::O1,
+                disabled_passes: [Pass::Mem2Reg].iter().chain(disabled).copied().collect(),
+                ..CompileOptions::default()
+            };
+            let asm = compile(src, &options).unwrap().asm;
+            let instructions = asm
+                .lines()
+                .filter(|line| !line.ends_with(':') && !line.starts_with('.'))
+                .count();
+            let mut emulator = crate::emulator::Emulator::new(&asm).unwrap();
+            (emulator.run().unwrap(), instructions)
+        };
+        let (expected, before) = run(&[Pass::Dse, Pass::Dce, Pass::SimplifyCfg]);
+        let (value, after) = run(&[]);
+        assert_eq!(expected, 108);
+        assert_eq!(value, expected);
+        assert!(after < before);
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let options = CompileOptions::default();
+        let diagnostics = compile("int main() { return 1 }", &options).unwrap_err();
+        assert!(matches!(diagnostics.errors[..], [CompileError::Parser(..)]));
+        assert!(diagnostics.program.is_some());
+
+        let diagnostics = compile("int main() { return f(); }", &options).unwrap_err();
+        assert!(matches!(
+            diagnostics.errors[..],
+            [CompileError::Semantic(_)]
+        ));
+
+        let options = CompileOptions {
+            implicit_decl: true,
+            ..CompileOptions::default()
+        };
+        let diagnostics = compile("int main() { return f() % 1.0; }", &options).unwrap_err();
+        assert!(matches!(diagnostics.errors[..], [CompileError::Codegen(_)]));
+        assert_eq!(diagnostics.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_warning_options() {
+        let src = "int main() { int a; int b; return a; }";
+        let output = compile(src, &CompileOptions::default()).unwrap();
+        let kinds: Vec<_> = output.warnings.iter().map(CompileWarning::kind).collect();
+        assert_eq!(
+            kinds,
+            [WarningKind::Uninitialized, WarningKind::UnusedVariable]
+        );
+
+        let mut options = CompileOptions {
+            warnings_as_errors: true,
+            ..CompileOptions::default()
+        };
+        options
+            .disabled_warnings
+            .insert(WarningKind::UnusedVariable);
+        let diagnostics = compile(src, &options).unwrap_err();
+        assert!(matches!(
+            diagnostics.errors[..],
+            [CompileError::Warning(CompileWarning::Cfg(_))]
+        ));
+        let [ref diagnostic] = diagnostics.to_diagnostics()[..] else {
+            panic!("Expected a single diagnostic");
+        };
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.code, "cfg::Uninitialized");
+        assert!(diagnostic.message.ends_with("[-Werror=uninitialized]"));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let options = CompileOptions::default();
+        let diagnostics = compile("int main() {\n  return 1 }", &options).unwrap_err();
+        assert_eq!(
+            diagnostics.to_diagnostics(),
+            vec![Diagnostic {
+                severity: Severity::Error,
+                code: "parse::UnexpectedToken".into(),
+                message: "Expected token Semicolon but got RBrace".into(),
+                span: Some(tokenizer::Span { lo: 24, hi: 25 }),
+                notes: vec![],
+            }]
+        );
+
+        let diagnostics = compile("int main() { return 1 @ 2; }", &options).unwrap_err();
+        let [ref diagnostic] = diagnostics.to_diagnostics()[..] else {
+            panic!("Expected a single diagnostic");
+        };
+        assert_eq!(diagnostic.code, "tokenize::UnknownCharacter");
+        assert_eq!(diagnostic.span, Some(tokenizer::Span { lo: 22, hi: 23 }));
+
+        let options = CompileOptions {
+            implicit_decl: true,
+            ..CompileOptions::default()
+        };
+        let diagnostics = compile("int main() { return f() % 1.0; }", &options).unwrap_err();
+        let codes: Vec<_> = diagnostics
+            .to_diagnostics()
+            .into_iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.code))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                (Severity::Warning, "semantic::ImplicitDeclaration".into()),
+                (Severity::Error, "codegen::InvalidBinaryOperands".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_spans() {
+        let span = |lo, hi| Some(tokenizer::Span { lo, hi });
+        let spans = |src, options: &CompileOptions| -> Vec<_> {
+            compile(src, options)
+                .unwrap_err()
+                .to_diagnostics()
+                .into_iter()
+                .map(|diagnostic| (diagnostic.code, diagnostic.span))
+                .collect()
+        };
+        let options = CompileOptions::default();
+        assert_eq!(
+            spans("int main() { return f(); }", &options),
+            vec![("semantic::UndeclaredFunction".into(), span(20, 23))]
+        );
+        assert_eq!(
+            spans("int main() { break; }", &options),
+            vec![("codegen::NoLoopFoundForBreak".into(), span(13, 18))]
+        );
+        let werror = CompileOptions {
+            warnings_as_errors: true,
+            ..CompileOptions::default()
+        };
+        assert_eq!(
+            spans("int main() { return 1 / 0; }", &werror),
+            vec![("fold::DivisionByZero".into(), span(20, 25))]
+        );
+
+        let options = CompileOptions {
+            implicit_decl: true,
+            ..CompileOptions::default()
+        };
+        assert_eq!(
+            spans("int main() { int a; return a + f() % 1.0; }", &options),
+            vec![
+                ("semantic::ImplicitDeclaration".into(), span(31, 34)),
+                ("cfg::Uninitialized".into(), span(27, 28)),
+                ("codegen::InvalidBinaryOperands".into(), span(31, 40)),
+            ]
+        );
+    }
+}
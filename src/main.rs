@@ -1,136 +1,279 @@
-mod codegen;
-mod parser;
-mod tokenizer;
+mod driver;
 
+use c_compiler::{
+    emulator::Emulator,
+    interpreter::Interpreter,
+    ir::{OptLevel, Pass},
+    parser, CompileOptions, Diagnostics,
+};
 use clap::Parser;
+use driver::{
+    Driver, DriverError, DriverResult, Emit, ErrorFormat, LinkerKind, Stage, Toolchain,
+    ToolchainConfig, WarningFlag, DEFAULT_CONFIG_FILE,
+};
 use std::{
-    fs,
+    env,
+    ffi::OsString,
     io::{self, Write},
-    path::{Path, PathBuf},
-    process::Command,
+    path::PathBuf,
+    process,
 };
 
 /// A toy C compiler that outputs ARM64 assembly.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// C source file path to compile.
-    file: Option<PathBuf>,
+    /// Input files. C sources are compiled, assembly files are assembled and
+    /// the other files are passed to the linker.
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Write the output to this file, `-` means stdout.
+    #[arg(short)]
+    output: Option<PathBuf>,
+
+    /// Only preprocess the sources.
+    #[arg(short = 'E', group = "stage")]
+    preprocess: bool,
 
-    /// Whether to just do a dry run. Will only print the assembly to stdout.
-    #[arg(short, long, default_value_t = false)]
+    /// Only compile the sources to assembly.
+    #[arg(short = 'S', group = "stage")]
+    compile: bool,
+
+    /// Only compile and assemble the sources to object files.
+    #[arg(short = 'c', group = "stage")]
+    assemble: bool,
+
+    /// Only print the assembly to stdout, the same as `-S -o -`.
+    #[arg(long, default_value_t = false, group = "stage")]
     dry_run: bool,
 
     /// Whether to print the AST to stdout.
-    #[arg(short, long, default_value_t = false)]
+    #[arg(long, default_value_t = false)]
     ast: bool,
 
-    /// Whether to not print the assembly to stdout.
-    #[arg(short, long, default_value_t = false)]
-    no_asm: bool,
+    /// Extra outputs to write next to the assembly, e.g. `cfg-dot` writes the
+    /// control-flow graphs to `<input>.dot`.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    emit: Vec<Emit>,
+
+    /// Maximum number of parser errors to report before stopping, 0 means no limit.
+    #[arg(long = "ferror-limit", default_value_t = parser::DEFAULT_ERROR_LIMIT)]
+    error_limit: usize,
+
+    /// How to print the errors and the warnings.
+    #[arg(long, value_enum, default_value_t = ErrorFormat::Human)]
+    error_format: ErrorFormat,
+
+    /// Turn a warning on or off, e.g. `-Wno-unused-variable`, or report the
+    /// warnings as errors with `-Werror`. The warnings are on by default,
+    /// `-Wall` and `-Wextra` turn all of them on and the unknown warnings
+    /// are ignored with a note.
+    #[arg(short = 'W', value_name = "WARNING")]
+    warnings: Vec<WarningFlag>,
+
+    /// The code generation options of `cc`, e.g. `-fno-builtin`. They are
+    /// ignored with a note.
+    #[arg(short = 'f', value_name = "OPTION")]
+    code_options: Vec<String>,
+
+    /// Accepted like `cc` does, there is no debug info yet.
+    #[arg(short = 'g', default_value_t = false)]
+    debug_info: bool,
+
+    /// The C standard, accepted as `-std=c99` like `cc` does. The subset of C
+    /// that is supported is the same for every standard.
+    #[arg(long = "std", value_name = "STANDARD")]
+    standard: Option<String>,
+
+    /// The optimization level. `-O0` compiles the AST directly, `-O1` runs
+    /// the optimization passes on the IR once and `-O2` runs them until they
+    /// don't change anything.
+    #[arg(short = 'O', value_name = "LEVEL", default_value = "0")]
+    opt_level: OptLevel,
+
+    /// Print the IR to stderr after the pass, e.g. `--print-after=dce`.
+    #[arg(long, value_name = "PASS", value_delimiter = ',')]
+    print_after: Vec<Pass>,
+
+    /// Don't run the pass even if the optimization level has it.
+    #[arg(long, value_name = "PASS", value_delimiter = ',')]
+    disable_pass: Vec<Pass>,
+
+    /// Whether to allow calls to undeclared functions like C89 does. They are
+    /// reported as warnings instead of errors.
+    #[arg(long, default_value_t = false)]
+    implicit_decl: bool,
+
+    /// Whether to run the program with the interpreter instead of compiling
+    /// it. The value returned from `main` becomes the exit code.
+    #[arg(long, default_value_t = false, group = "stage")]
+    interpret: bool,
+
+    /// Whether to run the generated assembly with the built-in AArch64
+    /// emulator instead of assembling it. The value returned from `main`
+    /// becomes the exit code.
+    #[arg(long, default_value_t = false, group = "stage")]
+    emulate: bool,
+
+    /// The assembler command, `as` by default.
+    #[arg(long = "as", env = "C_COMPILER_AS", value_name = "COMMAND")]
+    assembler: Option<String>,
+
+    /// The linker command, `ld` or `cc` by default depending on `--linker`.
+    #[arg(long = "ld", env = "C_COMPILER_LD", value_name = "COMMAND")]
+    linker: Option<String>,
+
+    /// How to link the object files, `ld` by default.
+    #[arg(long = "linker", env = "C_COMPILER_LINKER", value_enum)]
+    linker_kind: Option<LinkerKind>,
+
+    /// The SDK to link against, `xcrun` finds it by default.
+    #[arg(long, env = "C_COMPILER_SYSROOT")]
+    sysroot: Option<PathBuf>,
+
+    /// The minimum macOS version of the executable, 13.0.0 by default.
+    #[arg(long, env = "C_COMPILER_MACOS_VERSION_MIN", value_name = "VERSION")]
+    macos_version_min: Option<String>,
+
+    /// The toolchain config file, `c_compiler.toml` is used if it exists.
+    #[arg(long, env = "C_COMPILER_CONFIG")]
+    config: Option<PathBuf>,
+}
+
+impl Args {
+    fn stage(&self) -> Stage {
+        if self.preprocess {
+            Stage::Preprocess
+        } else if self.compile || self.dry_run {
+            Stage::Compile
+        } else if self.assemble {
+            Stage::Assemble
+        } else {
+            Stage::Link
+        }
+    }
+
+    /// The toolchain from the options and the environment variables, and
+    /// then from the config file.
+    fn toolchain(&self) -> DriverResult<Toolchain> {
+        let options = ToolchainConfig {
+            assembler: self.assembler.clone(),
+            linker: self.linker.clone(),
+            linker_kind: self.linker_kind,
+            sysroot: self.sysroot.clone(),
+            macos_version_min: self.macos_version_min.clone(),
+        };
+        let config_file = match self.config {
+            Some(ref path) => Some(path.clone()),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.exists()),
+        };
+        let config = match config_file {
+            Some(path) => ToolchainConfig::load(&path)?,
+            None => ToolchainConfig::default(),
+        };
+        Ok(Toolchain::from(options.or(config)))
+    }
+}
+
+/// The single dash options of `cc` that clap reads as short options, e.g.
+/// `-std=c99` becomes `--std=c99` and `-ferror-limit=1` becomes
+/// `--ferror-limit=1`.
+fn cc_args(args: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    args.map(|arg| match arg.to_str() {
+        Some(arg) if arg.starts_with("-std=") || arg.starts_with("-ferror-limit=") => {
+            OsString::from(format!("-{}", arg))
+        }
+        _ => arg,
+    })
+    .collect()
 }
 
 fn main() {
-    let args = Args::parse();
-    let file_content = if let Some(ref file_path) = args.file {
-        fs::read_to_string(file_path).unwrap()
+    let args = Args::parse_from(cc_args(env::args_os()));
+    let mut options = CompileOptions {
+        error_limit: args.error_limit,
+        implicit_decl: args.implicit_decl,
+        opt_level: args.opt_level,
+        disabled_passes: args.disable_pass.iter().copied().collect(),
+        print_after: args.print_after.iter().copied().collect(),
+        ..CompileOptions::default()
+    };
+    for flag in &args.warnings {
+        flag.apply(&mut options);
+    }
+    for option in &args.code_options {
+        eprintln!("Note: ignoring the code generation option -f{}", option);
+    }
+    let output = if args.dry_run {
+        Some(PathBuf::from("-"))
     } else {
-        println!("No input file provided. Using a dummy program.\n");
-        "int main() { int a = 1; int b =3;  return a + b; }".into()
+        args.output.clone()
     };
-
-    let tokenizer = tokenizer::Tokenizer::new(&file_content);
-    let token_stream = match tokenizer.tokenize() {
-        Ok(tokens) => tokens,
-        Err(err) => {
-            eprintln!("Tokenizing phase has failed: {}", err);
-            std::process::exit(1);
+    let result = args.toolchain().and_then(|toolchain| {
+        let driver = Driver::new(options.clone())
+            .with_stage(args.stage())
+            .with_output(output)
+            .with_print_ast(args.ast)
+            .with_emit(args.emit.clone())
+            .with_error_format(args.error_format)
+            .with_toolchain(toolchain);
+        if args.interpret || args.emulate {
+            run(&args, &driver, &options)
+        } else {
+            driver.run(&args.files).map(|_| 0)
+        }
+    });
+    match result {
+        Ok(value) => process::exit(value),
+        Err(DriverError::CompilationFailed(_)) => {
+            // The diagnostics are already reported.
+            process::exit(1)
         }
-    };
-
-    let parser = parser::Parser::new(token_stream);
-    let program_ast = match parser.parse() {
-        Ok(ast) => ast,
         Err(err) => {
-            eprintln!("Parsing phase has failed: {}", err);
-            std::process::exit(1);
+            args.error_format.report_error(&err);
+            process::exit(err.exit_code())
         }
-    };
-
-    if args.ast {
-        println!("AST output:\n{:#?}\n", program_ast);
     }
+}
 
-    let codegen = codegen::ARMCodegen::new();
-    let asm = match codegen.generate(program_ast) {
-        Ok(asm) => asm,
-        Err(err) => {
-            eprintln!("Codegen phase has failed: {}", err);
-            std::process::exit(1);
-        }
+/// Run the program with the interpreter or the emulator and return its exit code.
+fn run(args: &Args, driver: &Driver, options: &CompileOptions) -> DriverResult<i32> {
+    let [ref file] = args.files[..] else {
+        return Err(DriverError::MultipleRunInputs);
+    };
+    let source = driver::read_source(file)?;
+    let report = |diagnostics: Diagnostics| {
+        driver.report(file, &source, &diagnostics);
+        DriverError::CompilationFailed(1)
     };
 
-    if !args.no_asm {
-        println!("Assembly output:\n{}", asm);
-    }
-
-    if args.dry_run {
-        // No need to generate the assembly if we're just doing a dry run.
-        return;
-    }
+    let (result, stdout) = if args.interpret {
+        let analysis = c_compiler::analyze(&source, options).map_err(report)?;
+        driver.print_ast(&analysis.program);
+        driver.report_warnings(file, &source, &analysis.warnings);
 
-    if let Some(ref path) = args.file {
-        // Write the assembly to a file if it was provided.
-        let mut asm_file = Path::new("obj").join(path);
-        asm_file.set_extension("s");
-        println!("Writing assembly to file: {:?}", asm_file);
+        let mut interpreter = Interpreter::new(&analysis.program);
+        let result = interpreter.run().map_err(DriverError::from);
+        (result, interpreter.stdout().to_string())
+    } else {
+        let output = c_compiler::compile(&source, options).map_err(report)?;
+        driver.print_ast(&output.program);
+        driver.report_warnings(file, &source, &output.warnings);
+        driver.print_ir(&output.ir_dumps);
 
-        let _ = fs::create_dir_all(asm_file.parent().unwrap());
-        fs::write(&asm_file, asm).expect("Couldn't write to file");
-        compile_asm(&asm_file);
-    }
-}
+        let mut emulator = Emulator::new(&output.asm)?;
+        let result = emulator.run().map_err(DriverError::from);
+        (result, emulator.stdout().to_string())
+    };
 
-fn compile_asm(asm_file: &Path) {
-    let obj_file = asm_file.with_extension("o");
-    println!("Writing object file to: {:?}", obj_file);
-    // as -o output.o output.s
-    let output = Command::new("as")
-        .args(["-o", obj_file.to_str().unwrap(), asm_file.to_str().unwrap()])
-        .output()
-        .expect("Failed to execute process");
-
-    io::stdout().write_all(&output.stdout).unwrap();
-    io::stdout().write_all(&output.stderr).unwrap();
-
-    let executable_file = obj_file.with_extension("");
-    println!("Writing executable file to: {:?}", executable_file);
-    // ld -macosx_version_min 13.0.0 -o output output.o -lSystem -syslibroot `xcrun -sdk macosx --show-sdk-path` -e _main -arch arm64
-    let sdk_path = Command::new("xcrun")
-        .args(["-sdk", "macosx", "--show-sdk-path"])
-        .output()
-        .expect("Failed to execute process");
-
-    let output = Command::new("ld")
-        .args([
-            "-macosx_version_min",
-            "13.0.0",
-            "-o",
-            executable_file.to_str().unwrap(),
-            obj_file.to_str().unwrap(),
-            "-lSystem",
-            "-syslibroot",
-            String::from_utf8_lossy(&sdk_path.stdout).trim(),
-            "-e",
-            "_main",
-            "-arch",
-            "arm64",
-        ])
-        .output()
-        .expect("Failed to execute process");
-
-    io::stdout().write_all(&output.stdout).unwrap();
-    io::stdout().write_all(&output.stderr).unwrap();
+    // The output is shown even if the program fails.
+    let mut out = io::stdout().lock();
+    out.write_all(stdout.as_bytes())
+        .map_err(|err| DriverError::Write(PathBuf::from("-"), err))?;
+    let value = result?;
+    let _ = writeln!(out, "Program exited with: {}", value);
+    Ok(value)
 }
 
 #[cfg(test)]
@@ -142,4 +285,31 @@ mod test {
         use clap::CommandFactory;
         Args::command().debug_assert();
     }
+
+    #[test]
+    fn test_cc_options() {
+        let args = [
+            "c_compiler",
+            "-std=c99",
+            "-g",
+            "-Wall",
+            "-Wextra",
+            "-fno-builtin",
+            "-ferror-limit=3",
+            "a.c",
+        ];
+        let args = Args::try_parse_from(cc_args(args.into_iter().map(OsString::from))).unwrap();
+        assert_eq!(args.standard.as_deref(), Some("c99"));
+        assert!(args.debug_info);
+        assert_eq!(args.warnings, [WarningFlag::All, WarningFlag::All]);
+        assert_eq!(args.code_options, ["no-builtin"]);
+        assert_eq!(args.error_limit, 3);
+        assert_eq!(args.files, [PathBuf::from("a.c")]);
+
+        // The other options still work.
+        let args = ["c_compiler", "--ferror-limit", "3", "-Wshadow", "a.c"];
+        let args = Args::try_parse_from(args).unwrap();
+        assert_eq!(args.error_limit, 3);
+        assert_eq!(args.warnings, [WarningFlag::Unknown("shadow".into())]);
+    }
 }
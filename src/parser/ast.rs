@@ -1,13 +1,19 @@
 /// The AST nodes for the parser.
 ///
 /// Current AST definition:
-/// program = Program(function_declaration)
-/// function_declaration = Function(string, block_item list) //string is the function name
+/// program = Program(function_declaration list)
+/// function_declaration = Function(type, string, param list, block_item list option)
+///                       //string is the function name
+///                       //block items are missing for the declarations without a body
+/// param = Param(type, string option)
 ///
 /// block_item = Statement(statement) | Declaration(declaration)
 ///
-/// declaration = Declare(string, exp option) //string is variable name
-///                                          //exp is optional initializer
+/// declaration = Declare(type, string, exp option) //string is variable name
+///                                                //exp is optional initializer
+///
+/// Typedef declarations don't appear in the AST. The parser resolves every
+/// typedef name to its underlying type while parsing.
 ///
 /// statement = Return(exp)
 ///           | Exp(exp)
@@ -21,32 +27,59 @@
 ///     | UnOp(unary_operator, exp)
 ///     | Constant(int)
 ///     | CondExp(exp, exp, exp) //the three expressions are the condition, 'if' expression and 'else' expression, respectively
+///     | FunCall(string, exp list)
 ///
-/// TODO: Implement spans.
+/// The functions, the declarations, the statements of the blocks and the
+/// expressions that the later phases report errors for have the span of their
+/// source.
+use std::fmt;
 
-#[derive(Debug, PartialEq)]
+use crate::tokenizer::Span;
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Program {
-    pub function: Function,
+    pub functions: Vec<Function>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Function {
     pub name: String,
-    pub body: Block,
+    pub return_ty: Type,
+    pub params: Vec<Param>,
+    /// `None` if this is only a declaration, e.g. `int foo(int a);`.
+    pub body: Option<Block>,
+    /// Whether it's declared with `inline`, a hint for the inliner.
+    pub is_inline: bool,
+    /// The span of the name.
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq)]
+impl Function {
+    pub fn is_definition(&self) -> bool {
+        self.body.is_some()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Param {
+    /// Parameter names can be omitted in function declarations.
+    pub name: Option<String>,
+    pub ty: Type,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Block {
     pub items: Vec<BlockItem>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum BlockItem {
-    Statement(Statement),
+    /// The statement and its span.
+    Statement(Statement, Span),
     Declaration(VarDecl),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Statement {
     Block(Block),
     Return(Box<Expr>),
@@ -55,21 +88,52 @@ pub enum Statement {
     While(Box<Expr>, Box<Statement>),   // condition, body
     DoWhile(Box<Statement>, Box<Expr>), // body, condition
     For(For),
-    Break,
-    Continue,
+    Break(Span),
+    Continue(Span),
     Null, // This is not the null keyword. It's a null statement, e.g `;`.
+    /// Placeholder for a statement that failed to parse. It only appears in
+    /// the partial AST returned with the parser errors.
+    Error,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct VarDecl {
     pub name: String,
-    pub size: VarSize,
+    pub ty: Type,
     pub initializer: Option<Expr>,
+    /// The span of the name.
+    pub span: Span,
 }
 
-impl VarDecl {
-    pub fn get_byte_size(&self) -> usize {
-        self.size.to_bytes()
+/// Types that a declaration can have after resolving the typedef names.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Type {
+    Int,
+    Float,
+    Double,
+}
+
+impl Type {
+    pub fn size(self) -> VarSize {
+        match self {
+            Type::Int | Type::Float => VarSize::Word,
+            Type::Double => VarSize::DoubleWord,
+        }
+    }
+
+    pub fn is_floating(self) -> bool {
+        matches!(self, Type::Float | Type::Double)
+    }
+
+    /// Type of a binary arithmetic operation after the usual arithmetic conversions.
+    pub fn common(self, other: Type) -> Type {
+        if self == Type::Double || other == Type::Double {
+            Type::Double
+        } else if self == Type::Float || other == Type::Float {
+            Type::Float
+        } else {
+            Type::Int
+        }
     }
 }
 
@@ -98,32 +162,50 @@ impl VarSize {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Expr {
-    Assignment(String, Box<Expr>),
-    Var(String),
+    Assignment(String, Box<Expr>, Span),
+    Var(String, Span),
     Constant(Constant),
-    UnaryOp(UnaryOp, Box<Expr>),
-    BinaryOp(BinaryOp, Box<Expr>, Box<Expr>),
+    UnaryOp(UnaryOp, Box<Expr>, Span),
+    BinaryOp(BinaryOp, Box<Expr>, Box<Expr>, Span),
     TernaryConditional(TernaryConditional),
+    FunctionCall(String, Vec<Expr>, Span), // function name, arguments
     Null, // This is not the null keyword. It's a null expression, e.g `;`.
 }
 
-#[derive(Debug, PartialEq)]
+impl Expr {
+    /// The span of the whole expression. The constants and the null
+    /// expressions don't have one.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Expr::Assignment(.., span)
+            | Expr::Var(_, span)
+            | Expr::UnaryOp(.., span)
+            | Expr::BinaryOp(.., span)
+            | Expr::FunctionCall(.., span) => Some(*span),
+            Expr::TernaryConditional(ternary) => Some(ternary.span),
+            Expr::Constant(_) | Expr::Null => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Conditional {
     pub condition: Expr,
     pub if_stmt: Box<Statement>,
     pub else_stmt: Option<Box<Statement>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct TernaryConditional {
     pub condition: Box<Expr>,
     pub if_expr: Box<Expr>,
     pub else_expr: Box<Expr>,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct For {
     pub init: Box<DeclOrExpr>,
     pub condition: Box<Expr>,
@@ -131,21 +213,23 @@ pub struct For {
     pub body: Box<Statement>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum DeclOrExpr {
     Declaration(VarDecl),
     Expression(Expr),
 }
 
 #[allow(dead_code)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Constant {
     String(String),
     Int(i32),
+    Float(f32),
+    Double(f64),
     Bool(bool),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum UnaryOp {
     Negation,
     LogicalNegation,
@@ -184,4 +268,149 @@ impl BinaryOp {
     pub fn is_short_circuiting_op(&self) -> bool {
         matches!(self, BinaryOp::And | BinaryOp::Or)
     }
+
+    pub fn is_comparison_op(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Equal
+                | BinaryOp::NotEqual
+                | BinaryOp::LessThan
+                | BinaryOp::LessThanOrEq
+                | BinaryOp::GreaterThan
+                | BinaryOp::GreaterThanOrEq
+        )
+    }
+
+    /// Whether the result has the common type of the operands, e.g. `+` but not `<`.
+    pub fn is_arithmetic_op(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Addition
+                | BinaryOp::Subtraction
+                | BinaryOp::Multiplication
+                | BinaryOp::Division
+                | BinaryOp::Modulo
+        )
+    }
+}
+
+impl UnaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            UnaryOp::Negation => "-",
+            UnaryOp::LogicalNegation => "!",
+            UnaryOp::BitwiseComplement => "~",
+        }
+    }
+}
+
+impl BinaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Addition => "+",
+            BinaryOp::Subtraction => "-",
+            BinaryOp::Multiplication => "*",
+            BinaryOp::Division => "/",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::LessThan => "<",
+            BinaryOp::LessThanOrEq => "<=",
+            BinaryOp::GreaterThan => ">",
+            BinaryOp::GreaterThanOrEq => ">=",
+            BinaryOp::Modulo => "%",
+            BinaryOp::BitwiseAnd => "&",
+            BinaryOp::BitwiseOr => "|",
+            BinaryOp::BitwiseXor => "^",
+            BinaryOp::BitwiseShiftLeft => "<<",
+            BinaryOp::BitwiseShiftRight => ">>",
+        }
+    }
+}
+
+// The expressions are printed as C. The operands that are operations
+// themselves are always in parentheses, so the precedence doesn't matter.
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Double => write!(f, "double"),
+        }
+    }
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constant::String(string) => write!(f, "{:?}", string),
+            Constant::Int(int) => write!(f, "{}", int),
+            Constant::Float(float) => write!(f, "{:?}f", float),
+            Constant::Double(double) => write!(f, "{:?}", double),
+            Constant::Bool(bool) => write!(f, "{}", bool),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Assignment(name, expr, _) => write!(f, "{} = {}", name, expr),
+            Expr::Var(name, _) => write!(f, "{}", name),
+            Expr::Constant(constant) => write!(f, "{}", constant),
+            Expr::UnaryOp(op, expr, _) => write!(f, "{}{}", op.symbol(), Operand(expr)),
+            Expr::BinaryOp(op, lhs, rhs, _) => {
+                write!(f, "{} {} {}", Operand(lhs), op.symbol(), Operand(rhs))
+            }
+            Expr::TernaryConditional(ternary) => write!(
+                f,
+                "{} ? {} : {}",
+                Operand(&ternary.condition),
+                Operand(&ternary.if_expr),
+                Operand(&ternary.else_expr)
+            ),
+            Expr::FunctionCall(name, args, _) => {
+                write!(f, "{}(", name)?;
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Null => Ok(()),
+        }
+    }
+}
+
+/// An operand of an operation, in parentheses if it's an operation or a
+/// negative constant, e.g. `a - (-1)`.
+struct Operand<'a>(&'a Expr);
+
+impl fmt::Display for Operand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Expr::Assignment(..)
+            | Expr::UnaryOp(..)
+            | Expr::BinaryOp(..)
+            | Expr::TernaryConditional(_) => write!(f, "({})", self.0),
+            Expr::Constant(constant) if constant.to_string().starts_with('-') => {
+                write!(f, "({})", constant)
+            }
+            expr => write!(f, "{}", expr),
+        }
+    }
+}
+
+impl fmt::Display for VarDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.ty, self.name)?;
+        if let Some(ref initializer) = self.initializer {
+            write!(f, " = {}", initializer)?;
+        }
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::tokenizer::{Keyword, TokenKind};
+use crate::tokenizer::{IntLiteralType, Keyword, Span, TokenKind};
 
 #[derive(Error, Debug)]
 pub enum ParserError {
@@ -19,9 +19,6 @@ pub enum ParserError {
     #[error("Expected identifier, but got EOF")]
     UnexpectedEOFForIdent,
 
-    #[error("Expected function name {0:?}, but got {1:?}")]
-    UnexpectedFunctionName(String, String),
-
     #[error("Expected block item, but got EOF")]
     UnexpectedEOFForBlockItem,
     #[error("Expected statement, but got EOF")]
@@ -33,6 +30,8 @@ pub enum ParserError {
     UnexpectedTokenForAtom(TokenKind),
     #[error("Expected atom, but got EOF")]
     UnexpectedEOFForAtom,
+    #[error("Integer literal {0} of type {1:?} doesn't fit in 32 bits")]
+    UnsupportedIntegerLiteral(u64, IntLiteralType),
 
     #[error("Expected unary operator, but got {0:?}")]
     UnexpectedTokenForUnaryOp(TokenKind),
@@ -45,4 +44,32 @@ pub enum ParserError {
     UnexpectedEOFForDeclaration,
     #[error("Expected declaration, but got {0:?}")]
     UnexpectedTokenForDeclaration(TokenKind),
+
+    #[error("Expected type name, but got {0:?}")]
+    UnexpectedTokenForType(TokenKind),
+    #[error("Expected type name, but got EOF")]
+    UnexpectedEOFForType,
+
+    #[error("Conflicting types for typedef {0:?}")]
+    ConflictingTypedef(String),
+    #[error("{0:?} redeclared as a different kind of symbol")]
+    RedeclaredAsDifferentSymbol(String),
+
+    #[error("Too many errors emitted, stopping now (error limit is {0})")]
+    TooManyErrors(usize),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParserWarning {
+    #[error("Integer literal {0} doesn't fit in an int, its value is {1}")]
+    IntegerOverflow(u64, i32, Span),
+}
+
+impl ParserWarning {
+    /// The span of the literal.
+    pub fn span(&self) -> Span {
+        match self {
+            ParserWarning::IntegerOverflow(_, _, span) => *span,
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     parser::{error::ParserError, BinaryOp, Expr, OpAssociativity, ParserResult, UnaryOp},
-    tokenizer::{Token, TokenKind},
+    tokenizer::{Span, Token, TokenKind},
 };
 
 impl Token {
@@ -14,12 +14,12 @@ impl Token {
     }
 
     #[allow(dead_code)]
-    pub fn get_unary_op(&self, expr: Expr) -> ParserResult<Expr> {
-        self.kind.get_unary_op(expr)
+    pub fn get_unary_op(&self, expr: Expr, span: Span) -> ParserResult<Expr> {
+        self.kind.get_unary_op(expr, span)
     }
 
-    pub fn get_bin_op(&self, lhs: Expr, rhs: Expr) -> ParserResult<Expr> {
-        self.kind.get_bin_op(lhs, rhs)
+    pub fn get_bin_op(&self, lhs: Expr, rhs: Expr, span: Span) -> ParserResult<Expr> {
+        self.kind.get_bin_op(lhs, rhs, span)
     }
 
     pub fn get_op_prec_assoc(&self) -> ParserResult<(u8, OpAssociativity)> {
@@ -59,7 +59,7 @@ impl TokenKind {
         )
     }
 
-    pub fn get_unary_op(&self, expr: Expr) -> ParserResult<Expr> {
+    pub fn get_unary_op(&self, expr: Expr, span: Span) -> ParserResult<Expr> {
         Ok(Expr::UnaryOp(
             match self {
                 TokenKind::Minus => UnaryOp::Negation,
@@ -68,10 +68,11 @@ impl TokenKind {
                 other => return Err(ParserError::UnexpectedTokenForUnaryOp(other.clone())),
             },
             Box::new(expr),
+            span,
         ))
     }
 
-    pub fn get_bin_op(&self, lhs: Expr, rhs: Expr) -> ParserResult<Expr> {
+    pub fn get_bin_op(&self, lhs: Expr, rhs: Expr, span: Span) -> ParserResult<Expr> {
         Ok(Expr::BinaryOp(
             match self {
                 TokenKind::Plus => BinaryOp::Addition,
@@ -96,6 +97,7 @@ impl TokenKind {
             },
             Box::new(lhs),
             Box::new(rhs),
+            span,
         ))
     }
 
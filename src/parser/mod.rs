@@ -1,37 +1,117 @@
 pub mod ast;
 mod error;
 mod helpers;
+mod scope;
 
-use crate::tokenizer::{Keyword, Token, TokenKind, TokenStream};
+use crate::tokenizer::{Keyword, Span, Token, TokenKind, TokenStream};
 pub use ast::*;
 
-use self::error::ParserError;
+pub use self::error::{ParserError, ParserWarning};
+use self::scope::ScopeStack;
 
 type ParserResult<T> = Result<T, ParserError>;
 
+/// Same as clang's default for `-ferror-limit`.
+pub const DEFAULT_ERROR_LIMIT: usize = 20;
+
+/// An error with the span of the last token that was consumed before it.
+pub type SpannedError = (ParserError, Option<Span>);
+
 pub struct Parser {
     token_stream: TokenStream,
+    scopes: ScopeStack,
+    errors: Vec<SpannedError>,
+    /// The warnings, e.g. the literals that are narrowed to int.
+    warnings: Vec<ParserWarning>,
+    /// Maximum number of errors to report before giving up, 0 means no limit.
+    error_limit: usize,
+    /// Whether we stopped parsing after hitting the error limit.
+    aborted: bool,
+    /// Number of tokens consumed so far, used to make sure that the error
+    /// recovery always makes progress.
+    consumed: usize,
+    last_span: Option<Span>,
 }
 
 impl Parser {
     pub fn new(token_stream: TokenStream) -> Parser {
-        Parser { token_stream }
+        Parser {
+            token_stream,
+            scopes: ScopeStack::new(),
+            errors: vec![],
+            warnings: vec![],
+            error_limit: DEFAULT_ERROR_LIMIT,
+            aborted: false,
+            consumed: 0,
+            last_span: None,
+        }
+    }
+
+    pub fn with_error_limit(mut self, error_limit: usize) -> Parser {
+        self.error_limit = error_limit;
+        self
+    }
+
+    /// Parse the whole translation unit. The parser recovers from the errors,
+    /// so all of them are returned together.
+    pub fn parse(self) -> Result<Program, Vec<ParserError>> {
+        let (program, errors) = self.parse_with_errors();
+        if errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(errors)
+        }
     }
 
-    pub fn parse(mut self) -> ParserResult<Program> {
-        self.parse_program()
+    /// Parse the whole translation unit and return the partial AST along with
+    /// the errors. The statements that failed to parse are `Statement::Error`.
+    pub fn parse_with_errors(self) -> (Program, Vec<ParserError>) {
+        let (program, errors, _) = self.parse_with_spans();
+        (program, errors.into_iter().map(|(err, _)| err).collect())
+    }
+
+    /// Same as `parse_with_errors`, but the errors come with the span of the
+    /// token where they were found, and the warnings are returned too.
+    pub fn parse_with_spans(mut self) -> (Program, Vec<SpannedError>, Vec<ParserWarning>) {
+        let program = self.parse_program();
+        (program, self.errors, self.warnings)
     }
 }
 
 impl Parser {
     fn next(&mut self) -> Option<Token> {
-        self.token_stream.tokens.next()
+        self.consumed += 1;
+        let token = self.token_stream.tokens.next();
+        if let Some(ref token) = token {
+            self.last_span = token.span;
+        }
+        token
     }
 
     fn peek(&mut self) -> Option<&Token> {
         self.token_stream.tokens.peek()
     }
 
+    /// Where the next token starts, for the span of the node that starts
+    /// with it.
+    fn next_lo(&mut self) -> usize {
+        let end = self.last_span.map_or(0, |span| span.hi);
+        match self.peek() {
+            Some(Token {
+                span: Some(span), ..
+            }) => span.lo,
+            _ => end,
+        }
+    }
+
+    /// The span from the start of a node to the end of the last token.
+    fn span_from(&self, lo: usize) -> Span {
+        Span {
+            lo,
+            hi: self.last_span.map_or(lo, |span| span.hi),
+        }
+    }
+
     fn peek_token_kind(&mut self, expected: TokenKind) -> ParserResult<&Token> {
         match self.peek() {
             Some(token) if token.kind == expected => Ok(token),
@@ -65,93 +145,313 @@ impl Parser {
             None => Err(ParserError::UnexpectedEOFForIdent),
         }
     }
-}
 
-impl Parser {
-    fn parse_program(&mut self) -> ParserResult<Program> {
-        let function = self.parse_function(Some("main"))?;
-        Ok(Program { function })
+    fn expect_type(&mut self) -> ParserResult<Type> {
+        match self.next() {
+            Some(token) => match token.kind {
+                TokenKind::Keyword(Keyword::Int) => Ok(Type::Int),
+                TokenKind::Keyword(Keyword::Float) => Ok(Type::Float),
+                TokenKind::Keyword(Keyword::Double) => Ok(Type::Double),
+                TokenKind::Identifier(ref ident) => self
+                    .scopes
+                    .lookup_typedef(ident)
+                    .ok_or(ParserError::UnexpectedTokenForType(token.kind)),
+                _ => Err(ParserError::UnexpectedTokenForType(token.kind)),
+            },
+            None => Err(ParserError::UnexpectedEOFForType),
+        }
     }
 
-    fn parse_function(&mut self, expected_name: Option<&str>) -> ParserResult<Function> {
-        self.expect_keyword(Keyword::Int)?;
-        let function_name = self.expect_ident()?;
+    /// Record an error and stop parsing if it hits the error limit.
+    fn report(&mut self, err: ParserError) {
+        if self.aborted {
+            return;
+        }
+
+        self.errors.push((err, self.last_span));
+        if self.error_limit != 0 && self.errors.len() >= self.error_limit {
+            self.errors
+                .push((ParserError::TooManyErrors(self.error_limit), self.last_span));
+            self.aborted = true;
+        }
+    }
+
+    /// Skip the tokens until the start of the next statement for the panic
+    /// mode error recovery. It stops after a `;`, or before a `}` or a
+    /// statement keyword that's not inside a nested block.
+    fn synchronize(&mut self, start: usize) {
+        let mut depth = 0;
+
+        while let Some(kind) = self.peek().map(|token| token.kind.clone()) {
+            // Always skip at least one token, otherwise we can get stuck.
+            let made_progress = self.consumed > start;
+            match kind {
+                TokenKind::Semicolon if depth == 0 => {
+                    let _ = self.next();
+                    return;
+                }
+                TokenKind::RBrace if depth == 0 && made_progress => return,
+                TokenKind::Keyword(
+                    Keyword::Return
+                    | Keyword::If
+                    | Keyword::While
+                    | Keyword::Do
+                    | Keyword::For
+                    | Keyword::Break
+                    | Keyword::Continue
+                    | Keyword::Typedef
+                    | Keyword::Int
+                    | Keyword::Float
+                    | Keyword::Double,
+                ) if depth == 0 && made_progress => return,
+                TokenKind::LBrace => depth += 1,
+                TokenKind::RBrace if depth > 0 => depth -= 1,
+                _ => {}
+            }
+            let _ = self.next();
+        }
+    }
+
+    /// Skip the tokens until the end of the current top level declaration.
+    fn synchronize_top_level(&mut self) {
+        let mut depth = 0;
 
-        if let Some(expected_name) = expected_name {
-            if function_name != expected_name {
-                return Err(ParserError::UnexpectedFunctionName(
-                    expected_name.to_string(),
-                    function_name,
-                ));
+        while let Some(token) = self.next() {
+            match token.kind {
+                TokenKind::Semicolon if depth == 0 => return,
+                TokenKind::LBrace => depth += 1,
+                TokenKind::RBrace if depth <= 1 => return,
+                TokenKind::RBrace => depth -= 1,
+                _ => {}
             }
         }
+    }
 
-        self.expect(TokenKind::LParen)?;
-        // TODO: Implement the arguments.
-        self.expect(TokenKind::RParen)?;
+    /// Check if the next token is a type name that starts a declaration.
+    fn peek_is_type(&mut self) -> bool {
+        let kind = match self.peek() {
+            Some(token) => token.kind.clone(),
+            None => return false,
+        };
+
+        match kind {
+            TokenKind::Keyword(Keyword::Int | Keyword::Float | Keyword::Double) => true,
+            TokenKind::Identifier(ident) => self.scopes.lookup_typedef(&ident).is_some(),
+            _ => false,
+        }
+    }
+}
+
+impl Parser {
+    fn parse_program(&mut self) -> Program {
+        let mut functions = vec![];
+
+        while self.peek().is_some() {
+            let result = if self
+                .peek_token_kind(TokenKind::Keyword(Keyword::Typedef))
+                .is_ok()
+            {
+                self.parse_typedef().map(|_| None)
+            } else {
+                self.parse_function().map(Some)
+            };
+
+            match result {
+                Ok(Some(function)) => functions.push(function),
+                Ok(None) => {}
+                Err(err) => {
+                    self.report(err);
+                    if self.aborted {
+                        break;
+                    }
+                    // Forget the scopes of the function that failed to parse.
+                    self.scopes.truncate(1);
+                    self.synchronize_top_level();
+                }
+            }
+        }
 
-        let body = self.parse_block()?;
+        Program { functions }
+    }
+
+    fn parse_function(&mut self) -> ParserResult<Function> {
+        let is_inline = self
+            .peek_token_kind(TokenKind::Keyword(Keyword::Inline))
+            .is_ok();
+        if is_inline {
+            let _ = self.next();
+        }
+        let return_ty = self.expect_type()?;
+        let lo = self.next_lo();
+        let function_name = self.expect_ident()?;
+        let span = self.span_from(lo);
+        // Function names share the namespace with the variables and typedef names.
+        self.scopes.declare_variable(&function_name)?;
+
+        // Parameters are visible in the function body, so they get their own
+        // scope that also encloses the body.
+        self.scopes.push();
+        let params = self.parse_params()?;
+
+        let body = if self.peek_token_kind(TokenKind::Semicolon).is_ok() {
+            // Advance the token stream.
+            let _ = self.next();
+            None
+        } else {
+            Some(self.parse_block()?)
+        };
+        self.scopes.pop();
 
         // TODO: Assert zero or one return statements for each branch.
 
         Ok(Function {
             name: function_name,
+            return_ty,
+            params,
             body,
+            is_inline,
+            span,
         })
     }
 
+    fn parse_params(&mut self) -> ParserResult<Vec<Param>> {
+        let mut params = vec![];
+
+        self.expect(TokenKind::LParen)?;
+        if self.peek_token_kind(TokenKind::RParen).is_err() {
+            loop {
+                let ty = self.expect_type()?;
+                let name = match self.peek() {
+                    Some(Token {
+                        kind: TokenKind::Identifier(_),
+                        ..
+                    }) => {
+                        let name = self.expect_ident()?;
+                        self.scopes.declare_variable(&name)?;
+                        Some(name)
+                    }
+                    _ => None,
+                };
+                params.push(Param { name, ty });
+
+                if self.peek_token_kind(TokenKind::Comma).is_err() {
+                    break;
+                }
+                // Advance the token stream.
+                let _ = self.next();
+            }
+        }
+        self.expect(TokenKind::RParen)?;
+
+        Ok(params)
+    }
+
     fn parse_block(&mut self) -> ParserResult<Block> {
         let mut items = vec![];
 
         self.expect(TokenKind::LBrace)?;
-        while self.peek_token_kind(TokenKind::RBrace).is_err() {
-            items.push(self.parse_block_item()?);
+        self.scopes.push();
+        loop {
+            match self.peek() {
+                Some(Token {
+                    kind: TokenKind::RBrace,
+                    ..
+                }) => break,
+                None => return Err(ParserError::UnexpectedEOF(TokenKind::RBrace)),
+                _ => {}
+            }
+
+            let start = self.consumed;
+            let lo = self.next_lo();
+            let scope_depth = self.scopes.depth();
+            let result = if self
+                .peek_token_kind(TokenKind::Keyword(Keyword::Typedef))
+                .is_ok()
+            {
+                // Typedefs only update the symbol table, they don't have an AST node.
+                self.parse_typedef().map(|_| None)
+            } else {
+                self.parse_block_item().map(Some)
+            };
+
+            match result {
+                Ok(Some(item)) => items.push(item),
+                Ok(None) => {}
+                // There is nothing to recover at the end of the file, let the
+                // caller report it.
+                Err(err) if self.aborted || self.peek().is_none() => return Err(err),
+                Err(err) => {
+                    self.report(err);
+                    if self.aborted {
+                        return Err(ParserError::TooManyErrors(self.error_limit));
+                    }
+                    self.scopes.truncate(scope_depth);
+                    self.synchronize(start);
+                    items.push(BlockItem::Statement(Statement::Error, self.span_from(lo)));
+                }
+            }
         }
+        self.scopes.pop();
         self.expect(TokenKind::RBrace)?;
 
         Ok(Block { items })
     }
 
     fn parse_block_item(&mut self) -> ParserResult<BlockItem> {
-        match self.peek() {
-            Some(token) => match &token.kind {
-                TokenKind::Keyword(Keyword::Int) => {
-                    let decl = self.parse_declaration()?;
-                    self.expect(TokenKind::Semicolon)?;
-                    Ok(BlockItem::Declaration(decl))
-                }
-                _ => self.parse_statement().map(BlockItem::Statement),
-            },
-            None => Err(ParserError::UnexpectedEOFForBlockItem),
+        if self.peek().is_none() {
+            return Err(ParserError::UnexpectedEOFForBlockItem);
+        }
+
+        if self.peek_is_type() {
+            let decl = self.parse_declaration()?;
+            self.expect(TokenKind::Semicolon)?;
+            Ok(BlockItem::Declaration(decl))
+        } else {
+            let lo = self.next_lo();
+            let stmt = self.parse_statement()?;
+            Ok(BlockItem::Statement(stmt, self.span_from(lo)))
         }
     }
 
     fn parse_declaration(&mut self) -> ParserResult<VarDecl> {
-        match self.peek() {
-            Some(token) => match &token.kind {
-                TokenKind::Keyword(Keyword::Int) => {
-                    // Advance the token stream.
-                    let _ = self.next();
-                    let ident = self.expect_ident()?;
-                    let initializer = if self.peek_token_kind(TokenKind::Semicolon).is_ok() {
-                        None
-                    } else {
-                        self.expect(TokenKind::Assignment)?;
-                        let expr = self.parse_expr()?;
-                        Some(expr)
-                    };
-                    Ok(VarDecl {
-                        name: ident,
-                        size: VarSize::Word,
-                        initializer,
-                    })
-                }
-                _ => Err(ParserError::UnexpectedTokenForDeclaration(
+        if !self.peek_is_type() {
+            return match self.peek() {
+                Some(token) => Err(ParserError::UnexpectedTokenForDeclaration(
                     token.kind.clone(),
                 )),
-            },
-            None => Err(ParserError::UnexpectedEOFForDeclaration),
+                None => Err(ParserError::UnexpectedEOFForDeclaration),
+            };
         }
+
+        let ty = self.expect_type()?;
+        let lo = self.next_lo();
+        let ident = self.expect_ident()?;
+        let span = self.span_from(lo);
+        // The variable is in scope starting from its initializer.
+        self.scopes.declare_variable(&ident)?;
+        let initializer = if self.peek_token_kind(TokenKind::Semicolon).is_ok() {
+            None
+        } else {
+            self.expect(TokenKind::Assignment)?;
+            let expr = self.parse_expr()?;
+            Some(expr)
+        };
+        Ok(VarDecl {
+            name: ident,
+            ty,
+            initializer,
+            span,
+        })
+    }
+
+    /// Parse a typedef declaration, e.g. `typedef int myint;`, and add the new
+    /// type name to the current scope.
+    fn parse_typedef(&mut self) -> ParserResult<()> {
+        self.expect_keyword(Keyword::Typedef)?;
+        let ty = self.expect_type()?;
+        let name = self.expect_ident()?;
+        self.expect(TokenKind::Semicolon)?;
+        self.scopes.declare_typedef(&name, ty)
     }
 
     fn parse_statement(&mut self) -> ParserResult<Statement> {
@@ -195,6 +495,8 @@ impl Parser {
                     // Advance the token stream.
                     let _ = self.next();
 
+                    // The for loop has its own scope for the declaration in it.
+                    self.scopes.push();
                     self.expect(TokenKind::LParen)?;
                     // Parse the initial expression
                     let init = Box::new(self.parse_decl_or_expr()?);
@@ -208,6 +510,7 @@ impl Parser {
 
                     // Parse the body
                     let body = Box::new(self.parse_statement()?);
+                    self.scopes.pop();
 
                     Ok(Statement::For(For {
                         init,
@@ -219,14 +522,16 @@ impl Parser {
                 TokenKind::Keyword(Keyword::Break) => {
                     // Advance the token stream.
                     let _ = self.next();
+                    let span = self.last_span.unwrap_or_default();
                     self.expect(TokenKind::Semicolon)?;
-                    Ok(Statement::Break)
+                    Ok(Statement::Break(span))
                 }
                 TokenKind::Keyword(Keyword::Continue) => {
                     // Advance the token stream.
                     let _ = self.next();
+                    let span = self.last_span.unwrap_or_default();
                     self.expect(TokenKind::Semicolon)?;
-                    Ok(Statement::Continue)
+                    Ok(Statement::Continue(span))
                 }
                 TokenKind::Semicolon => {
                     // Advance the token stream.
@@ -266,6 +571,7 @@ impl Parser {
     /// climbing method.
     /// https://en.wikipedia.org/wiki/Operator-precedence_parser#Precedence_climbing_method
     fn parse_expr_with_min_precedence(&mut self, min_precedence: u8) -> ParserResult<Expr> {
+        let lo = self.next_lo();
         let mut atom_lhs = self.parse_atom()?;
 
         loop {
@@ -295,7 +601,7 @@ impl Parser {
                         precedence
                     };
                     let atom_rhs = self.parse_expr_with_min_precedence(next_min_precedence)?;
-                    atom_lhs = op.get_bin_op(atom_lhs, atom_rhs)?;
+                    atom_lhs = op.get_bin_op(atom_lhs, atom_rhs, self.span_from(lo))?;
                 }
                 Some(ref cond) if cond.kind == TokenKind::QuestionMark => {
                     // This is a ternary operator.
@@ -315,6 +621,7 @@ impl Parser {
                         condition: Box::new(atom_lhs),
                         if_expr: Box::new(if_expr),
                         else_expr: Box::new(else_expr),
+                        span: self.span_from(lo),
                     })
                 }
                 _ => break,
@@ -325,18 +632,39 @@ impl Parser {
     }
 
     fn parse_atom(&mut self) -> ParserResult<Expr> {
+        let lo = self.next_lo();
         let token = self.next().ok_or(ParserError::UnexpectedEOFForAtom)?;
         match token.kind {
-            TokenKind::Integer(int_val) => Ok(Expr::Constant(Constant::Int(int_val))),
+            TokenKind::Integer(value, ty) => {
+                // Only int is supported for now, so the literals of the other
+                // types are narrowed to int. The ones that don't fit wrap
+                // around like a conversion to int does, with a warning.
+                let bits = u32::try_from(value)
+                    .map_err(|_| ParserError::UnsupportedIntegerLiteral(value, ty))?;
+                if i32::try_from(bits).is_err() {
+                    self.warnings.push(ParserWarning::IntegerOverflow(
+                        value,
+                        bits as i32,
+                        self.span_from(lo),
+                    ));
+                }
+                Ok(Expr::Constant(Constant::Int(bits as i32)))
+            }
+            TokenKind::Decimal(double_val) => Ok(Expr::Constant(Constant::Double(double_val))),
+            TokenKind::Float(float_val) => Ok(Expr::Constant(Constant::Float(float_val))),
             TokenKind::Identifier(ident) => {
-                if self.peek_token_kind(TokenKind::Assignment).is_ok() {
+                if self.peek_token_kind(TokenKind::LParen).is_ok() {
+                    // Function call
+                    let args = self.parse_args()?;
+                    Ok(Expr::FunctionCall(ident, args, self.span_from(lo)))
+                } else if self.peek_token_kind(TokenKind::Assignment).is_ok() {
                     // Assignment
                     self.expect(TokenKind::Assignment)?;
                     let expr = self.parse_expr()?;
-                    Ok(Expr::Assignment(ident, Box::new(expr)))
+                    Ok(Expr::Assignment(ident, Box::new(expr), self.span_from(lo)))
                 } else {
                     // Variable
-                    Ok(Expr::Var(ident))
+                    Ok(Expr::Var(ident, self.span_from(lo)))
                 }
             }
             TokenKind::LParen => {
@@ -344,10 +672,21 @@ impl Parser {
                 self.expect(TokenKind::RParen)?;
                 Ok(expr)
             }
+            // `-2147483648` is INT_MIN, the literal doesn't fit in an int but
+            // its negation does.
+            TokenKind::Minus
+                if matches!(
+                    self.peek(),
+                    Some(Token { kind: TokenKind::Integer(value, _), .. }) if *value == 1 << 31
+                ) =>
+            {
+                let _ = self.next();
+                Ok(Expr::Constant(Constant::Int(i32::MIN)))
+            }
             // Unary ops
             op if op.is_unary_op() => {
                 let expr = self.parse_atom()?;
-                op.get_unary_op(expr)
+                op.get_unary_op(expr, self.span_from(lo))
             }
             // Warning for binary ops
             other if other.is_binary_op() => Err(ParserError::UnexpectedBinOpForAtom(other)),
@@ -355,6 +694,27 @@ impl Parser {
         }
     }
 
+    fn parse_args(&mut self) -> ParserResult<Vec<Expr>> {
+        let mut args = vec![];
+
+        self.expect(TokenKind::LParen)?;
+        if self.peek_token_kind(TokenKind::RParen).is_err() {
+            loop {
+                // Arguments can't be null expressions, so don't use `parse_expr` here.
+                args.push(self.parse_expr_with_min_precedence(1)?);
+
+                if self.peek_token_kind(TokenKind::Comma).is_err() {
+                    break;
+                }
+                // Advance the token stream.
+                let _ = self.next();
+            }
+        }
+        self.expect(TokenKind::RParen)?;
+
+        Ok(args)
+    }
+
     fn parse_if(&mut self) -> ParserResult<Conditional> {
         self.expect(TokenKind::Keyword(Keyword::If))?;
         self.expect(TokenKind::LParen)?;
@@ -382,9 +742,8 @@ impl Parser {
     }
 
     fn parse_decl_or_expr(&mut self) -> ParserResult<DeclOrExpr> {
-        let decl = self.parse_declaration();
-        if let Ok(decl) = decl {
-            return Ok(DeclOrExpr::Declaration(decl));
+        if self.peek_is_type() {
+            return Ok(DeclOrExpr::Declaration(self.parse_declaration()?));
         }
 
         let expr = self.parse_expr()?;
@@ -395,94 +754,266 @@ impl Parser {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tokenizer::{tests::ALLOWED_STAGES, Tokenizer};
+    use crate::tokenizer::Tokenizer;
+
+    fn parse_source(src: &str) -> ParserResult<Program> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        // Only the first error is interesting for most of the tests.
+        Parser::new(token_stream)
+            .parse()
+            .map_err(|mut errors| errors.remove(0))
+    }
+
+    fn span(lo: usize, hi: usize) -> Span {
+        Span { lo, hi }
+    }
+
+    fn parse_with_errors(src: &str, error_limit: usize) -> (Program, Vec<ParserError>) {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        Parser::new(token_stream)
+            .with_error_limit(error_limit)
+            .parse_with_errors()
+    }
 
     #[test]
-    fn test_parser_valid_files() {
-        use std::fs;
-
-        let test_dirs = fs::read_dir("tests/").unwrap();
-        for dir in test_dirs {
-            let dir = dir.unwrap();
-            if !dir.file_type().unwrap().is_dir() {
-                // Skip the files.
-                continue;
-            }
+    fn test_parse_file_scope_typedef() {
+        let program = parse_source("typedef int myint; int main() { myint a = 2; return a; }");
+        let program = program.unwrap();
+        assert_eq!(
+            program.functions[0].body.as_ref().unwrap().items[0],
+            BlockItem::Declaration(VarDecl {
+                name: "a".into(),
+                ty: Type::Int,
+                initializer: Some(Expr::Constant(Constant::Int(2))),
+                span: span(38, 39),
+            })
+        );
+    }
 
-            if !ALLOWED_STAGES.contains(&dir.file_name().to_str().unwrap()) {
-                // Skip the invalid directory.
-                continue;
-            }
+    #[test]
+    fn test_parse_typedef_of_typedef() {
+        let program = parse_source("typedef int a; typedef a b; b main() { b c; return 0; }");
+        assert!(program.is_ok());
+    }
+
+    #[test]
+    fn test_parse_block_scope_typedef() {
+        let program = parse_source("int main() { { typedef int t; t a = 1; } t b = 2; }");
+        assert!(program.is_err());
+    }
 
-            let mut path = dir.path();
-            path.push("valid");
-            let test_files = fs::read_dir(path).unwrap();
+    #[test]
+    fn test_parse_variable_shadows_typedef() {
+        let program = parse_source("typedef int t; int main() { int t = 3; t; return t; }");
+        let program = program.unwrap();
+        assert_eq!(
+            program.functions[0].body.as_ref().unwrap().items[1],
+            BlockItem::Statement(
+                Statement::Expression(Box::new(Expr::Var("t".into(), span(39, 40)))),
+                span(39, 41)
+            )
+        );
+    }
 
-            for file in test_files {
-                let file = file.unwrap();
-                let path = file.path();
-                let path = path.to_str().unwrap();
+    #[test]
+    fn test_parse_typedef_visible_after_shadowing_scope() {
+        let program =
+            parse_source("typedef int t; int main() { { int t = 3; t = 4; } t a = 1; return a; }");
+        assert!(program.is_ok());
+    }
 
-                println!("Testing parser for: {}", path);
-                let contents = fs::read_to_string(path).unwrap();
-                let tokenizer = Tokenizer::new(&contents);
-                let token_stream = tokenizer.tokenize();
+    #[test]
+    fn test_parse_typedef_redeclared_as_variable() {
+        let program = parse_source("int main() { typedef int t; int t; return 0; }");
+        assert!(matches!(
+            program,
+            Err(ParserError::RedeclaredAsDifferentSymbol(_))
+        ));
+    }
 
-                if path.contains("skip_on_failure") && token_stream.is_err() {
-                    println!("Failed but skipping: {}", path);
-                    continue;
-                }
+    #[test]
+    fn test_parse_typedef_in_for_init() {
+        let program =
+            parse_source("typedef int t; int main() { for (t i = 0; i < 3; i = i + 1) {} }");
+        assert!(program.is_ok());
+    }
 
-                let parser = Parser::new(token_stream.unwrap());
-                let program_ast = parser.parse();
-                assert!(!program_ast.is_err());
+    #[test]
+    fn test_parse_function_declaration() {
+        let program = parse_source("int add(int, int b); int main() { return add(1, 2); }");
+        let program = program.unwrap();
+        assert_eq!(
+            program.functions[0],
+            Function {
+                name: "add".into(),
+                return_ty: Type::Int,
+                params: vec![
+                    Param {
+                        name: None,
+                        ty: Type::Int
+                    },
+                    Param {
+                        name: Some("b".into()),
+                        ty: Type::Int
+                    },
+                ],
+                body: None,
+                is_inline: false,
+                span: span(4, 7),
             }
-        }
+        );
+        assert_eq!(
+            program.functions[1].body.as_ref().unwrap().items[0],
+            BlockItem::Statement(
+                Statement::Return(Box::new(Expr::FunctionCall(
+                    "add".into(),
+                    vec![
+                        Expr::Constant(Constant::Int(1)),
+                        Expr::Constant(Constant::Int(2))
+                    ],
+                    span(41, 50)
+                ))),
+                span(34, 51)
+            )
+        );
     }
 
     #[test]
-    fn test_parser_invalid_files() {
-        use std::fs;
-
-        let test_dirs = fs::read_dir("tests/").unwrap();
-        for dir in test_dirs {
-            let dir = dir.unwrap();
-            if !dir.file_type().unwrap().is_dir() {
-                // Skip the files.
-                continue;
-            }
+    fn test_parse_inline_function() {
+        let program = parse_source("inline int get(int a) { return a; } int main() { return 0; }");
+        let program = program.unwrap();
+        assert!(program.functions[0].is_inline);
+        assert!(!program.functions[1].is_inline);
+        assert!(parse_source("int main() { inline int a = 1; return a; }").is_err());
+    }
 
-            if !ALLOWED_STAGES.contains(&dir.file_name().to_str().unwrap()) {
-                // Skip the invalid directory.
-                continue;
-            }
+    #[test]
+    fn test_parse_nested_function_calls() {
+        let program = parse_source("int main() { return f(g(), 1 + 2, a ? b : c); }");
+        assert!(program.is_ok());
+    }
 
-            let mut path = dir.path();
-            path.push("invalid");
-            let test_files = fs::read_dir(path).unwrap();
+    #[test]
+    fn test_parse_trailing_comma_in_call() {
+        let program = parse_source("int main() { return f(1,); }");
+        assert!(program.is_err());
+    }
 
-            for file in test_files {
-                let file = file.unwrap();
-                let path = file.path();
-                let path = path.to_str().unwrap();
+    #[test]
+    fn test_parse_param_shadows_typedef() {
+        let program = parse_source("typedef int t; int f(t t) { return t; }");
+        assert!(program.is_ok());
+    }
 
-                println!("Testing parser for: {}", path);
-                let contents = fs::read_to_string(path).unwrap();
-                let tokenizer = Tokenizer::new(&contents);
-                let token_stream = tokenizer.tokenize().unwrap();
+    #[test]
+    fn test_parse_literal_narrowed_to_int() {
+        let returned = |src: &str| {
+            let token_stream = Tokenizer::new(src).tokenize_with_span().unwrap();
+            let (program, errors, warnings) = Parser::new(token_stream).parse_with_spans();
+            assert!(errors.is_empty());
+            let BlockItem::Statement(Statement::Return(expr), _) =
+                &program.functions[0].body.as_ref().unwrap().items[0]
+            else {
+                panic!("Expected a return");
+            };
+            (*expr.clone(), warnings)
+        };
+        assert_eq!(
+            returned("int main() { return 0x7FFFFFFFu; }"),
+            (Expr::Constant(Constant::Int(i32::MAX)), vec![])
+        );
+
+        // The literal of INT_MIN fits after the negation.
+        assert_eq!(
+            returned("int main() { return -2147483648; }"),
+            (Expr::Constant(Constant::Int(i32::MIN)), vec![])
+        );
+
+        // The others wrap around with a warning.
+        assert_eq!(
+            returned("int main() { return 0xFFFFFFFF; }"),
+            (
+                Expr::Constant(Constant::Int(-1)),
+                vec![ParserWarning::IntegerOverflow(0xFFFFFFFF, -1, span(20, 30))]
+            )
+        );
+        let (expr, warnings) = returned("int main() { return 2147483648; }");
+        assert_eq!(expr, Expr::Constant(Constant::Int(i32::MIN)));
+        assert_eq!(warnings.len(), 1);
+    }
 
-                let parser = Parser::new(token_stream);
-                let program_ast = parser.parse();
+    #[test]
+    fn test_parse_64_bit_literal() {
+        let program = parse_source("int main() { return 0x100000000; }");
+        assert!(matches!(
+            program,
+            Err(ParserError::UnsupportedIntegerLiteral(0x100000000, _))
+        ));
+    }
 
-                if let Ok(program_ast) = program_ast {
-                    let codegen = crate::codegen::ARMCodegen::new();
+    #[test]
+    fn test_parse_reports_multiple_errors() {
+        let (program, errors) = parse_with_errors(
+            "int main() { int a = +; a = 1 +; return a; } int f() { return 1 }",
+            0,
+        );
+        assert_eq!(errors.len(), 3);
+        // The statements without errors are still in the AST.
+        let body = program.functions[0].body.as_ref().unwrap();
+        assert_eq!(
+            body.items,
+            vec![
+                BlockItem::Statement(Statement::Error, span(13, 23)),
+                BlockItem::Statement(Statement::Error, span(24, 32)),
+                BlockItem::Statement(
+                    Statement::Return(Box::new(Expr::Var("a".into(), span(40, 41)))),
+                    span(33, 42)
+                ),
+            ]
+        );
+    }
 
-                    let asm = codegen.generate(program_ast);
-                    assert!(asm.is_err());
-                    return;
-                }
-                assert!(program_ast.is_err());
-            }
-        }
+    #[test]
+    fn test_parse_recovers_on_statement_keyword() {
+        let (program, errors) = parse_with_errors("int main() { a = ) return 2; }", 0);
+        assert_eq!(errors.len(), 1);
+        let body = program.functions[0].body.as_ref().unwrap();
+        assert_eq!(
+            body.items[1],
+            BlockItem::Statement(
+                Statement::Return(Box::new(Expr::Constant(Constant::Int(2)))),
+                span(19, 28)
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_recovers_after_nested_block() {
+        let (program, errors) = parse_with_errors("int main() { if (1 +) { a; b; } return 3; }", 0);
+        assert_eq!(errors.len(), 1);
+        let body = program.functions[0].body.as_ref().unwrap();
+        assert_eq!(body.items.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_recovers_at_top_level() {
+        let (program, errors) =
+            parse_with_errors("int f(1) { return 0; } int main() { return 0; }", 0);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(program.functions.len(), 1);
+        assert_eq!(program.functions[0].name, "main");
+    }
+
+    #[test]
+    fn test_parse_unexpected_eof_reported_once() {
+        let (_, errors) = parse_with_errors("int main() { { return 0;", 0);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_error_limit() {
+        let (_, errors) = parse_with_errors("int main() { +; +; +; +; +; }", 2);
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[2], ParserError::TooManyErrors(2)));
     }
 }
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use crate::parser::{error::ParserError, ParserResult, Type};
+
+/// What an ordinary identifier refers to inside a scope.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Symbol {
+    Typedef(Type),
+    Variable,
+}
+
+/// Symbol table that the parser uses to tell typedef names apart from the
+/// other identifiers. C grammar is ambiguous without it, e.g. `a;` can be an
+/// expression statement or a declaration depending on what `a` is.
+#[derive(Debug)]
+pub struct ScopeStack {
+    scopes: Vec<HashMap<String, Symbol>>,
+}
+
+impl ScopeStack {
+    /// Create a new scope stack with the file scope in it.
+    pub fn new() -> ScopeStack {
+        ScopeStack {
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    pub fn push(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop(&mut self) {
+        // The file scope always stays.
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Drop the scopes above the given depth, used when recovering from errors.
+    pub fn truncate(&mut self, depth: usize) {
+        self.scopes.truncate(depth.max(1));
+    }
+
+    /// Declare a typedef name in the innermost scope.
+    pub fn declare_typedef(&mut self, name: &str, ty: Type) -> ParserResult<()> {
+        match self.current().get(name) {
+            // Redefining a typedef to the same type is allowed since C11.
+            Some(Symbol::Typedef(existing)) if *existing == ty => Ok(()),
+            Some(Symbol::Typedef(_)) => Err(ParserError::ConflictingTypedef(name.to_string())),
+            Some(Symbol::Variable) => {
+                Err(ParserError::RedeclaredAsDifferentSymbol(name.to_string()))
+            }
+            None => {
+                self.current_mut()
+                    .insert(name.to_string(), Symbol::Typedef(ty));
+                Ok(())
+            }
+        }
+    }
+
+    /// Declare a variable in the innermost scope. This hides the typedef names
+    /// with the same name from the outer scopes.
+    pub fn declare_variable(&mut self, name: &str) -> ParserResult<()> {
+        if let Some(Symbol::Typedef(_)) = self.current().get(name) {
+            return Err(ParserError::RedeclaredAsDifferentSymbol(name.to_string()));
+        }
+        // Redeclared variables are reported later in the codegen.
+        self.current_mut()
+            .insert(name.to_string(), Symbol::Variable);
+        Ok(())
+    }
+
+    /// Find the type that the identifier names, if it's a typedef name in the
+    /// closest scope that declares it.
+    pub fn lookup_typedef(&self, name: &str) -> Option<Type> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .and_then(|symbol| match symbol {
+                Symbol::Typedef(ty) => Some(*ty),
+                Symbol::Variable => None,
+            })
+    }
+
+    fn current(&self) -> &HashMap<String, Symbol> {
+        self.scopes.last().unwrap()
+    }
+
+    fn current_mut(&mut self) -> &mut HashMap<String, Symbol> {
+        self.scopes.last_mut().unwrap()
+    }
+}
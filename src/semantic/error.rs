@@ -0,0 +1,53 @@
+use thiserror::Error;
+
+use crate::tokenizer::Span;
+
+#[derive(Error, Debug)]
+pub enum SemanticError {
+    #[error("Conflicting types for function {0:?}")]
+    ConflictingTypes(String, Span),
+    #[error("Redefinition of function {0:?}")]
+    FunctionRedefined(String, Span),
+    #[error("Call to undeclared function {0:?}")]
+    UndeclaredFunction(String, Span),
+    #[error("Function {name:?} expects {expected} arguments, but {got} were given")]
+    ArgumentCountMismatch {
+        name: String,
+        expected: usize,
+        got: usize,
+        span: Span,
+    },
+    #[error("Parameter name omitted in the definition of function {0:?}")]
+    UnnamedParameter(String, Span),
+    #[error("Function {0:?} takes more than {1} int or floating point arguments")]
+    TooManyArguments(String, usize, Span),
+}
+
+impl SemanticError {
+    /// The span of the function or the call.
+    pub fn span(&self) -> Span {
+        match self {
+            SemanticError::ConflictingTypes(_, span)
+            | SemanticError::FunctionRedefined(_, span)
+            | SemanticError::UndeclaredFunction(_, span)
+            | SemanticError::ArgumentCountMismatch { span, .. }
+            | SemanticError::UnnamedParameter(_, span)
+            | SemanticError::TooManyArguments(_, _, span) => *span,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SemanticWarning {
+    #[error("Implicit declaration of function {0:?}")]
+    ImplicitDeclaration(String, Span),
+}
+
+impl SemanticWarning {
+    /// The span of the call.
+    pub fn span(&self) -> Span {
+        match self {
+            SemanticWarning::ImplicitDeclaration(_, span) => *span,
+        }
+    }
+}
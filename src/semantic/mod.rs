@@ -0,0 +1,295 @@
+mod error;
+
+pub use self::error::{SemanticError, SemanticWarning};
+use crate::{codegen::MAX_REG_ARGS, parser::ast::*, tokenizer::Span};
+use std::collections::HashMap;
+
+type SemanticResult<T> = Result<T, SemanticError>;
+
+/// Checks the parts of the program that are valid syntactically but not
+/// semantically, e.g. calling a function with the wrong number of arguments.
+pub struct SemanticAnalyzer {
+    /// Whether to accept calls to undeclared functions like C89 does.
+    allow_implicit_decl: bool,
+    functions: HashMap<String, FunctionSignature>,
+    warnings: Vec<SemanticWarning>,
+}
+
+#[derive(Debug, PartialEq)]
+struct FunctionSignature {
+    return_ty: Type,
+    /// `None` if the function is implicitly declared, in which case the
+    /// parameters are unknown.
+    params: Option<Vec<Type>>,
+    defined: bool,
+}
+
+impl FunctionSignature {
+    fn is_compatible_with(&self, other: &FunctionSignature) -> bool {
+        if self.return_ty != other.return_ty {
+            return false;
+        }
+
+        match (&self.params, &other.params) {
+            (Some(params), Some(other_params)) => params == other_params,
+            _ => true,
+        }
+    }
+}
+
+impl SemanticAnalyzer {
+    pub fn new(allow_implicit_decl: bool) -> SemanticAnalyzer {
+        SemanticAnalyzer {
+            allow_implicit_decl,
+            functions: HashMap::new(),
+            warnings: vec![],
+        }
+    }
+
+    /// Analyze the program and return the warnings if there are no errors.
+    pub fn analyze(mut self, program: &Program) -> SemanticResult<Vec<SemanticWarning>> {
+        for func in &program.functions {
+            self.analyze_function(func)?;
+        }
+        Ok(self.warnings)
+    }
+
+    fn analyze_function(&mut self, func: &Function) -> SemanticResult<()> {
+        // The arguments are only passed in the registers, so all the engines
+        // reject the same programs.
+        let floating = func.params.iter().filter(|param| param.ty.is_floating());
+        let floating = floating.count();
+        if floating > MAX_REG_ARGS || func.params.len() - floating > MAX_REG_ARGS {
+            return Err(SemanticError::TooManyArguments(
+                func.name.clone(),
+                MAX_REG_ARGS,
+                func.span,
+            ));
+        }
+
+        let signature = FunctionSignature {
+            return_ty: func.return_ty,
+            params: Some(func.params.iter().map(|param| param.ty).collect()),
+            defined: func.is_definition(),
+        };
+
+        if let Some(existing) = self.functions.get(&func.name) {
+            if !existing.is_compatible_with(&signature) {
+                return Err(SemanticError::ConflictingTypes(
+                    func.name.clone(),
+                    func.span,
+                ));
+            }
+            if existing.defined && signature.defined {
+                return Err(SemanticError::FunctionRedefined(
+                    func.name.clone(),
+                    func.span,
+                ));
+            }
+        }
+
+        let Some(body) = &func.body else {
+            // A declaration after the definition shouldn't forget the definition.
+            self.functions
+                .entry(func.name.clone())
+                .and_modify(|existing| existing.params = signature.params.clone())
+                .or_insert(signature);
+            return Ok(());
+        };
+
+        if func.params.iter().any(|param| param.name.is_none()) {
+            return Err(SemanticError::UnnamedParameter(
+                func.name.clone(),
+                func.span,
+            ));
+        }
+
+        // Insert the signature before the body so that recursive calls work.
+        self.functions.insert(func.name.clone(), signature);
+        self.analyze_block(body)
+    }
+
+    fn analyze_block(&mut self, block: &Block) -> SemanticResult<()> {
+        for item in &block.items {
+            match item {
+                BlockItem::Statement(stmt, _) => self.analyze_statement(stmt)?,
+                BlockItem::Declaration(decl) => self.analyze_declaration(decl)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn analyze_declaration(&mut self, decl: &VarDecl) -> SemanticResult<()> {
+        if let Some(initializer) = &decl.initializer {
+            self.analyze_expr(initializer)?;
+        }
+        Ok(())
+    }
+
+    fn analyze_statement(&mut self, stmt: &Statement) -> SemanticResult<()> {
+        match stmt {
+            Statement::Block(block) => self.analyze_block(block)?,
+            Statement::Return(expr) | Statement::Expression(expr) => self.analyze_expr(expr)?,
+            Statement::Conditional(cond) => {
+                self.analyze_expr(&cond.condition)?;
+                self.analyze_statement(&cond.if_stmt)?;
+                if let Some(else_stmt) = &cond.else_stmt {
+                    self.analyze_statement(else_stmt)?;
+                }
+            }
+            Statement::While(expr, stmt) | Statement::DoWhile(stmt, expr) => {
+                self.analyze_expr(expr)?;
+                self.analyze_statement(stmt)?;
+            }
+            Statement::For(for_loop) => {
+                match for_loop.init.as_ref() {
+                    DeclOrExpr::Declaration(decl) => self.analyze_declaration(decl)?,
+                    DeclOrExpr::Expression(expr) => self.analyze_expr(expr)?,
+                }
+                self.analyze_expr(&for_loop.condition)?;
+                self.analyze_expr(&for_loop.increment)?;
+                self.analyze_statement(&for_loop.body)?;
+            }
+            Statement::Break(_) | Statement::Continue(_) | Statement::Null | Statement::Error => {}
+        }
+        Ok(())
+    }
+
+    fn analyze_expr(&mut self, expr: &Expr) -> SemanticResult<()> {
+        match expr {
+            Expr::Assignment(_, expr, _) | Expr::UnaryOp(_, expr, _) => self.analyze_expr(expr)?,
+            Expr::BinaryOp(_, lhs, rhs, _) => {
+                self.analyze_expr(lhs)?;
+                self.analyze_expr(rhs)?;
+            }
+            Expr::TernaryConditional(ternary) => {
+                self.analyze_expr(&ternary.condition)?;
+                self.analyze_expr(&ternary.if_expr)?;
+                self.analyze_expr(&ternary.else_expr)?;
+            }
+            Expr::FunctionCall(name, args, span) => {
+                for arg in args {
+                    self.analyze_expr(arg)?;
+                }
+                self.analyze_call(name, args.len(), *span)?;
+            }
+            Expr::Var(..) | Expr::Constant(_) | Expr::Null => {}
+        }
+        Ok(())
+    }
+
+    fn analyze_call(&mut self, name: &str, arg_count: usize, span: Span) -> SemanticResult<()> {
+        match self.functions.get(name) {
+            Some(FunctionSignature {
+                params: Some(params),
+                ..
+            }) if params.len() != arg_count => Err(SemanticError::ArgumentCountMismatch {
+                name: name.to_string(),
+                expected: params.len(),
+                got: arg_count,
+                span,
+            }),
+            // The types of the arguments aren't known here, so the implicitly
+            // declared functions take at most as many as either register file.
+            Some(FunctionSignature { params: None, .. }) if arg_count > MAX_REG_ARGS => Err(
+                SemanticError::TooManyArguments(name.to_string(), MAX_REG_ARGS, span),
+            ),
+            Some(_) => Ok(()),
+            None if self.allow_implicit_decl => {
+                // C89 implicitly declares it as `int name()`.
+                self.warnings
+                    .push(SemanticWarning::ImplicitDeclaration(name.to_string(), span));
+                self.functions.insert(
+                    name.to_string(),
+                    FunctionSignature {
+                        return_ty: Type::Int,
+                        params: None,
+                        defined: false,
+                    },
+                );
+                if arg_count > MAX_REG_ARGS {
+                    return Err(SemanticError::TooManyArguments(
+                        name.to_string(),
+                        MAX_REG_ARGS,
+                        span,
+                    ));
+                }
+                Ok(())
+            }
+            None => Err(SemanticError::UndeclaredFunction(name.to_string(), span)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::Parser, tokenizer::Tokenizer};
+
+    fn analyze(src: &str, allow_implicit_decl: bool) -> SemanticResult<Vec<SemanticWarning>> {
+        let token_stream = Tokenizer::new(src).tokenize().unwrap();
+        let program = Parser::new(token_stream).parse().unwrap();
+        SemanticAnalyzer::new(allow_implicit_decl).analyze(&program)
+    }
+
+    macro_rules! semantic_ok_test {
+        ($name:ident, $src:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(analyze($src, false).unwrap(), vec![]);
+            }
+        };
+    }
+
+    macro_rules! semantic_err_test {
+        ($name:ident, $src:expr => $err:pat) => {
+            #[test]
+            fn $name() {
+                assert!(matches!(analyze($src, false), Err($err)));
+            }
+        };
+    }
+
+    semantic_ok_test!(
+        test_forward_declaration,
+        "int f(int a); int main() { return f(1); } int f(int a) { return a; }"
+    );
+    semantic_ok_test!(
+        test_recursive_call,
+        "int f(int a) { return a ? f(a - 1) : 0; }"
+    );
+    semantic_ok_test!(
+        test_repeated_declaration,
+        "int f(int); int f(int a); int f(int b) { return b; } int f(int);"
+    );
+    semantic_err_test!(test_undeclared_function, "int main() { return f(); } int f() { return 1; }" => SemanticError::UndeclaredFunction(..));
+    semantic_err_test!(test_argument_count_mismatch, "int f(int a, int b); int main() { return f(1); }" => SemanticError::ArgumentCountMismatch { expected: 2, got: 1, .. });
+    semantic_err_test!(test_conflicting_declarations, "int f(int a); int f(int a, int b);" => SemanticError::ConflictingTypes(..));
+    semantic_err_test!(test_duplicate_definition, "int f() { return 1; } int f() { return 2; }" => SemanticError::FunctionRedefined(..));
+    semantic_err_test!(
+        test_too_many_parameters,
+        "int f(int a, int b, int c, int d, int e, int f, int g, int h, int i);" => SemanticError::TooManyArguments(_, 8, _)
+    );
+    semantic_ok_test!(
+        test_parameters_of_both_classes,
+        "int f(int a, int b, int c, int d, int e, int f, int g, int h, double i) { return a; }"
+    );
+    semantic_err_test!(test_unnamed_parameter_in_definition, "int f(int) { return 1; }" => SemanticError::UnnamedParameter(..));
+
+    #[test]
+    fn test_implicit_declaration_warning() {
+        let warnings = analyze("int main() { f(1); return f(2, 3); }", true).unwrap();
+        // Only the first call declares it.
+        assert_eq!(
+            warnings,
+            vec![SemanticWarning::ImplicitDeclaration(
+                "f".into(),
+                Span { lo: 13, hi: 17 }
+            )]
+        );
+        assert!(matches!(
+            analyze("int main() { return f(1, 2, 3, 4, 5, 6, 7, 8, 9); }", true),
+            Err(SemanticError::TooManyArguments(..))
+        ));
+    }
+}
@@ -0,0 +1,359 @@
+//! Runs the C programs in `test_programs/stage_*/{valid,invalid}` through
+//! every phase of the compiler and checks the results against the
+//! expectations written at the top of each file:
+//!
+//! ```c
+//! // exit: 3          the exit code of the program, required in `valid`
+//! // stdout: Hello    a line that the program writes, can be repeated
+//! // error: parse     the phase that should fail, required in `invalid`
+//! // xfail: reason    a known failure that doesn't fail the suite
+//! ```
+//!
+//! The phases are `tokenize`, `parse`, `semantic` and `codegen`. Valid
+//! programs are executed both by the interpreter and by the emulator, and
+//! they are also compiled with `-O2` and executed by the emulator.
+//!
+//! The generated assembly of the valid programs is compared with the `.s`
+//! files next to them, see [`snapshot`].
+//!
+//! `TEST_STAGES=stage_1,stage_3` runs only the given stages, and
+//! `TEST_FILTER=name` runs only the files whose paths contain `name`.
+
+mod snapshot;
+
+use std::{
+    env, fmt, fs, panic,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    emulator::Emulator, interpreter::Interpreter, ir::OptLevel, CompileError, CompileOptions,
+    Output,
+};
+
+const PROGRAMS_DIR: &str = "test_programs";
+
+/// A phase of the compiler that can reject a program.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Phase {
+    Tokenize,
+    Parse,
+    Semantic,
+    Codegen,
+}
+
+impl Phase {
+    fn parse(phase: &str) -> Option<Phase> {
+        match phase {
+            "tokenize" => Some(Phase::Tokenize),
+            "parse" => Some(Phase::Parse),
+            "semantic" => Some(Phase::Semantic),
+            "codegen" => Some(Phase::Codegen),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phase = match self {
+            Phase::Tokenize => "tokenize",
+            Phase::Parse => "parse",
+            Phase::Semantic => "semantic",
+            Phase::Codegen => "codegen",
+        };
+        write!(f, "{}", phase)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Expected {
+    Run { exit_code: u8, stdout: String },
+    Error(Phase),
+}
+
+#[derive(Debug, PartialEq)]
+struct TestCase {
+    path: PathBuf,
+    expected: Expected,
+    /// Why the test is expected to fail, if it's a known failure.
+    xfail: Option<String>,
+}
+
+impl TestCase {
+    /// Read the expectations from the comments at the top of the file.
+    fn parse(path: &Path, src: &str) -> Result<TestCase, String> {
+        let mut exit_code = None;
+        let mut stdout = String::new();
+        let mut error = None;
+        let mut xfail = None;
+
+        let comments = src
+            .lines()
+            .map(str::trim)
+            .take_while(|line| line.is_empty() || line.starts_with("//"))
+            .filter_map(|line| line.strip_prefix("//"));
+        for comment in comments {
+            let Some((key, value)) = comment.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "exit" => {
+                    // Negative values are accepted, the exit code is the lowest byte.
+                    let value: i32 = value
+                        .parse()
+                        .map_err(|_| format!("Invalid exit code {:?}", value))?;
+                    exit_code = Some(value as u8);
+                }
+                "stdout" => {
+                    stdout.push_str(value);
+                    stdout.push('\n');
+                }
+                "error" => {
+                    error = Some(
+                        Phase::parse(value).ok_or_else(|| format!("Unknown phase {:?}", value))?,
+                    );
+                }
+                "xfail" => xfail = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        let is_valid = path.parent().and_then(Path::file_name) == Some("valid".as_ref());
+        let expected = match (exit_code, error) {
+            (Some(exit_code), None) if is_valid => Expected::Run { exit_code, stdout },
+            (None, Some(phase)) if !is_valid => Expected::Error(phase),
+            _ if is_valid => return Err("Valid programs need an `exit` annotation".into()),
+            _ => return Err("Invalid programs need an `error` annotation".into()),
+        };
+
+        Ok(TestCase {
+            path: path.to_path_buf(),
+            expected,
+            xfail,
+        })
+    }
+
+    /// Run the program and return why it didn't meet the expectations.
+    fn run(&self, src: &str) -> Result<(), String> {
+        let result = compile(src, &CompileOptions::default());
+        let output = match (&self.expected, result) {
+            (Expected::Error(expected), Err((phase, _))) if *expected == phase => return Ok(()),
+            (Expected::Error(expected), Err((phase, err))) => {
+                return Err(format!(
+                    "Expected {} error, got {} error: {}",
+                    expected, phase, err
+                ))
+            }
+            (Expected::Error(expected), Ok(_)) => {
+                return Err(format!("Expected {} error, but it compiled", expected))
+            }
+            (Expected::Run { .. }, Err((phase, err))) => {
+                return Err(format!("Failed to compile, {} error: {}", phase, err))
+            }
+            (Expected::Run { .. }, Ok(output)) => output,
+        };
+        let Expected::Run { exit_code, stdout } = &self.expected else {
+            unreachable!()
+        };
+        snapshot::check(&self.path, &output.asm)?;
+
+        let check = |engine: &str, value: i32, output: &str| {
+            if value as u8 != *exit_code {
+                return Err(format!(
+                    "{}: expected exit code {}, got {}",
+                    engine, exit_code, value as u8
+                ));
+            }
+            if output != stdout {
+                return Err(format!(
+                    "{}: expected stdout {:?}, got {:?}",
+                    engine, stdout, output
+                ));
+            }
+            Ok(())
+        };
+
+        let mut interpreter = Interpreter::new(&output.program);
+        let value = interpreter
+            .run()
+            .map_err(|err| format!("interpreter: {}", err))?;
+        check("interpreter", value, interpreter.stdout())?;
+
+        let run = |engine: &str, asm: &str| {
+            let mut emulator = Emulator::new(asm).map_err(|err| format!("{}: {}", engine, err))?;
+            let value = emulator
+                .run()
+                .map_err(|err| format!("{}: {}", engine, err))?;
+            check(engine, value, emulator.stdout())
+        };
+        run("emulator", &output.asm)?;
+
+        // The optimized code has to do the same.
+        let options = CompileOptions {
+            opt_level: OptLevel::O2,
+            ..CompileOptions::default()
+        };
+        let output = compile(src, &options).map_err(|(phase, err)| {
+            format!("Failed to compile with -O2, {} error: {}", phase, err)
+        })?;
+        run("emulator -O2", &output.asm)
+    }
+}
+
+/// Compile the program like the CLI does, and return the phase that failed.
+fn compile(src: &str, options: &CompileOptions) -> Result<Output, (Phase, String)> {
+    crate::compile(src, options).map_err(|diagnostics| {
+        let err = &diagnostics.errors[0];
+        let phase = match err {
+            CompileError::Tokenizer(..) => Phase::Tokenize,
+            CompileError::Parser(..) => Phase::Parse,
+            CompileError::Semantic(_) => Phase::Semantic,
+            CompileError::Codegen(_) => Phase::Codegen,
+            // The tests don't use `-Werror`.
+            CompileError::Warning(_) => Phase::Semantic,
+        };
+        (phase, err.to_string())
+    })
+}
+
+#[derive(Debug, Default)]
+struct StageReport {
+    passed: usize,
+    /// Known failures that still fail.
+    xfailed: usize,
+    failures: Vec<String>,
+}
+
+/// Find the stage directories, sorted by their numbers.
+fn stages() -> Vec<PathBuf> {
+    let only = env::var("TEST_STAGES").ok();
+    let mut stages: Vec<PathBuf> = fs::read_dir(PROGRAMS_DIR)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            name.starts_with("stage_")
+                && only
+                    .as_ref()
+                    .is_none_or(|only| only.split(',').any(|stage| stage.trim() == name))
+        })
+        .collect();
+    stages.sort_by_key(|path| {
+        path.file_name().unwrap().to_str().unwrap()["stage_".len()..]
+            .parse::<u32>()
+            .unwrap_or(u32::MAX)
+    });
+    stages
+}
+
+/// The C files in the directory that match `TEST_FILTER`, sorted.
+fn c_files(dir: &Path) -> Vec<PathBuf> {
+    let filter = env::var("TEST_FILTER").ok();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut files: Vec<PathBuf> = entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "c"))
+        .filter(|path| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| path.to_str().unwrap().contains(filter.as_str()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Run the test case and return whether it's a known failure.
+fn run_case(case: &TestCase, src: &str) -> Result<bool, String> {
+    // A panic fails only this program, not the whole suite.
+    let result =
+        panic::catch_unwind(|| case.run(src)).unwrap_or_else(|_| Err("Panicked".to_string()));
+    match (&case.xfail, result) {
+        (None, result) => result.map(|_| false),
+        (Some(_), Err(_)) => Ok(true),
+        (Some(reason), Ok(_)) => Err(format!(
+            "Passed but it's marked as a known failure ({})",
+            reason
+        )),
+    }
+}
+
+fn run_stage(stage: &Path) -> StageReport {
+    let mut report = StageReport::default();
+
+    for kind in ["valid", "invalid"] {
+        for path in c_files(&stage.join(kind)) {
+            let src = fs::read_to_string(&path).unwrap();
+            let result = TestCase::parse(&path, &src).and_then(|case| run_case(&case, &src));
+            match result {
+                Ok(false) => report.passed += 1,
+                Ok(true) => report.xfailed += 1,
+                Err(reason) => report
+                    .failures
+                    .push(format!("{}: {}", path.display(), reason)),
+            }
+        }
+    }
+
+    report
+}
+
+#[test]
+fn test_stages() {
+    let mut failures = vec![];
+    for stage in stages() {
+        let report = run_stage(&stage);
+        println!(
+            "{}: {} passed, {} failed, {} known failures",
+            stage.file_name().unwrap().to_str().unwrap(),
+            report.passed,
+            report.failures.len(),
+            report.xfailed
+        );
+        failures.extend(report.failures);
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} test programs failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_annotations() {
+    let case = TestCase::parse(
+        Path::new("test_programs/stage_1/valid/a.c"),
+        "// exit: -1\n// stdout: Hi\n// stdout: there\nint main() { return -1; }",
+    )
+    .unwrap();
+    assert_eq!(
+        case.expected,
+        Expected::Run {
+            exit_code: 255,
+            stdout: "Hi\nthere\n".into()
+        }
+    );
+
+    let case = TestCase::parse(
+        Path::new("test_programs/stage_1/invalid/a.c"),
+        "// error: parse\n// xfail: not yet\nint main( {",
+    )
+    .unwrap();
+    assert_eq!(case.expected, Expected::Error(Phase::Parse));
+    assert_eq!(case.xfail.as_deref(), Some("not yet"));
+
+    // The annotation has to match the directory.
+    assert!(TestCase::parse(
+        Path::new("test_programs/stage_1/valid/a.c"),
+        "// error: parse"
+    )
+    .is_err());
+    assert!(TestCase::parse(Path::new("test_programs/stage_1/invalid/a.c"), "// exit: 0").is_err());
+}
@@ -0,0 +1,65 @@
+//! Golden files of the generated assembly. Each valid program has its
+//! expected assembly next to it with the `.s` extension, so the changes in the
+//! codegen show up in the diffs. `BLESS=1` writes the current output to them.
+
+use std::{env, fs, path::Path};
+
+/// Number of the lines to show around the first difference.
+const CONTEXT_LINES: usize = 3;
+
+/// Compare the assembly with the snapshot of the program, or update the
+/// snapshot if blessing is enabled.
+pub fn check(program_path: &Path, asm: &str) -> Result<(), String> {
+    let snapshot_path = program_path.with_extension("s");
+    let asm = with_trailing_newline(asm);
+
+    if env::var("BLESS").is_ok_and(|bless| bless != "0") {
+        return fs::write(&snapshot_path, asm)
+            .map_err(|err| format!("Couldn't write {}: {}", snapshot_path.display(), err));
+    }
+
+    let Ok(expected) = fs::read_to_string(&snapshot_path) else {
+        return Err(format!(
+            "Missing snapshot {}, run with BLESS=1 to create it",
+            snapshot_path.display()
+        ));
+    };
+    if expected == asm {
+        return Ok(());
+    }
+    Err(format!(
+        "Assembly doesn't match {}, run with BLESS=1 to update it\n{}",
+        snapshot_path.display(),
+        diff(&expected, &asm)
+    ))
+}
+
+/// Files end with a newline.
+fn with_trailing_newline(asm: &str) -> String {
+    let mut asm = asm.to_string();
+    if !asm.ends_with('\n') {
+        asm.push('\n');
+    }
+    asm
+}
+
+/// Show the lines around the first difference.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let first = expected
+        .iter()
+        .zip(&actual)
+        .position(|(expected, actual)| expected != actual)
+        .unwrap_or(expected.len().min(actual.len()));
+
+    let start = first.saturating_sub(CONTEXT_LINES);
+    let mut result = format!("First difference at line {}:\n", first + 1);
+    for line in &expected[start..(first + CONTEXT_LINES + 1).min(expected.len())] {
+        result.push_str(&format!("-{}\n", line));
+    }
+    for line in &actual[start..(first + CONTEXT_LINES + 1).min(actual.len())] {
+        result.push_str(&format!("+{}\n", line));
+    }
+    result
+}
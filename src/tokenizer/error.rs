@@ -14,9 +14,20 @@ pub enum TokenizerError {
     #[error("Unexpected EOF")]
     UnexpectedEOF,
 
+    #[error("Exponent has no digits")]
+    MissingExponentDigits,
+
+    #[error("No digits after the {0:?} prefix")]
+    MissingPrefixDigits(String),
+    #[error("Invalid digit '{0}' in base {1} integer literal")]
+    InvalidDigit(char, u32),
+    #[error("Invalid suffix {0:?} on integer literal")]
+    InvalidIntegerSuffix(String),
+    #[error("Invalid suffix {0:?} on floating point literal")]
+    InvalidFloatSuffix(String),
+    #[error("Integer literal {0:?} in base {1} is too large for any integer type")]
+    IntegerLiteralTooLarge(String, u32),
+
     #[error("Failed to parse a floating point number")]
     FloatParseFailed(#[from] std::num::ParseFloatError),
-
-    #[error("Failed to parse an int number")]
-    IntParseFailed(#[from] std::num::ParseIntError),
 }
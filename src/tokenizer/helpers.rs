@@ -1,6 +1,6 @@
 use crate::tokenizer::{
     error::TokenizerError,
-    token::{Keyword, TokenKind},
+    token::{IntLiteralType, Keyword, TokenKind},
     TokenizerResult,
 };
 
@@ -50,34 +50,133 @@ pub fn tokenize_ident_or_keyword(data: &str) -> TokenizerResult<(TokenKind, usiz
         "continue" => Ok((TokenKind::Keyword(Keyword::Continue), bytes_read)),
         "break" => Ok((TokenKind::Keyword(Keyword::Break), bytes_read)),
         "for" => Ok((TokenKind::Keyword(Keyword::For), bytes_read)),
+        "typedef" => Ok((TokenKind::Keyword(Keyword::Typedef), bytes_read)),
+        "float" => Ok((TokenKind::Keyword(Keyword::Float), bytes_read)),
+        "double" => Ok((TokenKind::Keyword(Keyword::Double), bytes_read)),
+        "inline" => Ok((TokenKind::Keyword(Keyword::Inline), bytes_read)),
         _ => Ok((TokenKind::Identifier(got.to_string()), bytes_read)),
     }
 }
 
 /// Tokenize an integer or a float.
 pub fn tokenize_integer(data: &str) -> TokenizerResult<(TokenKind, usize)> {
+    for (prefix, radix) in [("0x", 16), ("0X", 16), ("0b", 2), ("0B", 2)] {
+        if data.starts_with(prefix) {
+            return tokenize_prefixed_integer(data, prefix, radix);
+        }
+    }
+
+    let digits = |from: usize| {
+        take_while(&data[from..], |c| c.is_ascii_digit()).map_or(from, |(_, len)| from + len)
+    };
+
     let mut seen_dot = false;
+    let mut seen_exponent = false;
+    let mut bytes_read = digits(0);
 
-    let (decimal, bytes_read) = take_while(data, |c| {
-        if c.is_ascii_digit() {
-            true
-        } else if c == '.' {
-            if !seen_dot {
-                seen_dot = true;
-                true
-            } else {
-                false
+    if data[bytes_read..].starts_with('.') {
+        seen_dot = true;
+        bytes_read = digits(bytes_read + 1);
+    }
+
+    if data[bytes_read..].starts_with(['e', 'E']) {
+        let mut exponent_start = bytes_read + 1;
+        if data[exponent_start..].starts_with(['+', '-']) {
+            exponent_start += 1;
+        }
+        let exponent_end = digits(exponent_start);
+        if exponent_end == exponent_start {
+            return Err(TokenizerError::MissingExponentDigits);
+        }
+        seen_exponent = true;
+        bytes_read = exponent_end;
+    }
+
+    let decimal = &data[..bytes_read];
+    let (suffix, suffix_len) = take_suffix(&data[bytes_read..]);
+    if seen_dot || seen_exponent {
+        // `f` suffix makes it a float, otherwise it's a double. We don't have
+        // long doubles, so `l` suffix is a double too.
+        match suffix {
+            "f" | "F" => {
+                let n: f32 = decimal.parse()?;
+                Ok((TokenKind::Float(n), bytes_read + suffix_len))
             }
-        } else {
-            false
+            "" | "l" | "L" => {
+                let n: f64 = decimal.parse()?;
+                Ok((TokenKind::Decimal(n), bytes_read + suffix_len))
+            }
+            other => Err(TokenizerError::InvalidFloatSuffix(other.to_string())),
         }
-    })?;
+    } else if decimal.len() > 1 && decimal.starts_with('0') {
+        // Leading zero makes it an octal number.
+        let literal = integer_literal(&decimal[1..], 8, suffix)?;
+        Ok((literal, bytes_read + suffix_len))
+    } else {
+        let literal = integer_literal(decimal, 10, suffix)?;
+        Ok((literal, bytes_read + suffix_len))
+    }
+}
+
+/// Tokenize an integer that starts with a prefix for its base, e.g. `0x1F`.
+fn tokenize_prefixed_integer(
+    data: &str,
+    prefix: &str,
+    radix: u32,
+) -> TokenizerResult<(TokenKind, usize)> {
+    let rest = &data[prefix.len()..];
+    // Read all the decimal digits even for binary, so `0b12` fails because of
+    // the digit rather than the suffix.
+    let (digits, digits_len) = take_while(rest, |c| c.is_digit(radix.max(10)))
+        .map_err(|_| TokenizerError::MissingPrefixDigits(prefix.to_string()))?;
+    let (suffix, suffix_len) = take_suffix(&rest[digits_len..]);
+
+    let literal = integer_literal(digits, radix, suffix)?;
+    Ok((literal, prefix.len() + digits_len + suffix_len))
+}
 
-    if seen_dot {
-        let n: f64 = decimal.parse()?;
-        Ok((TokenKind::Decimal(n), bytes_read))
+/// Take the letters that directly follow a number.
+fn take_suffix(data: &str) -> (&str, usize) {
+    take_while(data, |c| c == '_' || c.is_alphanumeric()).unwrap_or(("", 0))
+}
+
+/// Build an integer literal token from its digits and suffix, choosing its
+/// type with the C rules.
+fn integer_literal(digits: &str, radix: u32, suffix: &str) -> TokenizerResult<TokenKind> {
+    if let Some(digit) = digits.chars().find(|c| !c.is_digit(radix)) {
+        return Err(TokenizerError::InvalidDigit(digit, radix));
+    }
+
+    let (unsigned, longs) = parse_integer_suffix(suffix)
+        .ok_or_else(|| TokenizerError::InvalidIntegerSuffix(suffix.to_string()))?;
+    let too_large = || TokenizerError::IntegerLiteralTooLarge(digits.to_string(), radix);
+    // Empty digits only happen for the octal zero, e.g. `0u`.
+    let value = if digits.is_empty() {
+        0
+    } else {
+        u64::from_str_radix(digits, radix).map_err(|_| too_large())?
+    };
+
+    let ty =
+        IntLiteralType::from_value(value, radix == 10, unsigned, longs).ok_or_else(too_large)?;
+    Ok(TokenKind::Integer(value, ty))
+}
+
+/// Parse an integer suffix into whether it's unsigned and the number of `l`s
+/// in it. Returns `None` for invalid suffixes like `lL` or `lul`.
+fn parse_integer_suffix(suffix: &str) -> Option<(bool, usize)> {
+    let (unsigned, rest) = if let Some(rest) = suffix.strip_prefix(['u', 'U']) {
+        (true, rest)
+    } else if let Some(rest) = suffix.strip_suffix(['u', 'U']) {
+        (true, rest)
     } else {
-        let n: i32 = decimal.parse()?;
-        Ok((TokenKind::Integer(n), bytes_read))
+        (false, suffix)
+    };
+
+    match rest {
+        "" => Some((unsigned, 0)),
+        "l" | "L" => Some((unsigned, 1)),
+        "ll" | "LL" => Some((unsigned, 2)),
+        _ => None,
     }
 }
@@ -3,7 +3,7 @@ mod helpers;
 mod whitespace;
 
 pub mod token;
-use self::error::TokenizerError;
+pub use self::error::TokenizerError;
 use self::helpers::*;
 pub use self::token::*;
 use std::iter::Peekable;
@@ -23,7 +23,7 @@ pub struct Tokenizer<'a> {
 
 impl<'a> Tokenizer<'a> {
     /// Create a new tokenizer from the given input.
-    pub fn new(input: &str) -> Tokenizer {
+    pub fn new(input: &str) -> Tokenizer<'_> {
         Tokenizer {
             remaining_source: input,
             pos: 0,
@@ -31,10 +31,31 @@ impl<'a> Tokenizer<'a> {
     }
 
     /// Tokenize the entire input stream and consume the tokenizer.
-    pub fn tokenize(mut self) -> TokenizerResult<TokenStream> {
+    pub fn tokenize(self) -> TokenizerResult<TokenStream> {
+        self.tokenize_with_span().map_err(|(err, _)| err)
+    }
+
+    /// Same as `tokenize`, but the error comes with the span of the character
+    /// where the failed token starts.
+    pub fn tokenize_with_span(mut self) -> Result<TokenStream, (TokenizerError, Span)> {
         let mut tokens = Vec::new();
-        while let Some(token) = self.next_token()? {
-            tokens.push(token);
+        loop {
+            match self.next_token() {
+                Ok(Some(token)) => tokens.push(token),
+                Ok(None) => break,
+                Err(err) => {
+                    let len = self
+                        .remaining_source
+                        .chars()
+                        .next()
+                        .map_or(0, char::len_utf8);
+                    let span = Span {
+                        lo: self.pos,
+                        hi: self.pos + len,
+                    };
+                    return Err((err, span));
+                }
+            }
         }
         Ok(TokenStream {
             tokens: tokens.into_iter().peekable(),
@@ -77,6 +98,7 @@ impl<'a> Tokenizer<'a> {
             '{' => (TokenKind::LBrace, 1),
             '}' => (TokenKind::RBrace, 1),
             ';' => (TokenKind::Semicolon, 1),
+            ',' => (TokenKind::Comma, 1),
             '+' => (TokenKind::Plus, 1),
             '-' => (TokenKind::Minus, 1),
             '*' => (TokenKind::Asterisk, 1),
@@ -102,6 +124,7 @@ impl<'a> Tokenizer<'a> {
             '>' => (TokenKind::GreaterThan, 1),
             '=' => (TokenKind::Assignment, 1),
             '0'..='9' => tokenize_integer(data)?,
+            '.' if rem_chars.peek().is_some_and(|c| c.is_ascii_digit()) => tokenize_integer(data)?,
             c @ '_' | c if c.is_alphabetic() => tokenize_ident_or_keyword(data)?,
             other => return Err(TokenizerError::UnknownCharacter(other)),
         };
@@ -118,51 +141,8 @@ impl<'a> Tokenizer<'a> {
 }
 
 #[cfg(test)]
-pub(crate) mod tests {
+mod tests {
     use super::*;
-    // Add the other stages here as we go along.
-    pub static ALLOWED_STAGES: &'static [&str] =
-        &["stage_1", "stage_2", "stage_3", "stage_4", "stage_5"];
-
-    #[test]
-    fn test_tokenizer_valid_files() {
-        use std::fs;
-
-        let test_dirs = fs::read_dir("tests/").unwrap();
-        for dir in test_dirs {
-            let dir = dir.unwrap();
-            if !dir.file_type().unwrap().is_dir() {
-                // Skip the files.
-                continue;
-            }
-
-            if !ALLOWED_STAGES.contains(&dir.file_name().to_str().unwrap()) {
-                // Skip the invalid directory.
-                continue;
-            }
-
-            let mut path = dir.path();
-            path.push("valid");
-            let test_files = fs::read_dir(path).unwrap();
-
-            for file in test_files {
-                let file = file.unwrap();
-                let path = file.path();
-                let path = path.to_str().unwrap();
-
-                println!("Testing lexer for: {}", path);
-                let contents = fs::read_to_string(path).unwrap();
-                let tokenizer = Tokenizer::new(&contents);
-                let token_stream = tokenizer.tokenize();
-                if path.contains("skip_on_failure") && token_stream.is_err() {
-                    println!("Failed but skipping: {}", path);
-                    continue;
-                }
-                assert!(!token_stream.is_err());
-            }
-        }
-    }
-
     macro_rules! tokenizer_test {
         ($name:ident, $src:expr => $should_be:expr) => {
             #[test]
@@ -201,13 +181,91 @@ pub(crate) mod tests {
         span: Some(Span { lo: 0, hi: 7 }),
     });
     tokenizer_single_token_test!(test_tokenize_integer, "123" => Token {
-        kind: TokenKind::Integer(123),
+        kind: TokenKind::Integer(123, IntLiteralType::Int),
         span: Some(Span { lo: 0, hi: 3 }),
     });
+
+    macro_rules! tokenizer_integer_test {
+        ($name:ident, $src:expr => $value:expr, $ty:ident) => {
+            #[test]
+            fn $name() {
+                let tokenizer = Tokenizer::new($src);
+                let mut token_stream = tokenizer.tokenize().unwrap();
+                assert_eq!(token_stream.tokens.len(), 1);
+                assert_eq!(
+                    token_stream.tokens.next().unwrap().kind,
+                    TokenKind::Integer($value, IntLiteralType::$ty)
+                );
+            }
+        };
+    }
+
+    macro_rules! tokenizer_error_test {
+        ($name:ident, $src:expr => $err:pat) => {
+            #[test]
+            fn $name() {
+                let tokenizer = Tokenizer::new($src);
+                assert!(matches!(tokenizer.tokenize(), Err($err)));
+            }
+        };
+    }
+
+    tokenizer_integer_test!(test_tokenize_hex, "0x1F" => 31, Int);
+    tokenizer_integer_test!(test_tokenize_upper_hex, "0XfF" => 255, Int);
+    tokenizer_integer_test!(test_tokenize_octal, "017" => 15, Int);
+    tokenizer_integer_test!(test_tokenize_zero, "0" => 0, Int);
+    tokenizer_integer_test!(test_tokenize_binary, "0b101" => 5, Int);
+    tokenizer_integer_test!(test_tokenize_unsigned_suffix, "3u" => 3, UnsignedInt);
+    tokenizer_integer_test!(test_tokenize_long_suffix, "3L" => 3, Long);
+    tokenizer_integer_test!(test_tokenize_unsigned_long_long_suffix, "3llU" => 3, UnsignedLongLong);
+    tokenizer_integer_test!(test_tokenize_long_long_unsigned_suffix, "3ULL" => 3, UnsignedLongLong);
+    tokenizer_integer_test!(test_tokenize_decimal_overflows_to_long, "2147483648" => 2147483648, Long);
+    tokenizer_integer_test!(test_tokenize_hex_overflows_to_unsigned, "0x80000000" => 0x80000000, UnsignedInt);
+    tokenizer_integer_test!(test_tokenize_hex_overflows_to_unsigned_long, "0x8000000000000000" => 0x8000000000000000, UnsignedLong);
+    tokenizer_integer_test!(test_tokenize_unsigned_overflows_to_unsigned_long, "4294967296u" => 4294967296, UnsignedLong);
+    tokenizer_integer_test!(test_tokenize_octal_with_suffix, "0u" => 0, UnsignedInt);
+
+    tokenizer_error_test!(test_tokenize_hex_without_digits, "0x" => TokenizerError::MissingPrefixDigits(_));
+    tokenizer_error_test!(test_tokenize_invalid_octal_digit, "019" => TokenizerError::InvalidDigit('9', 8));
+    tokenizer_error_test!(test_tokenize_invalid_binary_digit, "0b102" => TokenizerError::InvalidDigit('2', 2));
+    tokenizer_error_test!(test_tokenize_invalid_hex_suffix, "0x1G" => TokenizerError::InvalidIntegerSuffix(_));
+    tokenizer_error_test!(test_tokenize_invalid_integer_suffix, "123abc" => TokenizerError::InvalidIntegerSuffix(_));
+    tokenizer_error_test!(test_tokenize_mixed_case_long_long, "1lL" => TokenizerError::InvalidIntegerSuffix(_));
+    tokenizer_error_test!(test_tokenize_double_unsigned_suffix, "1uu" => TokenizerError::InvalidIntegerSuffix(_));
+    tokenizer_error_test!(test_tokenize_invalid_float_suffix, "1.5x" => TokenizerError::InvalidFloatSuffix(_));
+    tokenizer_error_test!(test_tokenize_decimal_too_large, "9223372036854775808" => TokenizerError::IntegerLiteralTooLarge(..));
+    tokenizer_error_test!(test_tokenize_hex_too_large, "0x10000000000000000" => TokenizerError::IntegerLiteralTooLarge(..));
     tokenizer_single_token_test!(test_tokenize_decimal, "123.23" => Token {
         kind: TokenKind::Decimal(123.23),
         span: Some(Span { lo: 0, hi: 6 }),
     });
+    tokenizer_single_token_test!(test_tokenize_decimal_with_exponent, "1.5e-3" => Token {
+        kind: TokenKind::Decimal(1.5e-3),
+        span: Some(Span { lo: 0, hi: 6 }),
+    });
+    tokenizer_single_token_test!(test_tokenize_decimal_without_dot, "2E10" => Token {
+        kind: TokenKind::Decimal(2e10),
+        span: Some(Span { lo: 0, hi: 4 }),
+    });
+    tokenizer_single_token_test!(test_tokenize_decimal_leading_dot, ".25" => Token {
+        kind: TokenKind::Decimal(0.25),
+        span: Some(Span { lo: 0, hi: 3 }),
+    });
+    tokenizer_single_token_test!(test_tokenize_decimal_trailing_dot, "3." => Token {
+        kind: TokenKind::Decimal(3.0),
+        span: Some(Span { lo: 0, hi: 2 }),
+    });
+    tokenizer_single_token_test!(test_tokenize_float, "1.5f" => Token {
+        kind: TokenKind::Float(1.5),
+        span: Some(Span { lo: 0, hi: 4 }),
+    });
+    tokenizer_single_token_test!(test_tokenize_float_keyword, "float" => Token {
+        kind: TokenKind::Keyword(Keyword::Float),
+        span: Some(Span { lo: 0, hi: 5 }),
+    });
+
+    tokenizer_error_test!(test_tokenize_exponent_without_digits, "1e+" => TokenizerError::MissingExponentDigits);
+
     tokenizer_single_token_test!(test_tokenize_l_brace, "{" => Token {
         kind: TokenKind::LBrace,
         span: Some(Span { lo: 0, hi: 1 }),
@@ -240,4 +298,13 @@ pub(crate) mod tests {
         kind: TokenKind::Keyword(Keyword::Else),
         span: Some(Span { lo: 0, hi: 4 }),
     });
+    tokenizer_test!(test_tokenize_function_call, "foo(a, 1)" => 6);
+    tokenizer_single_token_test!(test_tokenize_typedef, "typedef" => Token {
+        kind: TokenKind::Keyword(Keyword::Typedef),
+        span: Some(Span { lo: 0, hi: 7 }),
+    });
+    tokenizer_single_token_test!(test_tokenize_inline, "inline" => Token {
+        kind: TokenKind::Keyword(Keyword::Inline),
+        span: Some(Span { lo: 0, hi: 6 }),
+    });
 }
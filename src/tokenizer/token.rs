@@ -8,14 +8,16 @@ pub struct Token {
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
     Identifier(String),
-    Integer(i32),
+    Integer(u64, IntLiteralType),
     Decimal(f64),
+    Float(f32), // Decimal with the `f` suffix.
     Keyword(Keyword),
     LParen,            // (
     RParen,            // )
     LBrace,            // {
     RBrace,            // }
     Semicolon,         // ;
+    Comma,             // ,
     Plus,              // +
     Minus,             // -
     Asterisk,          // *
@@ -52,10 +54,78 @@ pub enum Keyword {
     Continue,
     Break,
     For,
+    Typedef,
+    Float,
+    Double,
+    Inline,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// Type of an integer literal, chosen by its value and suffix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntLiteralType {
+    Int,
+    UnsignedInt,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
+}
+
+impl IntLiteralType {
+    /// Find the first type that can represent the value, as listed in the
+    /// C11 standard 6.4.4.1. Decimal literals without the `u` suffix never
+    /// become unsigned.
+    pub fn from_value(value: u64, decimal: bool, unsigned: bool, longs: usize) -> Option<Self> {
+        use IntLiteralType::*;
+
+        let candidates: &[IntLiteralType] = match (unsigned, longs, decimal) {
+            (false, 0, true) => &[Int, Long, LongLong],
+            (false, 0, false) => &[
+                Int,
+                UnsignedInt,
+                Long,
+                UnsignedLong,
+                LongLong,
+                UnsignedLongLong,
+            ],
+            (true, 0, _) => &[UnsignedInt, UnsignedLong, UnsignedLongLong],
+            (false, 1, true) => &[Long, LongLong],
+            (false, 1, false) => &[Long, UnsignedLong, LongLong, UnsignedLongLong],
+            (true, 1, _) => &[UnsignedLong, UnsignedLongLong],
+            (false, _, true) => &[LongLong],
+            (false, _, false) => &[LongLong, UnsignedLongLong],
+            (true, _, _) => &[UnsignedLongLong],
+        };
+
+        candidates
+            .iter()
+            .copied()
+            .find(|ty| value <= ty.max_value())
+    }
+
+    /// Largest value of the type in ARM64, where long is 64 bits.
+    pub fn max_value(self) -> u64 {
+        match self {
+            IntLiteralType::Int => i32::MAX as u64,
+            IntLiteralType::UnsignedInt => u32::MAX as u64,
+            IntLiteralType::Long | IntLiteralType::LongLong => i64::MAX as u64,
+            IntLiteralType::UnsignedLong | IntLiteralType::UnsignedLongLong => u64::MAX,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub struct Span {
     pub lo: usize,
     pub hi: usize,
 }
+
+impl Span {
+    /// The span from the start of this one to the end of the other one.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            lo: self.lo,
+            hi: other.hi,
+        }
+    }
+}
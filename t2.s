@@ -0,0 +1,22 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+Lmain_bb0:
+mov w9, #0
+mov w10, #0
+Lmain_bb1:
+mov w11, w9
+mov w12, w10
+add w13, w12, w11
+mov w2, #1
+add w12, w11, w2
+cmp w12, #10
+mov w9, w12
+mov w10, w13
+blt Lmain_bb1
+Lmain_bb2:
+mov w0, w13
+Lmain_return:
+ret
@@ -0,0 +1,24 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _f
+.p2align 2
+_f:
+Lf_bb0:
+mov w9, w0
+cmp w9, #10
+mov w9, #5
+blt Lf_bb2
+Lf_bb1:
+mov w9, #1
+Lf_bb2:
+mov w10, w9
+mov w0, w10
+Lf_return:
+ret
+.globl _main
+.p2align 2
+_main:
+Lmain_bb0:
+mov w0, #5
+Lmain_return:
+ret
@@ -0,0 +1,4 @@
+// error: parse
+int main( {
+    return 0;
+}
@@ -0,0 +1,3 @@
+// error: parse
+int main() {
+    return 0;
@@ -0,0 +1,4 @@
+// error: parse
+int main() {
+    RETURN 0;
+}
@@ -0,0 +1,7 @@
+// exit: 4
+/* block
+   comment */
+int main() {
+    // line comment
+    return /* inline */ 4;
+}
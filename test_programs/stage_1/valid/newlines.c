@@ -0,0 +1,10 @@
+// exit: 0
+int
+main
+(
+)
+{
+return
+0
+;
+}
@@ -0,0 +1,2 @@
+// exit: 0
+int main(){return 0;}
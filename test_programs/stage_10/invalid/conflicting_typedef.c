@@ -0,0 +1,7 @@
+// error: parse
+typedef int T;
+typedef double T;
+
+int main() {
+    return 0;
+}
@@ -0,0 +1,5 @@
+// error: codegen
+int main() {
+    float a = 1.0f;
+    return ~a;
+}
@@ -0,0 +1,5 @@
+// error: codegen
+int main() {
+    double a = 2.5;
+    return a % 2;
+}
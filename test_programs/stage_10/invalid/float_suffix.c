@@ -0,0 +1,4 @@
+// error: tokenize
+int main() {
+    return 1.5q;
+}
@@ -0,0 +1,4 @@
+// error: parse
+int main() {
+    return 5000000000;
+}
@@ -0,0 +1,4 @@
+// error: tokenize
+int main() {
+    return 09;
+}
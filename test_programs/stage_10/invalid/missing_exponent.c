@@ -0,0 +1,5 @@
+// error: tokenize
+int main() {
+    double a = 1e;
+    return 0;
+}
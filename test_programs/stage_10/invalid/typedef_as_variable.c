@@ -0,0 +1,7 @@
+// error: parse
+typedef int T;
+int T;
+
+int main() {
+    return 0;
+}
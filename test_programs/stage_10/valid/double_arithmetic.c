@@ -0,0 +1,6 @@
+// exit: 12
+int main() {
+    double a = 0.1;
+    double b = 0.2;
+    return (a + b) * 40;
+}
@@ -0,0 +1,32 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #32
+movz x16, #39322, lsl #0
+movk x16, #39321, lsl #16
+movk x16, #39321, lsl #32
+movk x16, #16313, lsl #48
+fmov d0, x16
+str d0, [sp, #24]
+movz x16, #39322, lsl #0
+movk x16, #39321, lsl #16
+movk x16, #39321, lsl #32
+movk x16, #16329, lsl #48
+fmov d0, x16
+str d0, [sp, #16]
+ldr d0, [sp, #24]
+str d0, [sp, #8]
+ldr d0, [sp, #16]
+ldr d1, [sp, #8]
+fadd d0, d1, d0
+str d0, [sp, #8]
+mov w0, #40
+scvtf d0, w0
+ldr d1, [sp, #8]
+fmul d0, d1, d0
+fcvtzs w0, d0
+Lmain_return:
+add sp, sp, #32
+ret
@@ -0,0 +1,6 @@
+// exit: 4
+int main() {
+    double a = 1e2;
+    double b = 2.5E-1;
+    return a * b / 10 + .5 + 1.;
+}
@@ -0,0 +1,36 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #48
+movz x16, #16473, lsl #48
+fmov d0, x16
+str d0, [sp, #40]
+movz x16, #16336, lsl #48
+fmov d0, x16
+str d0, [sp, #32]
+ldr d0, [sp, #40]
+str d0, [sp, #24]
+ldr d0, [sp, #32]
+ldr d1, [sp, #24]
+fmul d0, d1, d0
+str d0, [sp, #24]
+mov w0, #10
+scvtf d0, w0
+ldr d1, [sp, #24]
+fdiv d0, d1, d0
+str d0, [sp, #24]
+movz x16, #16352, lsl #48
+fmov d0, x16
+ldr d1, [sp, #24]
+fadd d0, d1, d0
+str d0, [sp, #24]
+movz x16, #16368, lsl #48
+fmov d0, x16
+ldr d1, [sp, #24]
+fadd d0, d1, d0
+fcvtzs w0, d0
+Lmain_return:
+add sp, sp, #48
+ret
@@ -0,0 +1,8 @@
+// exit: 10
+double scale(double x, int factor, float offset) {
+    return x * factor + offset;
+}
+
+int main() {
+    return scale(1.5, 4, 4.0f);
+}
@@ -0,0 +1,46 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _scale
+.p2align 2
+_scale:
+sub sp, sp, #32
+str d0, [sp, #24]
+str w0, [sp, #20]
+str s1, [sp, #16]
+ldr d0, [sp, #24]
+str d0, [sp, #8]
+ldr w0, [sp, #20]
+scvtf d0, w0
+ldr d1, [sp, #8]
+fmul d0, d1, d0
+str d0, [sp, #8]
+ldr s0, [sp, #16]
+fcvt d0, s0
+ldr d1, [sp, #8]
+fadd d0, d1, d0
+Lscale_return:
+add sp, sp, #32
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #32
+movz x16, #16376, lsl #48
+fmov d0, x16
+str d0, [sp, #24]
+mov w0, #4
+str w0, [sp, #16]
+movz w16, #16512, lsl #16
+fmov s0, w16
+str s0, [sp, #8]
+ldr d0, [sp, #24]
+ldr w0, [sp, #16]
+ldr s1, [sp, #8]
+bl _scale
+fcvtzs w0, d0
+Lmain_return:
+add sp, sp, #32
+ldp x29, x30, [sp], #16
+ret
@@ -0,0 +1,6 @@
+// exit: 6
+int main() {
+    float a = 1.5f;
+    float b = 2.25f;
+    return a * b * 2;
+}
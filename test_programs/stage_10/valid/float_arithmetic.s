@@ -0,0 +1,26 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #32
+movz w16, #16320, lsl #16
+fmov s0, w16
+str s0, [sp, #28]
+movz w16, #16400, lsl #16
+fmov s0, w16
+str s0, [sp, #24]
+ldr s0, [sp, #28]
+str s0, [sp, #16]
+ldr s0, [sp, #24]
+ldr s1, [sp, #16]
+fmul s0, s1, s0
+str s0, [sp, #16]
+mov w0, #2
+scvtf s0, w0
+ldr s1, [sp, #16]
+fmul s0, s1, s0
+fcvtzs w0, s0
+Lmain_return:
+add sp, sp, #32
+ret
@@ -0,0 +1,6 @@
+// exit: 5
+int main() {
+    double a = 0.1 + 0.2;
+    float b = 0.5f;
+    return (a != 0.3) + (b < a) * 2 + (b == 0.5) * 4 - (a >= 1.0);
+}
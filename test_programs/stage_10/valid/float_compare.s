@@ -0,0 +1,67 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #96
+movz x16, #13108, lsl #0
+movk x16, #13107, lsl #16
+movk x16, #13107, lsl #32
+movk x16, #16339, lsl #48
+fmov d0, x16
+str d0, [sp, #88]
+movz w16, #16128, lsl #16
+fmov s0, w16
+str s0, [sp, #84]
+ldr d0, [sp, #88]
+str d0, [sp, #72]
+movz x16, #13107, lsl #0
+movk x16, #13107, lsl #16
+movk x16, #13107, lsl #32
+movk x16, #16339, lsl #48
+fmov d0, x16
+ldr d1, [sp, #72]
+fcmp d1, d0
+cset w0, ne
+str w0, [sp, #72]
+ldr s0, [sp, #84]
+fcvt d0, s0
+str d0, [sp, #64]
+ldr d0, [sp, #88]
+ldr d1, [sp, #64]
+fcmp d1, d0
+cset w0, mi
+str w0, [sp, #64]
+mov w0, #2
+ldr w1, [sp, #64]
+mul w0, w1, w0
+ldr w1, [sp, #72]
+add w0, w1, w0
+str w0, [sp, #72]
+ldr s0, [sp, #84]
+fcvt d0, s0
+str d0, [sp, #64]
+movz x16, #16352, lsl #48
+fmov d0, x16
+ldr d1, [sp, #64]
+fcmp d1, d0
+cset w0, eq
+str w0, [sp, #64]
+mov w0, #4
+ldr w1, [sp, #64]
+mul w0, w1, w0
+ldr w1, [sp, #72]
+add w0, w1, w0
+str w0, [sp, #72]
+ldr d0, [sp, #88]
+str d0, [sp, #64]
+movz x16, #16368, lsl #48
+fmov d0, x16
+ldr d1, [sp, #64]
+fcmp d1, d0
+cset w0, ge
+ldr w1, [sp, #72]
+sub w0, w1, w0
+Lmain_return:
+add sp, sp, #96
+ret
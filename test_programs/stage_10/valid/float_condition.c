@@ -0,0 +1,11 @@
+// exit: 3
+int main() {
+    float a = 0.0f;
+    double b = 0.5;
+    int r = 0;
+    if (!a)
+        r = r + 1;
+    if (b)
+        r = r + 2;
+    return r;
+}
@@ -0,0 +1,37 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #48
+fmov s0, wzr
+str s0, [sp, #44]
+movz x16, #16352, lsl #48
+fmov d0, x16
+str d0, [sp, #32]
+mov w0, #0
+str w0, [sp, #28]
+ldr s0, [sp, #44]
+fcmp s0, #0.0
+bne Lmain_if_end_0
+ldr w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #1
+ldr w1, [sp, #16]
+add w0, w1, w0
+str w0, [sp, #28]
+Lmain_if_end_0:
+ldr d0, [sp, #32]
+fcmp d0, #0.0
+beq Lmain_if_end_1
+ldr w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #2
+ldr w1, [sp, #16]
+add w0, w1, w0
+str w0, [sp, #28]
+Lmain_if_end_1:
+ldr w0, [sp, #28]
+Lmain_return:
+add sp, sp, #48
+ret
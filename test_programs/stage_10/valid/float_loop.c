@@ -0,0 +1,10 @@
+// exit: 11
+int main() {
+    double x = 0.0;
+    int n = 0;
+    while (x < 1.0) {
+        x = x + 0.1;
+        n = n + 1;
+    }
+    return n;
+}
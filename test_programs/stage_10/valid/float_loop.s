@@ -0,0 +1,40 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #48
+fmov d0, xzr
+str d0, [sp, #40]
+mov w0, #0
+str w0, [sp, #36]
+Lmain_while_start_0:
+ldr d0, [sp, #40]
+str d0, [sp, #24]
+movz x16, #16368, lsl #48
+fmov d0, x16
+ldr d1, [sp, #24]
+fcmp d1, d0
+bpl Lmain_while_end_0
+ldr d0, [sp, #40]
+str d0, [sp, #24]
+movz x16, #39322, lsl #0
+movk x16, #39321, lsl #16
+movk x16, #39321, lsl #32
+movk x16, #16313, lsl #48
+fmov d0, x16
+ldr d1, [sp, #24]
+fadd d0, d1, d0
+str d0, [sp, #40]
+ldr w0, [sp, #36]
+str w0, [sp, #24]
+mov w0, #1
+ldr w1, [sp, #24]
+add w0, w1, w0
+str w0, [sp, #36]
+b Lmain_while_start_0
+Lmain_while_end_0:
+ldr w0, [sp, #36]
+Lmain_return:
+add sp, sp, #48
+ret
@@ -0,0 +1,6 @@
+// exit: 2
+int main() {
+    double a = 2.5;
+    double b = -a;
+    return -b;
+}
@@ -0,0 +1,16 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #16
+movz x16, #16388, lsl #48
+fmov d0, x16
+str d0, [sp, #8]
+fneg d0, d0
+str d0, [sp, #0]
+fneg d0, d0
+fcvtzs w0, d0
+Lmain_return:
+add sp, sp, #16
+ret
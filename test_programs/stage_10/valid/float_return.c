@@ -0,0 +1,8 @@
+// exit: 3
+float half(int a) {
+    return a / 2.0f;
+}
+
+int main() {
+    return half(7);
+}
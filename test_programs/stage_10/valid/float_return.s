@@ -0,0 +1,30 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _half
+.p2align 2
+_half:
+sub sp, sp, #16
+str w0, [sp, #12]
+scvtf s0, w0
+str s0, [sp, #0]
+movz w16, #16384, lsl #16
+fmov s0, w16
+ldr s1, [sp, #0]
+fdiv s0, s1, s0
+Lhalf_return:
+add sp, sp, #16
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #16
+mov w0, #7
+str w0, [sp, #8]
+bl _half
+fcvtzs w0, s0
+Lmain_return:
+add sp, sp, #16
+ldp x29, x30, [sp], #16
+ret
@@ -0,0 +1,4 @@
+// exit: 42
+int main() {
+    return 0x20 + 010 + 0b10;
+}
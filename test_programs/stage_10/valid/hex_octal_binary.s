@@ -0,0 +1,8 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+mov w0, #42
+Lmain_return:
+ret
@@ -0,0 +1,7 @@
+// exit: 3
+int main() {
+    int min = -2147483648;
+    // Narrowed to -1 with a warning.
+    int all_ones = 0xFFFFFFFF;
+    return (min < 0) + (min == -2147483647 - 1) + (all_ones == -1);
+}
@@ -0,0 +1,39 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #48
+movz w0, #0
+movk w0, #32768, lsl #16
+str w0, [sp, #44]
+mov w0, #-1
+str w0, [sp, #40]
+ldr w0, [sp, #44]
+str w0, [sp, #32]
+mov w0, #0
+ldr w1, [sp, #32]
+cmp w1, w0
+cset w0, lt
+str w0, [sp, #32]
+ldr w0, [sp, #44]
+str w0, [sp, #24]
+movz w0, #0
+movk w0, #32768, lsl #16
+ldr w1, [sp, #24]
+cmp w1, w0
+cset w0, eq
+ldr w1, [sp, #32]
+add w0, w1, w0
+str w0, [sp, #32]
+ldr w0, [sp, #40]
+str w0, [sp, #24]
+mov w0, #-1
+ldr w1, [sp, #24]
+cmp w1, w0
+cset w0, eq
+ldr w1, [sp, #32]
+add w0, w1, w0
+Lmain_return:
+add sp, sp, #48
+ret
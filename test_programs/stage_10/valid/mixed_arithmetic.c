@@ -0,0 +1,7 @@
+// exit: 9
+int main() {
+    int a = 3;
+    float b = 1.5f;
+    double c = 2.0;
+    return a * b * c;
+}
@@ -0,0 +1,29 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #32
+mov w0, #3
+str w0, [sp, #28]
+movz w16, #16320, lsl #16
+fmov s0, w16
+str s0, [sp, #24]
+movz x16, #16384, lsl #48
+fmov d0, x16
+str d0, [sp, #16]
+ldr w0, [sp, #28]
+scvtf s0, w0
+str s0, [sp, #8]
+ldr s0, [sp, #24]
+ldr s1, [sp, #8]
+fmul s0, s1, s0
+fcvt d0, s0
+str d0, [sp, #8]
+ldr d0, [sp, #16]
+ldr d1, [sp, #8]
+fmul d0, d1, d0
+fcvtzs w0, d0
+Lmain_return:
+add sp, sp, #32
+ret
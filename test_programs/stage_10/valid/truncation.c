@@ -0,0 +1,6 @@
+// exit: 253
+int main() {
+    double a = -3.99;
+    int b = a;
+    return b;
+}
@@ -0,0 +1,17 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #16
+movz x16, #20972, lsl #0
+movk x16, #7864, lsl #16
+movk x16, #60293, lsl #32
+movk x16, #49167, lsl #48
+fmov d0, x16
+str d0, [sp, #8]
+fcvtzs w0, d0
+str w0, [sp, #4]
+Lmain_return:
+add sp, sp, #16
+ret
@@ -0,0 +1,11 @@
+// exit: 7
+typedef int number;
+
+number add(number a, number b) {
+    return a + b;
+}
+
+int main() {
+    number a = 3;
+    return add(a, 4);
+}
@@ -0,0 +1,34 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _add
+.p2align 2
+_add:
+sub sp, sp, #16
+str w0, [sp, #12]
+str w1, [sp, #8]
+ldr w0, [sp, #12]
+str w0, [sp, #0]
+ldr w0, [sp, #8]
+ldr w1, [sp, #0]
+add w0, w1, w0
+Ladd_return:
+add sp, sp, #16
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #32
+mov w0, #3
+str w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #4
+str w0, [sp, #8]
+ldr w0, [sp, #16]
+ldr w1, [sp, #8]
+bl _add
+Lmain_return:
+add sp, sp, #32
+ldp x29, x30, [sp], #16
+ret
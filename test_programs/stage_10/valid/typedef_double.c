@@ -0,0 +1,7 @@
+// exit: 6
+typedef double real;
+
+int main() {
+    real r = 1.5;
+    return r * 4;
+}
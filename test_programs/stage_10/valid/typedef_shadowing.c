@@ -0,0 +1,11 @@
+// exit: 5
+typedef int T;
+
+int main() {
+    T a = 2;
+    {
+        int T = 3;
+        a = a + T;
+    }
+    return a;
+}
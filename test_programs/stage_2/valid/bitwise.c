@@ -0,0 +1,4 @@
+// exit: 243
+int main() {
+    return ~12;
+}
@@ -0,0 +1,4 @@
+// exit: 255
+int main() {
+    return ~0;
+}
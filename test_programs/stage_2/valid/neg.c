@@ -0,0 +1,4 @@
+// exit: 251
+int main() {
+    return -5;
+}
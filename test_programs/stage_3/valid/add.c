@@ -0,0 +1,4 @@
+// exit: 3
+int main() {
+    return 1 + 2;
+}
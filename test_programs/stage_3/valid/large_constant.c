@@ -0,0 +1,4 @@
+// exit: 100
+int main() {
+    return 1000000 / 10000;
+}
@@ -0,0 +1,4 @@
+// exit: 2
+int main() {
+    return 17 % 5;
+}
@@ -0,0 +1,4 @@
+// exit: 254
+int main() {
+    return -17 % 5;
+}
@@ -0,0 +1,4 @@
+// exit: 6
+int main() {
+    return 2 * 3;
+}
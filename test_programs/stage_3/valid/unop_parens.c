@@ -0,0 +1,4 @@
+// exit: 253
+int main() {
+    return ~(1 + 1);
+}
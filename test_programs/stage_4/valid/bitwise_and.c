@@ -0,0 +1,4 @@
+// exit: 8
+int main() {
+    return 12 & 10;
+}
@@ -0,0 +1,4 @@
+// exit: 14
+int main() {
+    return 12 | 10;
+}
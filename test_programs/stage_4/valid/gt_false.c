@@ -0,0 +1,4 @@
+// exit: 0
+int main() {
+    return 1 > 2;
+}
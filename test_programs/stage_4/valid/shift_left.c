@@ -0,0 +1,4 @@
+// exit: 40
+int main() {
+    return 5 << 3;
+}
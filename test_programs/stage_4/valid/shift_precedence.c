@@ -0,0 +1,4 @@
+// exit: 24
+int main() {
+    return 1 + 2 << 3;
+}
@@ -0,0 +1,4 @@
+// exit: 5
+int main() {
+    return 40 >> 3;
+}
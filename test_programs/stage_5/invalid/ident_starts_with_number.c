@@ -0,0 +1,5 @@
+// error: tokenize
+int main() {
+    int 2a = 3;
+    return 0;
+}
@@ -0,0 +1,6 @@
+// error: codegen
+int main() {
+    int a = 1;
+    int a = 2;
+    return a;
+}
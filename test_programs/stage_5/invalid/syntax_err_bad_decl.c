@@ -0,0 +1,5 @@
+// error: parse
+int main() {
+    int foo bar = 3;
+    return bar;
+}
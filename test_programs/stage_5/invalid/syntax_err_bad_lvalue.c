@@ -0,0 +1,6 @@
+// error: parse
+int main() {
+    int a = 2;
+    a + 3 = 4;
+    return a;
+}
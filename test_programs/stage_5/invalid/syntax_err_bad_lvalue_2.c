@@ -0,0 +1,7 @@
+// error: parse
+// xfail: the operand of a unary operator can be an assignment
+int main() {
+    int a = 2;
+    !a = 3;
+    return a;
+}
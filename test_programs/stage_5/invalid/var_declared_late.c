@@ -0,0 +1,7 @@
+// error: codegen
+// xfail: variables are collected before the codegen walks the statements
+int main() {
+    a = 1 + 2;
+    int a;
+    return a;
+}
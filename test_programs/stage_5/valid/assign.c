@@ -0,0 +1,6 @@
+// exit: 2
+int main() {
+    int a;
+    a = 2;
+    return a;
+}
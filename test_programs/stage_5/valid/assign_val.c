@@ -0,0 +1,6 @@
+// exit: 0
+int main() {
+    int a;
+    int b = a = 0;
+    return b;
+}
@@ -0,0 +1,7 @@
+// exit: 4
+int main() {
+    int a;
+    int b;
+    a = b = 4;
+    return a - b + a;
+}
@@ -0,0 +1,5 @@
+// exit: 0
+int main() {
+    int a = 2;
+    return 0;
+}
@@ -0,0 +1,7 @@
+// exit: 16
+int main() {
+    int a = 100000;
+    int b = -100000;
+    int c = 2147483647;
+    return a + b + (c + 1 == -2147483647 - 1) * 16;
+}
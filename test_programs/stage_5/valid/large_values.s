@@ -0,0 +1,41 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #64
+movz w0, #34464
+movk w0, #1, lsl #16
+str w0, [sp, #60]
+movz w0, #31072
+movk w0, #65534, lsl #16
+str w0, [sp, #56]
+movz w0, #65535
+movk w0, #32767, lsl #16
+str w0, [sp, #52]
+ldr w0, [sp, #60]
+str w0, [sp, #40]
+ldr w0, [sp, #56]
+ldr w1, [sp, #40]
+add w0, w1, w0
+str w0, [sp, #40]
+ldr w0, [sp, #52]
+str w0, [sp, #32]
+mov w0, #1
+ldr w1, [sp, #32]
+add w0, w1, w0
+str w0, [sp, #32]
+movz w0, #0
+movk w0, #32768, lsl #16
+ldr w1, [sp, #32]
+cmp w1, w0
+cset w0, eq
+str w0, [sp, #32]
+mov w0, #16
+ldr w1, [sp, #32]
+mul w0, w1, w0
+ldr w1, [sp, #40]
+add w0, w1, w0
+Lmain_return:
+add sp, sp, #64
+ret
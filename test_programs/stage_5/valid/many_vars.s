@@ -0,0 +1,66 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #112
+mov w0, #1
+str w0, [sp, #108]
+mov w0, #2
+str w0, [sp, #104]
+mov w0, #3
+str w0, [sp, #100]
+mov w0, #4
+str w0, [sp, #96]
+mov w0, #5
+str w0, [sp, #92]
+mov w0, #6
+str w0, [sp, #88]
+mov w0, #7
+str w0, [sp, #84]
+mov w0, #8
+str w0, [sp, #80]
+mov w0, #9
+str w0, [sp, #76]
+mov w0, #10
+str w0, [sp, #72]
+ldr w0, [sp, #108]
+str w0, [sp, #64]
+ldr w0, [sp, #104]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #100]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #96]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #92]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #88]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #84]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #80]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #76]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #72]
+ldr w1, [sp, #64]
+add w0, w1, w0
+Lmain_return:
+add sp, sp, #112
+ret
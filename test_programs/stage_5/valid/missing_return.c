@@ -0,0 +1,4 @@
+// exit: 0
+int main() {
+    int a = 2;
+}
@@ -0,0 +1,12 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #16
+mov w0, #2
+str w0, [sp, #12]
+mov w0, #0
+Lmain_return:
+add sp, sp, #16
+ret
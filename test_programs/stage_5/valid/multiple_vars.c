@@ -0,0 +1,6 @@
+// exit: 3
+int main() {
+    int a = 1;
+    int b = 2;
+    return a + b;
+}
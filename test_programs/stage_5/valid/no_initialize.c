@@ -0,0 +1,5 @@
+// exit: 0
+int main() {
+    int a;
+    return 0;
+}
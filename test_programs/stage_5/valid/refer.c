@@ -0,0 +1,5 @@
+// exit: 2
+int main() {
+    int a = 2;
+    return a;
+}
@@ -0,0 +1,5 @@
+// exit: 0
+int main() {
+    2 + 2;
+    return 0;
+}
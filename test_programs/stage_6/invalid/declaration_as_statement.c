@@ -0,0 +1,5 @@
+// error: parse
+int main() {
+    if (5)
+        int i = 0;
+}
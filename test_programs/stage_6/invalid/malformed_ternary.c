@@ -0,0 +1,4 @@
+// error: parse
+int main() {
+    return 1 ? 2 : 3 : 4;
+}
@@ -0,0 +1,6 @@
+// exit: 2
+int main() {
+    int a = 0;
+    a = 1 ? 2 : 3;
+    return a;
+}
@@ -0,0 +1,8 @@
+// exit: 2
+int main() {
+    int a = 0;
+    if (a)
+        return 1;
+    else
+        return 2;
+}
@@ -0,0 +1,26 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #16
+mov w0, #0
+str w0, [sp, #12]
+mov w0, #1
+str w0, [sp, #8]
+ldr w0, [sp, #12]
+cbz w0, Lmain_if_else_0
+mov w0, #1
+str w0, [sp, #8]
+b Lmain_if_end_0
+Lmain_if_else_0:
+ldr w0, [sp, #8]
+cbz w0, Lmain_if_end_1
+mov w0, #2
+str w0, [sp, #8]
+Lmain_if_end_1:
+Lmain_if_end_0:
+ldr w0, [sp, #8]
+Lmain_return:
+add sp, sp, #16
+ret
@@ -0,0 +1,10 @@
+// exit: 3
+int main() {
+    int a = 1;
+    if (a)
+        if (a == 2)
+            return 2;
+        else
+            return 3;
+    return 4;
+}
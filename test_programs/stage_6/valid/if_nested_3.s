@@ -0,0 +1,27 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #16
+mov w0, #1
+str w0, [sp, #12]
+cbz w0, Lmain_if_end_0
+ldr w0, [sp, #12]
+str w0, [sp, #0]
+mov w0, #2
+ldr w1, [sp, #0]
+cmp w1, w0
+bne Lmain_if_else_1
+mov w0, #2
+b Lmain_return
+b Lmain_if_end_1
+Lmain_if_else_1:
+mov w0, #3
+b Lmain_return
+Lmain_if_end_1:
+Lmain_if_end_0:
+mov w0, #4
+Lmain_return:
+add sp, sp, #16
+ret
@@ -0,0 +1,8 @@
+// exit: 1
+int main() {
+    int a = 1;
+    int b = 0;
+    if (a)
+        b = 1;
+    return b;
+}
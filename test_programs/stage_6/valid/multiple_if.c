@@ -0,0 +1,14 @@
+// exit: 8
+int main() {
+    int a = 0;
+    int b = 0;
+    if (a)
+        a = 2;
+    else
+        a = 3;
+    if (b)
+        b = 4;
+    else
+        b = 5;
+    return a + b;
+}
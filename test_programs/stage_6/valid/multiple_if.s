@@ -0,0 +1,36 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #16
+mov w0, #0
+str w0, [sp, #12]
+mov w0, #0
+str w0, [sp, #8]
+ldr w0, [sp, #12]
+cbz w0, Lmain_if_else_0
+mov w0, #2
+str w0, [sp, #12]
+b Lmain_if_end_0
+Lmain_if_else_0:
+mov w0, #3
+str w0, [sp, #12]
+Lmain_if_end_0:
+ldr w0, [sp, #8]
+cbz w0, Lmain_if_else_1
+mov w0, #4
+str w0, [sp, #8]
+b Lmain_if_end_1
+Lmain_if_else_1:
+mov w0, #5
+str w0, [sp, #8]
+Lmain_if_end_1:
+ldr w0, [sp, #12]
+str w0, [sp, #0]
+ldr w0, [sp, #8]
+ldr w1, [sp, #0]
+add w0, w1, w0
+Lmain_return:
+add sp, sp, #16
+ret
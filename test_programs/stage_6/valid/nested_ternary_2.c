@@ -0,0 +1,6 @@
+// exit: 15
+int main() {
+    int a = 1 ? 2 ? 3 : 4 : 5;
+    int b = 0 ? 2 ? 3 : 4 : 5;
+    return a * b;
+}
@@ -0,0 +1,7 @@
+// exit: 1
+int main() {
+    int flag = 1;
+    int a = 0;
+    flag ? a = 1 : (a = 0);
+    return a;
+}
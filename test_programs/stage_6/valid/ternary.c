@@ -0,0 +1,5 @@
+// exit: 4
+int main() {
+    int a = 0;
+    return a > -1 ? 4 : 5;
+}
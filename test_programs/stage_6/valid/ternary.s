@@ -0,0 +1,21 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #16
+mov w0, #0
+str w0, [sp, #12]
+str w0, [sp, #0]
+mov w0, #-1
+ldr w1, [sp, #0]
+cmp w1, w0
+ble Lmain_ternary_else_0
+mov w0, #4
+b Lmain_ternary_end_0
+Lmain_ternary_else_0:
+mov w0, #5
+Lmain_ternary_end_0:
+Lmain_return:
+add sp, sp, #16
+ret
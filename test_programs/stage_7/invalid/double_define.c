@@ -0,0 +1,7 @@
+// error: codegen
+int main() {
+    {
+        int a;
+        int a;
+    }
+}
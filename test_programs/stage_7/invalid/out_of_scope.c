@@ -0,0 +1,8 @@
+// error: codegen
+// xfail: variables in nested blocks share the function scope in the codegen
+int main() {
+    {
+        int a = 2;
+    }
+    return a;
+}
@@ -0,0 +1,7 @@
+// error: parse
+int main() {
+    if (0) {
+        return 1;
+    }}
+    return 2;
+}
@@ -0,0 +1,6 @@
+// error: parse
+int main() {
+    if (0) {
+        return 1;
+    return 2;
+}
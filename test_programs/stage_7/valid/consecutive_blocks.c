@@ -0,0 +1,11 @@
+// exit: 1
+int main() {
+    int a = 1;
+    {
+        int b = 2;
+    }
+    {
+        int c = 3;
+    }
+    return a;
+}
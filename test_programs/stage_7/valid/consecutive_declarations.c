@@ -0,0 +1,13 @@
+// exit: 3
+int main() {
+    int a = 0;
+    {
+        int b = 1;
+        a = b;
+    }
+    {
+        int c = 2;
+        a = a + c;
+    }
+    return a;
+}
@@ -0,0 +1,8 @@
+// exit: 5
+int main() {
+    if (5) {
+        int i = 0;
+        return 5 + i;
+    }
+    return 0;
+}
@@ -0,0 +1,18 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #16
+mov w0, #0
+str w0, [sp, #12]
+mov w0, #5
+str w0, [sp, #0]
+ldr w0, [sp, #12]
+ldr w1, [sp, #0]
+add w0, w1, w0
+b Lmain_return
+mov w0, #0
+Lmain_return:
+add sp, sp, #16
+ret
@@ -0,0 +1,10 @@
+// exit: 3
+// xfail: variables in nested blocks share the function scope in the codegen
+int main() {
+    int a = 2;
+    {
+        a = 3;
+        int a = 0;
+    }
+    return a;
+}
@@ -0,0 +1,12 @@
+// exit: 3
+int main() {
+    int a = 2;
+    if (a < 3) {
+        {
+            int b = 3;
+            return b;
+        }
+        return a;
+    }
+    return 0;
+}
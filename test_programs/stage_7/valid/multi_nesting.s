@@ -0,0 +1,23 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #32
+mov w0, #2
+str w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #3
+ldr w1, [sp, #16]
+cmp w1, w0
+bge Lmain_if_end_0
+mov w0, #3
+str w0, [sp, #12]
+b Lmain_return
+ldr w0, [sp, #28]
+b Lmain_return
+Lmain_if_end_0:
+mov w0, #0
+Lmain_return:
+add sp, sp, #32
+ret
@@ -0,0 +1,16 @@
+// exit: 1
+int main() {
+    int a = 0;
+    if (a) {
+        int b = 2;
+        return b;
+    } else {
+        int c = 3;
+        if (a < c) {
+            return !a;
+        } else {
+            return 5;
+        }
+    }
+    return a;
+}
@@ -0,0 +1,36 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #32
+mov w0, #0
+str w0, [sp, #28]
+cbz w0, Lmain_if_else_0
+mov w0, #2
+str w0, [sp, #24]
+b Lmain_return
+b Lmain_if_end_0
+Lmain_if_else_0:
+mov w0, #3
+str w0, [sp, #20]
+ldr w0, [sp, #28]
+str w0, [sp, #8]
+ldr w0, [sp, #20]
+ldr w1, [sp, #8]
+cmp w1, w0
+bge Lmain_if_else_1
+ldr w0, [sp, #28]
+cmp w0, #0
+cset w0, eq
+b Lmain_return
+b Lmain_if_end_1
+Lmain_if_else_1:
+mov w0, #5
+b Lmain_return
+Lmain_if_end_1:
+Lmain_if_end_0:
+ldr w0, [sp, #28]
+Lmain_return:
+add sp, sp, #32
+ret
@@ -0,0 +1,11 @@
+// exit: 4
+// xfail: variables in nested blocks share the function scope in the codegen
+int main() {
+    int a = 2;
+    int b = 3;
+    {
+        int a = 1;
+        b = b + a;
+    }
+    return b;
+}
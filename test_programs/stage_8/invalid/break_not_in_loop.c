@@ -0,0 +1,5 @@
+// error: codegen
+int main() {
+    break;
+    return 0;
+}
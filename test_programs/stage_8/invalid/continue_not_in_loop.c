@@ -0,0 +1,4 @@
+// error: codegen
+int main() {
+    continue;
+}
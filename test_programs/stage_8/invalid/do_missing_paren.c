@@ -0,0 +1,5 @@
+// error: parse
+int main() {
+    do 3;
+    while 4;
+}
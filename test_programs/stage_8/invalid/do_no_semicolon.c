@@ -0,0 +1,7 @@
+// error: parse
+int main() {
+    do {
+        3;
+    } while (4)
+    return 1;
+}
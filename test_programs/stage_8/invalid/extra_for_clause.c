@@ -0,0 +1,6 @@
+// error: parse
+int main() {
+    for (int i = 2; i < 3; 3; i = i + 1)
+        3;
+    return 0;
+}
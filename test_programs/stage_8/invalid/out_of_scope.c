@@ -0,0 +1,7 @@
+// error: codegen
+// xfail: variables in nested blocks share the function scope in the codegen
+int main() {
+    for (int i = 0; i < 3; i = i + 1)
+        ;
+    return i;
+}
@@ -0,0 +1,6 @@
+// error: parse
+int main() {
+    while 1 {
+        return 0;
+    }
+}
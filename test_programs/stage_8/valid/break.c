@@ -0,0 +1,10 @@
+// exit: 6
+int main() {
+    int sum = 0;
+    for (int i = 0; i < 10; i = i + 1) {
+        sum = sum + i;
+        if (sum > 4)
+            break;
+    }
+    return sum;
+}
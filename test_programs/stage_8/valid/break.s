@@ -0,0 +1,43 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #48
+mov w0, #0
+str w0, [sp, #44]
+mov w0, #0
+str w0, [sp, #40]
+Lmain_for_start_0:
+ldr w0, [sp, #40]
+str w0, [sp, #32]
+mov w0, #10
+ldr w1, [sp, #32]
+cmp w1, w0
+bge Lmain_for_end_0
+ldr w0, [sp, #44]
+str w0, [sp, #32]
+ldr w0, [sp, #40]
+ldr w1, [sp, #32]
+add w0, w1, w0
+str w0, [sp, #44]
+str w0, [sp, #32]
+mov w0, #4
+ldr w1, [sp, #32]
+cmp w1, w0
+ble Lmain_if_end_1
+b Lmain_for_end_0
+Lmain_if_end_1:
+Lmain_for_continue_0:
+ldr w0, [sp, #40]
+str w0, [sp, #32]
+mov w0, #1
+ldr w1, [sp, #32]
+add w0, w1, w0
+str w0, [sp, #40]
+b Lmain_for_start_0
+Lmain_for_end_0:
+ldr w0, [sp, #44]
+Lmain_return:
+add sp, sp, #48
+ret
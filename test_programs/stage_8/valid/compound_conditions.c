@@ -0,0 +1,19 @@
+// exit: 39
+int main() {
+    int count = 0;
+    int i;
+    for (i = 0; i < 10 && !(i == 7); i = i + 1) {
+        if (i % 2 == 0 || i > 4)
+            count = count + 1;
+        if (!(i < 3) && i != 5)
+            count = count + 10;
+    }
+    do {
+        count = count + 1;
+    } while (count < 37 || 0);
+    double d = 1.5;
+    count = count + (d >= 1.5 && d < 2.0 ? 2 : 100);
+    while (count > 100 || !count)
+        count = 0;
+    return count;
+}
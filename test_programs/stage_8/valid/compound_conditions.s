@@ -0,0 +1,138 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #144
+mov w0, #0
+str w0, [sp, #140]
+mov w0, #0
+str w0, [sp, #136]
+mov w0, #0
+str w0, [sp, #136]
+Lmain_for_start_0:
+ldr w0, [sp, #136]
+str w0, [sp, #128]
+mov w0, #10
+ldr w1, [sp, #128]
+cmp w1, w0
+bge Lmain_for_end_0
+ldr w0, [sp, #136]
+str w0, [sp, #128]
+mov w0, #7
+ldr w1, [sp, #128]
+cmp w1, w0
+beq Lmain_for_end_0
+ldr w0, [sp, #136]
+str w0, [sp, #128]
+mov w0, #2
+ldr w1, [sp, #128]
+sdiv w2, w1, w0
+msub w0, w2, w0, w1
+str w0, [sp, #128]
+mov w0, #0
+ldr w1, [sp, #128]
+cmp w1, w0
+beq Lmain_or_skip_2
+ldr w0, [sp, #136]
+str w0, [sp, #128]
+mov w0, #4
+ldr w1, [sp, #128]
+cmp w1, w0
+ble Lmain_if_end_1
+Lmain_or_skip_2:
+ldr w0, [sp, #140]
+str w0, [sp, #128]
+mov w0, #1
+ldr w1, [sp, #128]
+add w0, w1, w0
+str w0, [sp, #140]
+Lmain_if_end_1:
+ldr w0, [sp, #136]
+str w0, [sp, #128]
+mov w0, #3
+ldr w1, [sp, #128]
+cmp w1, w0
+blt Lmain_if_end_3
+ldr w0, [sp, #136]
+str w0, [sp, #128]
+mov w0, #5
+ldr w1, [sp, #128]
+cmp w1, w0
+beq Lmain_if_end_3
+ldr w0, [sp, #140]
+str w0, [sp, #128]
+mov w0, #10
+ldr w1, [sp, #128]
+add w0, w1, w0
+str w0, [sp, #140]
+Lmain_if_end_3:
+Lmain_for_continue_0:
+ldr w0, [sp, #136]
+str w0, [sp, #128]
+mov w0, #1
+ldr w1, [sp, #128]
+add w0, w1, w0
+str w0, [sp, #136]
+b Lmain_for_start_0
+Lmain_for_end_0:
+Lmain_do_start_4:
+ldr w0, [sp, #140]
+str w0, [sp, #128]
+mov w0, #1
+ldr w1, [sp, #128]
+add w0, w1, w0
+str w0, [sp, #140]
+Lmain_do_continue_4:
+ldr w0, [sp, #140]
+str w0, [sp, #128]
+mov w0, #37
+ldr w1, [sp, #128]
+cmp w1, w0
+blt Lmain_do_start_4
+Lmain_do_end_4:
+movz x16, #16376, lsl #48
+fmov d0, x16
+str d0, [sp, #32]
+ldr w0, [sp, #140]
+str w0, [sp, #128]
+ldr d0, [sp, #32]
+str d0, [sp, #120]
+movz x16, #16376, lsl #48
+fmov d0, x16
+ldr d1, [sp, #120]
+fcmp d1, d0
+blt Lmain_ternary_else_5
+ldr d0, [sp, #32]
+str d0, [sp, #120]
+movz x16, #16384, lsl #48
+fmov d0, x16
+ldr d1, [sp, #120]
+fcmp d1, d0
+bpl Lmain_ternary_else_5
+mov w0, #2
+b Lmain_ternary_end_5
+Lmain_ternary_else_5:
+mov w0, #100
+Lmain_ternary_end_5:
+ldr w1, [sp, #128]
+add w0, w1, w0
+str w0, [sp, #140]
+Lmain_while_start_6:
+ldr w0, [sp, #140]
+str w0, [sp, #128]
+mov w0, #100
+ldr w1, [sp, #128]
+cmp w1, w0
+bgt Lmain_or_skip_7
+ldr w0, [sp, #140]
+cbnz w0, Lmain_while_end_6
+Lmain_or_skip_7:
+mov w0, #0
+str w0, [sp, #140]
+b Lmain_while_start_6
+Lmain_while_end_6:
+ldr w0, [sp, #140]
+Lmain_return:
+add sp, sp, #144
+ret
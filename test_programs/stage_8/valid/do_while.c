@@ -0,0 +1,8 @@
+// exit: 16
+int main() {
+    int a = 1;
+    do {
+        a = a * 2;
+    } while (a < 11);
+    return a;
+}
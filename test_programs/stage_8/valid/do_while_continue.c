@@ -0,0 +1,12 @@
+// exit: 6
+int main() {
+    int a = 0;
+    int b = 0;
+    do {
+        a = a + 1;
+        if (a < 3)
+            continue;
+        b = b + 1;
+    } while (a < 8);
+    return b;
+}
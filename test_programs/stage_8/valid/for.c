@@ -0,0 +1,7 @@
+// exit: 3
+int main() {
+    int a = 0;
+    for (a = 0; a < 3; a = a + 1)
+        a = a * 2;
+    return a;
+}
@@ -0,0 +1,36 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #32
+mov w0, #0
+str w0, [sp, #28]
+mov w0, #0
+str w0, [sp, #24]
+Lmain_for_start_0:
+ldr w0, [sp, #24]
+str w0, [sp, #16]
+mov w0, #3
+ldr w1, [sp, #16]
+cmp w1, w0
+bge Lmain_for_end_0
+ldr w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #1
+ldr w1, [sp, #16]
+add w0, w1, w0
+str w0, [sp, #28]
+Lmain_for_continue_0:
+ldr w0, [sp, #24]
+str w0, [sp, #16]
+mov w0, #1
+ldr w1, [sp, #16]
+add w0, w1, w0
+str w0, [sp, #24]
+b Lmain_for_start_0
+Lmain_for_end_0:
+ldr w0, [sp, #28]
+Lmain_return:
+add sp, sp, #32
+ret
@@ -0,0 +1,10 @@
+// exit: 33
+// xfail: the comma operator is not supported
+int main() {
+    int i = 0;
+    int j = 0;
+    for (i = 0, j = 0; i * j < 10; i = i + 1) {
+        j = j + 1;
+    }
+    return i + j * 10;
+}
@@ -0,0 +1,72 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #80
+mov w0, #0
+str w0, [sp, #76]
+mov w0, #0
+str w0, [sp, #72]
+Lmain_for_start_0:
+ldr w0, [sp, #72]
+str w0, [sp, #64]
+mov w0, #10
+ldr w1, [sp, #64]
+cmp w1, w0
+bge Lmain_for_end_0
+mov w0, #0
+str w0, [sp, #52]
+Lmain_for_start_1:
+ldr w0, [sp, #52]
+str w0, [sp, #64]
+mov w0, #10
+ldr w1, [sp, #64]
+cmp w1, w0
+bge Lmain_for_end_1
+ldr w0, [sp, #72]
+str w0, [sp, #64]
+mov w0, #2
+ldr w1, [sp, #64]
+sdiv w0, w1, w0
+str w0, [sp, #64]
+mov w0, #2
+ldr w1, [sp, #64]
+mul w0, w1, w0
+str w0, [sp, #64]
+ldr w0, [sp, #72]
+ldr w1, [sp, #64]
+cmp w1, w0
+bne Lmain_if_else_2
+b Lmain_for_end_1
+b Lmain_if_end_2
+Lmain_if_else_2:
+ldr w0, [sp, #76]
+str w0, [sp, #64]
+ldr w0, [sp, #72]
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #76]
+Lmain_if_end_2:
+Lmain_for_continue_1:
+ldr w0, [sp, #52]
+str w0, [sp, #64]
+mov w0, #1
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #52]
+b Lmain_for_start_1
+Lmain_for_end_1:
+Lmain_for_continue_0:
+ldr w0, [sp, #72]
+str w0, [sp, #64]
+mov w0, #1
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #72]
+b Lmain_for_start_0
+Lmain_for_end_0:
+ldr w0, [sp, #76]
+Lmain_return:
+add sp, sp, #80
+ret
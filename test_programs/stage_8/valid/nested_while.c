@@ -0,0 +1,14 @@
+// exit: 16
+int main() {
+    int a = 0;
+    int b = 0;
+    while (a < 4) {
+        int c = 0;
+        while (c < 4) {
+            c = c + 1;
+            b = b + 1;
+        }
+        a = a + 1;
+    }
+    return b;
+}
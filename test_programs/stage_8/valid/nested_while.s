@@ -0,0 +1,52 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+sub sp, sp, #64
+mov w0, #0
+str w0, [sp, #60]
+mov w0, #0
+str w0, [sp, #56]
+Lmain_while_start_0:
+ldr w0, [sp, #60]
+str w0, [sp, #48]
+mov w0, #4
+ldr w1, [sp, #48]
+cmp w1, w0
+bge Lmain_while_end_0
+mov w0, #0
+str w0, [sp, #44]
+Lmain_while_start_1:
+ldr w0, [sp, #44]
+str w0, [sp, #48]
+mov w0, #4
+ldr w1, [sp, #48]
+cmp w1, w0
+bge Lmain_while_end_1
+ldr w0, [sp, #44]
+str w0, [sp, #48]
+mov w0, #1
+ldr w1, [sp, #48]
+add w0, w1, w0
+str w0, [sp, #44]
+ldr w0, [sp, #56]
+str w0, [sp, #48]
+mov w0, #1
+ldr w1, [sp, #48]
+add w0, w1, w0
+str w0, [sp, #56]
+b Lmain_while_start_1
+Lmain_while_end_1:
+ldr w0, [sp, #60]
+str w0, [sp, #48]
+mov w0, #1
+ldr w1, [sp, #48]
+add w0, w1, w0
+str w0, [sp, #60]
+b Lmain_while_start_0
+Lmain_while_end_0:
+ldr w0, [sp, #56]
+Lmain_return:
+add sp, sp, #64
+ret
@@ -0,0 +1,6 @@
+// exit: 2
+int main() {
+    while (1) {
+        return 2;
+    }
+}
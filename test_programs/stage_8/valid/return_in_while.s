@@ -0,0 +1,12 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+Lmain_while_start_0:
+mov w0, #2
+b Lmain_return
+b Lmain_while_start_0
+Lmain_while_end_0:
+Lmain_return:
+ret
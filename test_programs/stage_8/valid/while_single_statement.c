@@ -0,0 +1,7 @@
+// exit: 6
+int main() {
+    int a = 0;
+    while (a < 5)
+        a = a + 2;
+    return a;
+}
@@ -0,0 +1,8 @@
+// error: semantic
+int foo(int a) {
+    return 3 + a;
+}
+
+int main() {
+    return foo();
+}
@@ -0,0 +1,12 @@
+// error: semantic
+int foo() {
+    return 3;
+}
+
+int main() {
+    return foo();
+}
+
+int foo() {
+    return 4;
+}
@@ -0,0 +1,8 @@
+// error: semantic
+int f(int a, int b, int c, int d, int e, int f, int g, int h, int i) {
+    return i;
+}
+
+int main() {
+    return f(1, 2, 3, 4, 5, 6, 7, 8, 9);
+}
@@ -0,0 +1,8 @@
+// error: parse
+int foo(int a, int b) {
+    return a;
+}
+
+int main() {
+    return foo(1, 2,);
+}
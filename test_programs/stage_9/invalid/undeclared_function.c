@@ -0,0 +1,8 @@
+// error: semantic
+int main() {
+    return foo(1);
+}
+
+int foo(int a) {
+    return a;
+}
@@ -0,0 +1,8 @@
+// error: semantic
+int foo(int) {
+    return 1;
+}
+
+int main() {
+    return foo(1);
+}
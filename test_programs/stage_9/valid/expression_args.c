@@ -0,0 +1,8 @@
+// exit: 4
+int sub_3(int x, int y, int z) {
+    return x - y - z;
+}
+
+int main() {
+    return sub_3(2 + 2, 1, -1);
+}
@@ -0,0 +1,13 @@
+// exit: 8
+int fib(int n) {
+    if (n == 0 || n == 1) {
+        return n;
+    } else {
+        return fib(n - 1) + fib(n - 2);
+    }
+}
+
+int main() {
+    int n = 6;
+    return fib(n);
+}
@@ -0,0 +1,61 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _fib
+.p2align 2
+_fib:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #64
+str w0, [sp, #60]
+str w0, [sp, #48]
+mov w0, #0
+ldr w1, [sp, #48]
+cmp w1, w0
+beq Lfib_or_skip_1
+ldr w0, [sp, #60]
+str w0, [sp, #48]
+mov w0, #1
+ldr w1, [sp, #48]
+cmp w1, w0
+bne Lfib_if_else_0
+Lfib_or_skip_1:
+ldr w0, [sp, #60]
+b Lfib_return
+b Lfib_if_end_0
+Lfib_if_else_0:
+ldr w0, [sp, #60]
+str w0, [sp, #48]
+mov w0, #1
+ldr w1, [sp, #48]
+sub w0, w1, w0
+str w0, [sp, #48]
+bl _fib
+str w0, [sp, #48]
+ldr w0, [sp, #60]
+str w0, [sp, #40]
+mov w0, #2
+ldr w1, [sp, #40]
+sub w0, w1, w0
+str w0, [sp, #40]
+bl _fib
+ldr w1, [sp, #48]
+add w0, w1, w0
+Lfib_if_end_0:
+Lfib_return:
+add sp, sp, #64
+ldp x29, x30, [sp], #16
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #16
+mov w0, #6
+str w0, [sp, #12]
+str w0, [sp, #0]
+bl _fib
+Lmain_return:
+add sp, sp, #16
+ldp x29, x30, [sp], #16
+ret
@@ -0,0 +1,10 @@
+// exit: 3
+int foo();
+
+int main() {
+    return foo();
+}
+
+int foo() {
+    return 3;
+}
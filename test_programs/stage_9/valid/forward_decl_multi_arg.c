@@ -0,0 +1,10 @@
+// exit: 1
+int foo(int, int);
+
+int main() {
+    return foo(2, 1);
+}
+
+int foo(int x, int y) {
+    return x - y;
+}
@@ -0,0 +1,33 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #16
+mov w0, #2
+str w0, [sp, #8]
+mov w0, #1
+str w0, [sp, #0]
+ldr w0, [sp, #8]
+ldr w1, [sp, #0]
+bl _foo
+Lmain_return:
+add sp, sp, #16
+ldp x29, x30, [sp], #16
+ret
+.globl _foo
+.p2align 2
+_foo:
+sub sp, sp, #16
+str w0, [sp, #12]
+str w1, [sp, #8]
+ldr w0, [sp, #12]
+str w0, [sp, #0]
+ldr w0, [sp, #8]
+ldr w1, [sp, #0]
+sub w0, w1, w0
+Lfoo_return:
+add sp, sp, #16
+ret
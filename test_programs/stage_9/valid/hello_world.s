@@ -0,0 +1,55 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #112
+mov w0, #72
+str w0, [sp, #104]
+bl _putchar
+mov w0, #101
+str w0, [sp, #104]
+bl _putchar
+mov w0, #108
+str w0, [sp, #104]
+bl _putchar
+mov w0, #108
+str w0, [sp, #104]
+bl _putchar
+mov w0, #111
+str w0, [sp, #104]
+bl _putchar
+mov w0, #44
+str w0, [sp, #104]
+bl _putchar
+mov w0, #32
+str w0, [sp, #104]
+bl _putchar
+mov w0, #87
+str w0, [sp, #104]
+bl _putchar
+mov w0, #111
+str w0, [sp, #104]
+bl _putchar
+mov w0, #114
+str w0, [sp, #104]
+bl _putchar
+mov w0, #108
+str w0, [sp, #104]
+bl _putchar
+mov w0, #100
+str w0, [sp, #104]
+bl _putchar
+mov w0, #33
+str w0, [sp, #104]
+bl _putchar
+mov w0, #10
+str w0, [sp, #104]
+bl _putchar
+mov w0, #0
+Lmain_return:
+add sp, sp, #112
+ldp x29, x30, [sp], #16
+ret
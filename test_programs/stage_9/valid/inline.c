@@ -0,0 +1,25 @@
+// exit: 30
+inline int abs_value(int a) {
+    if (a < 0)
+        return -a;
+    return a;
+}
+
+int sum_to(int n) {
+    int sum = 0;
+    for (int i = 1; i <= n; i = i + 1)
+        sum = sum + i;
+    return sum;
+}
+
+int twice(int a) {
+    int b = a;
+    return b + a;
+}
+
+int main() {
+    int a = 0;
+    for (int i = -3; i < 3; i = i + 1)
+        a = a + abs_value(i);
+    return twice(a) + sum_to(4) + abs_value(-2);
+}
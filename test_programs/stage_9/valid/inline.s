@@ -0,0 +1,121 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _abs_value
+.p2align 2
+_abs_value:
+sub sp, sp, #16
+str w0, [sp, #12]
+str w0, [sp, #0]
+mov w0, #0
+ldr w1, [sp, #0]
+cmp w1, w0
+bge Labs_value_if_end_0
+ldr w0, [sp, #12]
+neg w0, w0
+b Labs_value_return
+Labs_value_if_end_0:
+ldr w0, [sp, #12]
+Labs_value_return:
+add sp, sp, #16
+ret
+.globl _sum_to
+.p2align 2
+_sum_to:
+sub sp, sp, #48
+str w0, [sp, #44]
+mov w0, #0
+str w0, [sp, #40]
+mov w0, #1
+str w0, [sp, #36]
+Lsum_to_for_start_0:
+ldr w0, [sp, #36]
+str w0, [sp, #24]
+ldr w0, [sp, #44]
+ldr w1, [sp, #24]
+cmp w1, w0
+bgt Lsum_to_for_end_0
+ldr w0, [sp, #40]
+str w0, [sp, #24]
+ldr w0, [sp, #36]
+ldr w1, [sp, #24]
+add w0, w1, w0
+str w0, [sp, #40]
+Lsum_to_for_continue_0:
+ldr w0, [sp, #36]
+str w0, [sp, #24]
+mov w0, #1
+ldr w1, [sp, #24]
+add w0, w1, w0
+str w0, [sp, #36]
+b Lsum_to_for_start_0
+Lsum_to_for_end_0:
+ldr w0, [sp, #40]
+Lsum_to_return:
+add sp, sp, #48
+ret
+.globl _twice
+.p2align 2
+_twice:
+sub sp, sp, #16
+str w0, [sp, #12]
+str w0, [sp, #8]
+str w0, [sp, #0]
+ldr w0, [sp, #12]
+ldr w1, [sp, #0]
+add w0, w1, w0
+Ltwice_return:
+add sp, sp, #16
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #80
+mov w0, #0
+str w0, [sp, #76]
+mov w0, #-3
+str w0, [sp, #72]
+Lmain_for_start_0:
+ldr w0, [sp, #72]
+str w0, [sp, #64]
+mov w0, #3
+ldr w1, [sp, #64]
+cmp w1, w0
+bge Lmain_for_end_0
+ldr w0, [sp, #76]
+str w0, [sp, #64]
+ldr w0, [sp, #72]
+str w0, [sp, #56]
+bl _abs_value
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #76]
+Lmain_for_continue_0:
+ldr w0, [sp, #72]
+str w0, [sp, #64]
+mov w0, #1
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #72]
+b Lmain_for_start_0
+Lmain_for_end_0:
+ldr w0, [sp, #76]
+str w0, [sp, #64]
+bl _twice
+str w0, [sp, #64]
+mov w0, #4
+str w0, [sp, #56]
+bl _sum_to
+ldr w1, [sp, #64]
+add w0, w1, w0
+str w0, [sp, #64]
+mov w0, #-2
+str w0, [sp, #56]
+bl _abs_value
+ldr w1, [sp, #64]
+add w0, w1, w0
+Lmain_return:
+add sp, sp, #80
+ldp x29, x30, [sp], #16
+ret
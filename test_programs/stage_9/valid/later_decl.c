@@ -0,0 +1,10 @@
+// exit: 5
+int foo(int a) {
+    return a + 1;
+}
+
+int main() {
+    return foo(4);
+}
+
+int foo(int a);
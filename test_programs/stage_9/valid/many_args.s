@@ -0,0 +1,81 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _sum
+.p2align 2
+_sum:
+sub sp, sp, #96
+str w0, [sp, #92]
+str w1, [sp, #88]
+str w2, [sp, #84]
+str w3, [sp, #80]
+str w4, [sp, #76]
+str w5, [sp, #72]
+str w6, [sp, #68]
+str w7, [sp, #64]
+ldr w0, [sp, #92]
+str w0, [sp, #56]
+ldr w0, [sp, #88]
+ldr w1, [sp, #56]
+add w0, w1, w0
+str w0, [sp, #56]
+ldr w0, [sp, #84]
+ldr w1, [sp, #56]
+add w0, w1, w0
+str w0, [sp, #56]
+ldr w0, [sp, #80]
+ldr w1, [sp, #56]
+add w0, w1, w0
+str w0, [sp, #56]
+ldr w0, [sp, #76]
+ldr w1, [sp, #56]
+add w0, w1, w0
+str w0, [sp, #56]
+ldr w0, [sp, #72]
+ldr w1, [sp, #56]
+add w0, w1, w0
+str w0, [sp, #56]
+ldr w0, [sp, #68]
+ldr w1, [sp, #56]
+add w0, w1, w0
+str w0, [sp, #56]
+ldr w0, [sp, #64]
+ldr w1, [sp, #56]
+add w0, w1, w0
+Lsum_return:
+add sp, sp, #96
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #64
+mov w0, #1
+str w0, [sp, #56]
+mov w0, #2
+str w0, [sp, #48]
+mov w0, #3
+str w0, [sp, #40]
+mov w0, #4
+str w0, [sp, #32]
+mov w0, #5
+str w0, [sp, #24]
+mov w0, #6
+str w0, [sp, #16]
+mov w0, #7
+str w0, [sp, #8]
+mov w0, #8
+str w0, [sp, #0]
+ldr w0, [sp, #56]
+ldr w1, [sp, #48]
+ldr w2, [sp, #40]
+ldr w3, [sp, #32]
+ldr w4, [sp, #24]
+ldr w5, [sp, #16]
+ldr w6, [sp, #8]
+ldr w7, [sp, #0]
+bl _sum
+Lmain_return:
+add sp, sp, #64
+ldp x29, x30, [sp], #16
+ret
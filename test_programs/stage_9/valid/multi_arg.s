@@ -0,0 +1,41 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _sub_3
+.p2align 2
+_sub_3:
+sub sp, sp, #32
+str w0, [sp, #28]
+str w1, [sp, #24]
+str w2, [sp, #20]
+ldr w0, [sp, #28]
+str w0, [sp, #8]
+ldr w0, [sp, #24]
+ldr w1, [sp, #8]
+sub w0, w1, w0
+str w0, [sp, #8]
+ldr w0, [sp, #20]
+ldr w1, [sp, #8]
+sub w0, w1, w0
+Lsub_3_return:
+add sp, sp, #32
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #32
+mov w0, #10
+str w0, [sp, #24]
+mov w0, #4
+str w0, [sp, #16]
+mov w0, #5
+str w0, [sp, #8]
+ldr w0, [sp, #24]
+ldr w1, [sp, #16]
+ldr w2, [sp, #8]
+bl _sub_3
+Lmain_return:
+add sp, sp, #32
+ldp x29, x30, [sp], #16
+ret
@@ -0,0 +1,75 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _is_even
+.p2align 2
+_is_even:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #32
+str w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #0
+ldr w1, [sp, #16]
+cmp w1, w0
+bne Lis_even_ternary_else_0
+mov w0, #1
+b Lis_even_ternary_end_0
+Lis_even_ternary_else_0:
+ldr w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #1
+ldr w1, [sp, #16]
+sub w0, w1, w0
+str w0, [sp, #16]
+bl _is_odd
+Lis_even_ternary_end_0:
+Lis_even_return:
+add sp, sp, #32
+ldp x29, x30, [sp], #16
+ret
+.globl _is_odd
+.p2align 2
+_is_odd:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #32
+str w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #0
+ldr w1, [sp, #16]
+cmp w1, w0
+bne Lis_odd_ternary_else_0
+mov w0, #0
+b Lis_odd_ternary_end_0
+Lis_odd_ternary_else_0:
+ldr w0, [sp, #28]
+str w0, [sp, #16]
+mov w0, #1
+ldr w1, [sp, #16]
+sub w0, w1, w0
+str w0, [sp, #16]
+bl _is_even
+Lis_odd_ternary_end_0:
+Lis_odd_return:
+add sp, sp, #32
+ldp x29, x30, [sp], #16
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #32
+mov w0, #10
+str w0, [sp, #24]
+bl _is_even
+str w0, [sp, #24]
+mov w0, #10
+str w0, [sp, #16]
+bl _is_odd
+ldr w1, [sp, #24]
+add w0, w1, w0
+Lmain_return:
+add sp, sp, #32
+ldp x29, x30, [sp], #16
+ret
@@ -0,0 +1,12 @@
+// exit: 11
+int twice(int a) {
+    return a * 2;
+}
+
+int add(int a, int b) {
+    return a + b;
+}
+
+int main() {
+    return add(twice(twice(2)), add(1, twice(1)));
+}
@@ -0,0 +1,57 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _twice
+.p2align 2
+_twice:
+sub sp, sp, #16
+str w0, [sp, #12]
+str w0, [sp, #0]
+mov w0, #2
+ldr w1, [sp, #0]
+mul w0, w1, w0
+Ltwice_return:
+add sp, sp, #16
+ret
+.globl _add
+.p2align 2
+_add:
+sub sp, sp, #16
+str w0, [sp, #12]
+str w1, [sp, #8]
+ldr w0, [sp, #12]
+str w0, [sp, #0]
+ldr w0, [sp, #8]
+ldr w1, [sp, #0]
+add w0, w1, w0
+Ladd_return:
+add sp, sp, #16
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #64
+mov w0, #2
+str w0, [sp, #56]
+bl _twice
+str w0, [sp, #56]
+bl _twice
+str w0, [sp, #56]
+mov w0, #1
+str w0, [sp, #48]
+mov w0, #1
+str w0, [sp, #40]
+bl _twice
+str w0, [sp, #40]
+ldr w0, [sp, #48]
+ldr w1, [sp, #40]
+bl _add
+str w0, [sp, #48]
+ldr w0, [sp, #56]
+ldr w1, [sp, #48]
+bl _add
+Lmain_return:
+add sp, sp, #64
+ldp x29, x30, [sp], #16
+ret
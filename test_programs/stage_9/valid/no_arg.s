@@ -0,0 +1,17 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _three
+.p2align 2
+_three:
+mov w0, #3
+Lthree_return:
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+bl _three
+Lmain_return:
+ldp x29, x30, [sp], #16
+ret
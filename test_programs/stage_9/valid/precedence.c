@@ -0,0 +1,8 @@
+// exit: 0
+int three() {
+    return 3;
+}
+
+int main() {
+    return !three();
+}
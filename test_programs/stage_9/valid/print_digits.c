@@ -0,0 +1,20 @@
+// exit: 0
+// stdout: 1
+// stdout: 7
+// stdout: 49
+// stdout: 343
+int putchar(int c);
+
+int print_number(int n) {
+    if (n >= 10)
+        print_number(n / 10);
+    return putchar(48 + n % 10);
+}
+
+int main() {
+    for (int i = 1; i < 1000; i = i * 7) {
+        print_number(i);
+        putchar(10);
+    }
+    return 0;
+}
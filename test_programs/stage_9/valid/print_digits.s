@@ -0,0 +1,73 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _print_number
+.p2align 2
+_print_number:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #64
+str w0, [sp, #60]
+str w0, [sp, #48]
+mov w0, #10
+ldr w1, [sp, #48]
+cmp w1, w0
+blt Lprint_number_if_end_0
+ldr w0, [sp, #60]
+str w0, [sp, #48]
+mov w0, #10
+ldr w1, [sp, #48]
+sdiv w0, w1, w0
+str w0, [sp, #48]
+bl _print_number
+Lprint_number_if_end_0:
+mov w0, #48
+str w0, [sp, #48]
+ldr w0, [sp, #60]
+str w0, [sp, #40]
+mov w0, #10
+ldr w1, [sp, #40]
+sdiv w2, w1, w0
+msub w0, w2, w0, w1
+ldr w1, [sp, #48]
+add w0, w1, w0
+str w0, [sp, #48]
+bl _putchar
+Lprint_number_return:
+add sp, sp, #64
+ldp x29, x30, [sp], #16
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #48
+mov w0, #1
+str w0, [sp, #44]
+Lmain_for_start_0:
+ldr w0, [sp, #44]
+str w0, [sp, #32]
+mov w0, #1000
+ldr w1, [sp, #32]
+cmp w1, w0
+bge Lmain_for_end_0
+ldr w0, [sp, #44]
+str w0, [sp, #32]
+bl _print_number
+mov w0, #10
+str w0, [sp, #32]
+bl _putchar
+Lmain_for_continue_0:
+ldr w0, [sp, #44]
+str w0, [sp, #32]
+mov w0, #7
+ldr w1, [sp, #32]
+mul w0, w1, w0
+str w0, [sp, #44]
+b Lmain_for_start_0
+Lmain_for_end_0:
+mov w0, #0
+Lmain_return:
+add sp, sp, #48
+ldp x29, x30, [sp], #16
+ret
@@ -0,0 +1,23 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #16
+mov w0, #3
+str w0, [sp, #8]
+bl _foo
+Lmain_return:
+add sp, sp, #16
+ldp x29, x30, [sp], #16
+ret
+.globl _foo
+.p2align 2
+_foo:
+sub sp, sp, #16
+str w0, [sp, #12]
+Lfoo_return:
+add sp, sp, #16
+ret
@@ -0,0 +1,8 @@
+// exit: 6
+int twice(int x) {
+    return 2 * x;
+}
+
+int main() {
+    return twice(3);
+}
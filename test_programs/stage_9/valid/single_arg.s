@@ -0,0 +1,28 @@
+.section __TEXT,__text,regular,pure_instructions
+.build_version macos, 13, 0 sdk_version 13, 3
+.globl _twice
+.p2align 2
+_twice:
+sub sp, sp, #16
+str w0, [sp, #12]
+mov w0, #2
+str w0, [sp, #0]
+ldr w0, [sp, #12]
+ldr w1, [sp, #0]
+mul w0, w1, w0
+Ltwice_return:
+add sp, sp, #16
+ret
+.globl _main
+.p2align 2
+_main:
+stp x29, x30, [sp, #-16]!
+mov x29, sp
+sub sp, sp, #16
+mov w0, #3
+str w0, [sp, #8]
+bl _twice
+Lmain_return:
+add sp, sp, #16
+ldp x29, x30, [sp], #16
+ret
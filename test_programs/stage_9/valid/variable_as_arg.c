@@ -0,0 +1,9 @@
+// exit: 2
+int x(int y) {
+    return y + 1;
+}
+
+int main() {
+    int a = x(1);
+    return a;
+}